@@ -7,7 +7,7 @@ use nalgebra::Point3;
 
 #[derive(Default)]
 pub struct EditorComponent {
-    label: &'static str,
+    label: String,
     value_x: String,
     value_y: String,
     value_z: String,
@@ -16,17 +16,20 @@ pub struct EditorComponent {
 #[derive(Debug, Clone)]
 pub enum Message {
     InternalEdit(usize, String),
+    Submit,
 }
 
 #[derive(Debug, Clone)]
 pub enum Action {
     Valid(Point3<f32>),
     Invalid,
+    /// Enter was pressed in one of the coordinates.
+    Submitted,
 }
 impl<'a> EditorComponent {
-    pub fn new(label: &'static str, twist_point: &Point3<f32>) -> Self {
+    pub fn new(label: impl Into<String>, twist_point: &Point3<f32>) -> Self {
         Self {
-            label,
+            label: label.into(),
             value_x: EditorComponent::edit_string(twist_point.x),
             value_y: EditorComponent::edit_string(twist_point.y),
             value_z: EditorComponent::edit_string(twist_point.z),
@@ -37,6 +40,7 @@ impl<'a> EditorComponent {
     pub fn update(&mut self, message: Message) -> Action {
         match message {
             Message::InternalEdit(component, input) => self.handle_update(component, input),
+            Message::Submit => Action::Submitted,
         }
     }
 
@@ -81,29 +85,34 @@ impl<'a> EditorComponent {
         }
     }
 
-    pub fn view<M>(&'a self, on_edit: &'a (impl Fn(Message) -> M + 'a)) -> Element<'a, M>
+    pub fn view<M>(&'a self, on_edit: impl Fn(Message) -> M + Clone + 'a) -> Element<'a, M>
     where
         M: Clone + 'a,
     {
+        let submit = on_edit(Message::Submit);
+        let (on_edit_x, on_edit_y, on_edit_z) = (on_edit.clone(), on_edit.clone(), on_edit);
         column!(
             row!(
-                self.label,
+                self.label.as_str(),
                 text_input("x", &self.value_x)
-                    .on_input(|input| on_edit(Message::InternalEdit(1, input)))
+                    .on_input(move |input| on_edit_x(Message::InternalEdit(1, input)))
+                    .on_submit(submit.clone())
                     .style(|theme, status| EditorComponent::get_style(
                         &self.value_x,
                         theme,
                         status
                     )),
                 text_input("y", &self.value_y)
-                    .on_input(|input| on_edit(Message::InternalEdit(2, input)))
+                    .on_input(move |input| on_edit_y(Message::InternalEdit(2, input)))
+                    .on_submit(submit.clone())
                     .style(|theme, status| EditorComponent::get_style(
                         &self.value_y,
                         theme,
                         status
                     )),
                 text_input("z", &self.value_z)
-                    .on_input(|input| on_edit(Message::InternalEdit(3, input)))
+                    .on_input(move |input| on_edit_z(Message::InternalEdit(3, input)))
+                    .on_submit(submit)
                     .style(|theme, status| EditorComponent::get_style(
                         &self.value_z,
                         theme,
@@ -1,4 +1,6 @@
 pub mod context_menu;
 pub mod context_menu_overlay;
 pub mod editor_component;
+pub mod loupe;
+pub mod pan_zoom;
 pub mod zoom_viewer;
@@ -0,0 +1,272 @@
+use iced::{
+    Border, Color, Element, Event, Length, Rectangle, Size, Transformation, Vector,
+    advanced::{
+        Clipboard, Layout, Shell, Widget,
+        layout::{Limits, Node},
+        overlay, renderer,
+        widget::{Operation, Tree, tree},
+    },
+    mouse::{self, Button, Cursor},
+};
+
+/// Magnifies its content around the cursor while a left button press taken by the content is
+/// held, e.g. while a handle is dragged. Unlike [`ZoomViewer`](crate::zoom_viewer::ZoomViewer)
+/// the whole content is magnified, overlays included.
+pub struct Loupe<'a, Message, Theme = iced::Theme, Renderer = iced::Renderer>
+where
+    Renderer: renderer::Renderer,
+{
+    content: Element<'a, Message, Theme, Renderer>,
+    zoom: f32,
+    size: f32,
+}
+
+impl<'a, Message, Theme, Renderer> Loupe<'a, Message, Theme, Renderer>
+where
+    Renderer: renderer::Renderer,
+{
+    pub fn new(content: impl Into<Element<'a, Message, Theme, Renderer>>) -> Self {
+        Loupe {
+            content: content.into(),
+            zoom: 4.0,
+            size: 200.0,
+        }
+    }
+
+    /// Magnification of the loupe, the loupe is not shown at 1 or below.
+    pub fn zoom(mut self, zoom: f32) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    /// Side of the square loupe in logical pixels.
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+}
+
+impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for Loupe<'a, Message, Theme, Renderer>
+where
+    Renderer: renderer::Renderer,
+{
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn layout(&mut self, tree: &mut Tree, renderer: &Renderer, limits: &Limits) -> Node {
+        self.content
+            .as_widget_mut()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+
+        let state = tree.state.downcast_ref::<State>();
+        let bounds = layout.bounds();
+        if !state.active || self.zoom <= 1.0 {
+            return;
+        }
+        let Some(center) = cursor.position_over(bounds) else {
+            return;
+        };
+        let Some(clip) = Rectangle::new(
+            center - Vector::new(self.size / 2.0, self.size / 2.0),
+            Size::new(self.size, self.size),
+        )
+        .intersection(&bounds)
+        .and_then(|clip| clip.intersection(viewport)) else {
+            return;
+        };
+
+        // the content point under the cursor stays in place, everything else moves away from it
+        let transformation = Transformation::translate(center.x, center.y)
+            * Transformation::scale(self.zoom)
+            * Transformation::translate(-center.x, -center.y);
+        let magnified_viewport = Rectangle::new(
+            center - Vector::new(clip.width, clip.height) * (0.5 / self.zoom),
+            Size::new(clip.width, clip.height) * (1.0 / self.zoom),
+        );
+        renderer.with_layer(clip, |renderer| {
+            renderer.fill_quad(
+                renderer::Quad {
+                    bounds: clip,
+                    ..renderer::Quad::default()
+                },
+                Color::BLACK,
+            );
+            renderer.with_transformation(transformation, |renderer| {
+                self.content.as_widget().draw(
+                    &tree.children[0],
+                    renderer,
+                    theme,
+                    style,
+                    layout,
+                    Cursor::Unavailable,
+                    &magnified_viewport,
+                );
+            });
+        });
+        // a layer of its own, the border would be under the content in the same layer
+        renderer.with_layer(clip, |renderer| {
+            renderer.fill_quad(
+                renderer::Quad {
+                    bounds: clip,
+                    border: Border {
+                        color: Color::from_rgba(0.95, 0.95, 0.95, 0.9),
+                        width: 1.0,
+                        radius: 0.0.into(),
+                    },
+                    ..renderer::Quad::default()
+                },
+                Color::TRANSPARENT,
+            );
+        });
+    }
+
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(&[&self.content]);
+    }
+
+    fn operate<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<()>,
+    ) {
+        self.content
+            .as_widget_mut()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn update(
+        &mut self,
+        tree: &mut Tree,
+        event: &Event,
+        layout: Layout<'_>,
+        cursor: Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget_mut().update(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        );
+
+        let state = tree.state.downcast_mut::<State>();
+        match event {
+            // only presses the content took, a click next to the handles shows nothing
+            Event::Mouse(mouse::Event::ButtonPressed(Button::Left))
+                if shell.is_event_captured() && cursor.is_over(layout.bounds()) =>
+            {
+                state.active = true;
+                shell.request_redraw();
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(Button::Left))
+                if std::mem::take(&mut state.active) =>
+            {
+                shell.request_redraw();
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) if state.active => {
+                shell.request_redraw();
+            }
+            _ => {}
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        let state = tree.state.downcast_ref::<State>();
+        if state.active && self.zoom > 1.0 && cursor.is_over(layout.bounds()) {
+            return mouse::Interaction::Crosshair;
+        }
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'b>,
+        renderer: &Renderer,
+        viewport: &Rectangle,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        self.content.as_widget_mut().overlay(
+            &mut tree.children[0],
+            layout,
+            renderer,
+            viewport,
+            translation,
+        )
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<Loupe<'a, Message, Theme, Renderer>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: 'a,
+    Renderer: 'a + renderer::Renderer,
+{
+    fn from(loupe: Loupe<'a, Message, Theme, Renderer>) -> Self {
+        Element::new(loupe)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct State {
+    /// A press taken by the content is held.
+    active: bool,
+}
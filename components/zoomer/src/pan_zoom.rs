@@ -0,0 +1,364 @@
+use iced::{
+    Element, Event, Length, Point, Rectangle, Size, Vector,
+    advanced::{
+        Clipboard, Layout, Shell, Widget,
+        layout::{self, Limits, Node},
+        overlay, renderer,
+        widget::{Operation, Tree, tree},
+    },
+    keyboard, mouse,
+    mouse::{Button, Cursor, ScrollDelta},
+    window,
+};
+
+/// Smallest zoom reached with the wheel.
+pub const MIN_ZOOM: f32 = 0.1;
+/// Largest zoom reached with the wheel, image pixels are 8 screen pixels wide.
+pub const MAX_ZOOM: f32 = 8.0;
+/// Zoom factor of one wheel step.
+const WHEEL_STEP: f32 = 1.2;
+/// Scrolled pixels counted as one wheel step, for touchpads.
+const PIXELS_PER_STEP: f32 = 50.0;
+
+/// Viewport showing its content scaled by `zoom` and moved by `offset`.
+///
+/// The wheel zooms around the cursor, the middle button or the left button with
+/// [`space_held`](Self::space_held) pans, `Ctrl+0` fits the content and `Ctrl+1` shows it at
+/// 100%. The content is laid out at its zoomed size, so widgets drawing relative to their
+/// bounds stay aligned at every zoom.
+pub struct PanZoom<'a, Message, Theme = iced::Theme, Renderer = iced::Renderer>
+where
+    Renderer: renderer::Renderer,
+{
+    content: Element<'a, Message, Theme, Renderer>,
+    content_size: Size,
+    zoom: f32,
+    offset: Vector,
+    space_held: bool,
+    on_change: Option<Box<dyn Fn(f32, Vector) -> Message + 'a>>,
+}
+
+impl<'a, Message, Theme, Renderer> PanZoom<'a, Message, Theme, Renderer>
+where
+    Renderer: renderer::Renderer,
+{
+    /// `content_size` is the size of the content at 100%.
+    pub fn new(
+        content: impl Into<Element<'a, Message, Theme, Renderer>>,
+        content_size: Size,
+        zoom: f32,
+        offset: Vector,
+    ) -> Self {
+        PanZoom {
+            content: content.into(),
+            content_size,
+            zoom,
+            offset,
+            space_held: false,
+            on_change: None,
+        }
+    }
+
+    /// Space is held and was not typed into a text field, the left button pans. Widgets
+    /// after this one see the key only after it, so the application tells.
+    pub fn space_held(mut self, space_held: bool) -> Self {
+        self.space_held = space_held;
+        self
+    }
+
+    /// Message with the new zoom and offset when the view is zoomed or panned.
+    pub fn on_change(mut self, on_change: impl Fn(f32, Vector) -> Message + 'a) -> Self {
+        self.on_change = Some(Box::new(on_change));
+        self
+    }
+
+    fn zoomed_size(&self, zoom: f32) -> Size {
+        self.content_size * zoom
+    }
+
+    /// Zoom and offset showing `zoom` with the content point under `anchor` kept in place.
+    fn zoom_at(&self, zoom: f32, anchor: Vector, viewport: Size) -> (f32, Vector) {
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let offset = clamp_offset(self.offset, self.zoomed_size(self.zoom), viewport);
+        let content_point = (anchor - offset) * (1.0 / self.zoom);
+        (
+            zoom,
+            clamp_offset(
+                anchor - content_point * zoom,
+                self.zoomed_size(zoom),
+                viewport,
+            ),
+        )
+    }
+}
+
+/// Keeps content larger than the viewport covering it and centers smaller content.
+pub fn clamp_offset(offset: Vector, content: Size, viewport: Size) -> Vector {
+    let clamp = |offset: f32, content: f32, viewport: f32| {
+        if content <= viewport {
+            (viewport - content) / 2.0
+        } else {
+            offset.clamp(viewport - content, 0.0)
+        }
+    };
+    Vector::new(
+        clamp(offset.x, content.width, viewport.width),
+        clamp(offset.y, content.height, viewport.height),
+    )
+}
+
+/// Zoom fitting `content` at 100% inside `viewport`.
+pub fn fit_zoom(content: Size, viewport: Size) -> f32 {
+    if content.width < 1.0 || content.height < 1.0 {
+        return 1.0;
+    }
+    (viewport.width / content.width)
+        .min(viewport.height / content.height)
+        .clamp(MIN_ZOOM, MAX_ZOOM)
+}
+
+impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for PanZoom<'a, Message, Theme, Renderer>
+where
+    Renderer: renderer::Renderer,
+{
+    fn size(&self) -> Size<Length> {
+        Size {
+            width: Length::Fill,
+            height: Length::Fill,
+        }
+    }
+
+    fn layout(&mut self, tree: &mut Tree, renderer: &Renderer, limits: &Limits) -> Node {
+        let size = limits.resolve(Length::Fill, Length::Fill, Size::ZERO);
+        let zoomed_size = self.zoomed_size(self.zoom);
+        let content = self.content.as_widget_mut().layout(
+            &mut tree.children[0],
+            renderer,
+            &Limits::new(Size::ZERO, zoomed_size),
+        );
+        let offset = clamp_offset(self.offset, zoomed_size, size);
+        layout::Node::with_children(size, vec![content.move_to(Point::new(offset.x, offset.y))])
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: Cursor,
+        viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+        let Some(visible) = bounds.intersection(viewport) else {
+            return;
+        };
+        let cursor = if cursor.is_over(visible) {
+            cursor
+        } else {
+            Cursor::Unavailable
+        };
+        renderer.with_layer(visible, |renderer| {
+            self.content.as_widget().draw(
+                &tree.children[0],
+                renderer,
+                theme,
+                style,
+                layout.children().next().unwrap(),
+                cursor,
+                &visible,
+            );
+        });
+    }
+
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(&[&self.content]);
+    }
+
+    fn operate<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<()>,
+    ) {
+        self.content.as_widget_mut().operate(
+            &mut tree.children[0],
+            layout.children().next().unwrap(),
+            renderer,
+            operation,
+        );
+    }
+
+    fn update(
+        &mut self,
+        tree: &mut Tree,
+        event: &Event,
+        layout: Layout<'_>,
+        cursor: Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+        let state = tree.state.downcast_mut::<State>();
+        let over = cursor.position_over(bounds);
+        let mut change = None;
+        match event {
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
+                match key.as_ref() {
+                    keyboard::Key::Character("0") if modifiers.command() => {
+                        let zoom = fit_zoom(self.content_size, bounds.size());
+                        change = Some((zoom, Vector::ZERO));
+                    }
+                    keyboard::Key::Character("1") if modifiers.command() => {
+                        let center = Vector::new(bounds.width / 2.0, bounds.height / 2.0);
+                        change = Some(self.zoom_at(1.0, center, bounds.size()));
+                    }
+                    _ => {}
+                }
+            }
+            // the release of the button is not seen outside the window
+            Event::Window(window::Event::Unfocused) => state.pan = None,
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+                if let Some(position) = over {
+                    let steps = match delta {
+                        ScrollDelta::Lines { y, .. } => *y,
+                        ScrollDelta::Pixels { y, .. } => *y / PIXELS_PER_STEP,
+                    };
+                    let anchor = position - bounds.position();
+                    change = Some(self.zoom_at(
+                        self.zoom * WHEEL_STEP.powf(steps),
+                        anchor,
+                        bounds.size(),
+                    ));
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(button)) => {
+                let pans =
+                    *button == Button::Middle || (*button == Button::Left && self.space_held);
+                if pans && let Some(position) = over {
+                    let offset =
+                        clamp_offset(self.offset, self.zoomed_size(self.zoom), bounds.size());
+                    state.pan = Some((position, offset));
+                    shell.capture_event();
+                    return;
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(Button::Middle | Button::Left))
+                if state.pan.is_some() =>
+            {
+                state.pan = None;
+                shell.capture_event();
+                return;
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                if let Some((start, offset)) = state.pan {
+                    change = Some((self.zoom, offset + (*position - start)));
+                }
+            }
+            _ => {}
+        }
+        if let Some((zoom, offset)) = change {
+            if let Some(on_change) = &self.on_change {
+                shell.publish(on_change(zoom, offset));
+            }
+            shell.capture_event();
+            return;
+        }
+
+        let cursor = if cursor.is_over(bounds) {
+            cursor
+        } else {
+            Cursor::Unavailable
+        };
+        self.content.as_widget_mut().update(
+            &mut tree.children[0],
+            event,
+            layout.children().next().unwrap(),
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            &bounds.intersection(viewport).unwrap_or(bounds),
+        );
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        let bounds = layout.bounds();
+        if !cursor.is_over(bounds) {
+            return mouse::Interaction::default();
+        }
+        let state = tree.state.downcast_ref::<State>();
+        if state.pan.is_some() {
+            return mouse::Interaction::Grabbing;
+        }
+        if self.space_held {
+            return mouse::Interaction::Grab;
+        }
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout.children().next().unwrap(),
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'b>,
+        renderer: &Renderer,
+        viewport: &Rectangle,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        self.content.as_widget_mut().overlay(
+            &mut tree.children[0],
+            layout.children().next().unwrap(),
+            renderer,
+            viewport,
+            translation,
+        )
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<PanZoom<'a, Message, Theme, Renderer>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: 'a,
+    Renderer: 'a + renderer::Renderer,
+{
+    fn from(pan_zoom: PanZoom<'a, Message, Theme, Renderer>) -> Self {
+        Element::new(pan_zoom)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct State {
+    /// Cursor position and offset when the pan started.
+    pan: Option<(Point, Vector)>,
+}
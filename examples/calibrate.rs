@@ -5,6 +5,7 @@ use iced::widget::image::Handle;
 use iced::widget::{Image, button, column, container, row, slider, text};
 use iced::{Alignment, Element, Length, Theme};
 use image::{DynamicImage, GenericImageView, ImageBuffer, Rgba};
+use perspective::texture::sample_bilinear;
 
 pub fn main() -> iced::Result {
     iced::application(
@@ -258,36 +259,3 @@ fn undistort_image(
 
     Ok(DynamicImage::ImageRgba8(out))
 }
-
-fn sample_bilinear(img: &ImageBuffer<Rgba<u8>, Vec<u8>>, fx: f32, fy: f32) -> Rgba<u8> {
-    let (w, h) = img.dimensions();
-    if fx < 0.0 || fy < 0.0 || fx >= w as f32 - 1.0 || fy >= h as f32 - 1.0 {
-        // outside, return black or nearest
-        let sx = fx.clamp(0.0, (w - 1) as f32) as u32;
-        let sy = fy.clamp(0.0, (h - 1) as f32) as u32;
-        return *img.get_pixel(sx, sy);
-    }
-
-    let x0 = fx.floor() as u32;
-    let y0 = fy.floor() as u32;
-    let x1 = x0 + 1;
-    let y1 = y0 + 1;
-
-    let dx = fx - x0 as f32;
-    let dy = fy - y0 as f32;
-
-    let p00 = img.get_pixel(x0, y0).0;
-    let p10 = img.get_pixel(x1, y0).0;
-    let p01 = img.get_pixel(x0, y1).0;
-    let p11 = img.get_pixel(x1, y1).0;
-
-    let mut out = [0u8; 4];
-    for i in 0..4 {
-        let v = (p00[i] as f32) * (1.0 - dx) * (1.0 - dy)
-            + (p10[i] as f32) * dx * (1.0 - dy)
-            + (p01[i] as f32) * (1.0 - dx) * dy
-            + (p11[i] as f32) * dx * dy;
-        out[i] = v.clamp(0.0, 255.0) as u8;
-    }
-    Rgba(out)
-}
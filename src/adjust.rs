@@ -0,0 +1,143 @@
+use image::{GrayImage, Luma, RgbaImage, imageops};
+use serde::{Deserialize, Serialize};
+
+/// Color of the edge overlay.
+const EDGE_COLOR: [f32; 3] = [0.0, 1.0, 1.0];
+/// Longest side of the copy adjusted while the adjustments change.
+const PREVIEW_SIZE: u32 = 1024;
+
+/// Changes to the photo shown in the canvas only, exports keep using the original photo.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DisplayAdjustments {
+    /// Brightness change in stops.
+    pub exposure: f32,
+    pub gamma: f32,
+    /// Contrast around mid grey, 1 keeps the photo contrast.
+    pub contrast: f32,
+    pub grayscale: bool,
+    /// Highlights the edges of the photo.
+    pub edges: bool,
+}
+
+impl Default for DisplayAdjustments {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            gamma: 1.0,
+            contrast: 1.0,
+            grayscale: false,
+            edges: false,
+        }
+    }
+}
+
+impl DisplayAdjustments {
+    /// The photo is shown as it is.
+    pub fn is_neutral(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Photo the display adjustments start from, with a smaller preview adjusted while the
+/// adjustments change. Edge maps are computed the first time edges are shown.
+pub struct DisplayPhoto {
+    photo: RgbaImage,
+    preview: RgbaImage,
+    edges: Option<GrayImage>,
+    preview_edges: Option<GrayImage>,
+}
+
+impl DisplayPhoto {
+    pub fn new(photo: RgbaImage) -> Self {
+        let (width, height) = photo.dimensions();
+        let scale = (PREVIEW_SIZE as f32 / width.max(height) as f32).min(1.0);
+        let preview = imageops::resize(
+            &photo,
+            ((width as f32 * scale).round() as u32).max(1),
+            ((height as f32 * scale).round() as u32).max(1),
+            imageops::FilterType::Triangle,
+        );
+        Self {
+            photo,
+            preview,
+            edges: None,
+            preview_edges: None,
+        }
+    }
+
+    /// The photo, or its preview, with `adjustments` applied.
+    pub fn adjust(&mut self, adjustments: &DisplayAdjustments, preview: bool) -> RgbaImage {
+        let (photo, edges) = if preview {
+            (&self.preview, &mut self.preview_edges)
+        } else {
+            (&self.photo, &mut self.edges)
+        };
+        if adjustments.edges && edges.is_none() {
+            *edges = Some(edge_map(photo));
+        }
+        adjust_image(photo, edges.as_ref(), adjustments)
+    }
+}
+
+/// Sobel gradient magnitude of the luminance of `photo`.
+pub fn edge_map(photo: &RgbaImage) -> GrayImage {
+    let luma = imageops::grayscale(photo);
+    let (width, height) = luma.dimensions();
+    GrayImage::from_fn(width, height, |x, y| {
+        let value = |dx: i32, dy: i32| {
+            let x = (x as i32 + dx).clamp(0, width as i32 - 1) as u32;
+            let y = (y as i32 + dy).clamp(0, height as i32 - 1) as u32;
+            luma.get_pixel(x, y).0[0] as f32
+        };
+        let gx = value(1, -1) + 2.0 * value(1, 0) + value(1, 1)
+            - value(-1, -1)
+            - 2.0 * value(-1, 0)
+            - value(-1, 1);
+        let gy = value(-1, 1) + 2.0 * value(0, 1) + value(1, 1)
+            - value(-1, -1)
+            - 2.0 * value(0, -1)
+            - value(1, -1);
+        Luma([((gx * gx + gy * gy).sqrt() / 4.0).min(255.0) as u8])
+    })
+}
+
+/// `photo` with the `adjustments` applied, `edges` is the [`edge_map`] of the photo and
+/// is only needed for the edge overlay.
+pub fn adjust_image(
+    photo: &RgbaImage,
+    edges: Option<&GrayImage>,
+    adjustments: &DisplayAdjustments,
+) -> RgbaImage {
+    let gain = 2f32.powf(adjustments.exposure);
+    let gamma = adjustments.gamma.max(0.01);
+    // the same curve for every channel, looked up instead of computed per pixel
+    let curve: Vec<f32> = (0..256)
+        .map(|value| {
+            let value = (value as f32 / 255.0 * gain).min(1.0).powf(1.0 / gamma);
+            ((value - 0.5) * adjustments.contrast + 0.5).clamp(0.0, 1.0)
+        })
+        .collect();
+
+    let mut adjusted = photo.clone();
+    adjusted.enumerate_pixels_mut().for_each(|(x, y, pixel)| {
+        let [r, g, b, _] = pixel.0;
+        let mut color = [curve[r as usize], curve[g as usize], curve[b as usize]];
+        if adjustments.grayscale {
+            let luminance = 0.2126 * color[0] + 0.7152 * color[1] + 0.0722 * color[2];
+            color = [luminance; 3];
+        }
+        if adjustments.edges
+            && let Some(edges) = edges
+        {
+            let strength = (edges.get_pixel(x, y).0[0] as f32 / 128.0).min(1.0);
+            color
+                .iter_mut()
+                .zip(EDGE_COLOR)
+                .for_each(|(channel, edge)| *channel += (edge - *channel) * strength);
+        }
+        for (channel, value) in pixel.0.iter_mut().zip(color) {
+            *channel = (value * 255.0).round() as u8;
+        }
+    });
+    adjusted
+}
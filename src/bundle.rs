@@ -0,0 +1,506 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use iced::{Point, Size};
+use nalgebra::{DMatrix, DVector, Matrix3, Matrix4, Point2, Point3, Rotation3, Vector2, Vector3};
+
+use crate::{compute::data::ComputeSolution, utils::relative_to_image_plane};
+
+/// Levenberg-Marquardt iterations before giving up on convergence.
+const MAX_ITERATIONS: usize = 100;
+
+/// A solved image taking part in the adjustment.
+pub struct BundleImage {
+    pub compute_solution: ComputeSolution<f32>,
+    pub image_size: Size<f32>,
+    /// Refines one field of view and principal point with the other sharing images,
+    /// the intrinsics of the remaining images stay as solved.
+    pub shared_intrinsics: bool,
+    /// Known world points (twist correspondences) with their image points, they stay fixed.
+    pub control_points: Vec<(Point3<f32>, Point2<f32>)>,
+    /// World point id and image point of every observation.
+    pub observations: Vec<(usize, Point)>,
+}
+
+/// Refined cameras and world points with the reprojection errors in pixels.
+#[derive(Clone)]
+pub struct BundleAdjustment {
+    /// One camera per image, in the order given.
+    pub cameras: Vec<ComputeSolution<f32>>,
+    pub points: BTreeMap<usize, Point3<f32>>,
+    /// Root mean square error over the control points and observations of each image.
+    pub image_residuals: Vec<Option<f32>>,
+    /// Error of each view of a world point, in the order of the images seeing it.
+    pub point_residuals: BTreeMap<usize, Vec<f32>>,
+    pub root_mean_square: f32,
+}
+
+/// Focal length and principal point in image plane coordinates.
+#[derive(Clone, Copy)]
+struct Intrinsics {
+    focal_length: f64,
+    principal_point: Vector2<f64>,
+}
+
+impl Intrinsics {
+    fn from_solution(compute_solution: &ComputeSolution<f32>) -> Self {
+        Self {
+            focal_length: 1.0 / (compute_solution.field_of_view() as f64 / 2.0).tan(),
+            principal_point: compute_solution.ortho_center().cast(),
+        }
+    }
+}
+
+/// Camera of one image: the solved view transform, refined by a rotation in camera space
+/// on top of it (so axis flips in the solution survive) and a free translation.
+struct Camera {
+    linear: Matrix3<f64>,
+    intrinsics: Intrinsics,
+    image_size: Size<f32>,
+}
+
+/// One image point of a fixed or free world point.
+struct Residual {
+    image: usize,
+    point: Target,
+    observed: Vector2<f64>,
+}
+
+enum Target {
+    Fixed(Vector3<f64>),
+    Free(usize),
+}
+
+struct Problem {
+    cameras: Vec<Camera>,
+    residuals: Vec<Residual>,
+    /// Index of the shared intrinsics in the parameters, after the cameras.
+    shared: Option<usize>,
+    sharing: Vec<bool>,
+}
+
+impl Problem {
+    fn point_offset(&self) -> usize {
+        self.cameras.len() * 6 + self.shared.map_or(0, |_| 3)
+    }
+
+    fn intrinsics(&self, image: usize, parameters: &DVector<f64>) -> Intrinsics {
+        match self.shared {
+            Some(offset) if self.sharing[image] => Intrinsics {
+                focal_length: parameters[offset],
+                principal_point: Vector2::new(parameters[offset + 1], parameters[offset + 2]),
+            },
+            _ => self.cameras[image].intrinsics,
+        }
+    }
+
+    fn view(&self, image: usize, parameters: &DVector<f64>) -> (Matrix3<f64>, Vector3<f64>) {
+        let offset = image * 6;
+        let rotation = Rotation3::new(Vector3::new(
+            parameters[offset],
+            parameters[offset + 1],
+            parameters[offset + 2],
+        ));
+        (
+            rotation.matrix() * self.cameras[image].linear,
+            Vector3::new(
+                parameters[offset + 3],
+                parameters[offset + 4],
+                parameters[offset + 5],
+            ),
+        )
+    }
+
+    /// Reprojection error in pixels, `None` behind the camera.
+    fn error(&self, residual: &Residual, parameters: &DVector<f64>) -> Option<Vector2<f64>> {
+        let world = match residual.point {
+            Target::Fixed(point) => point,
+            Target::Free(index) => {
+                let offset = self.point_offset() + index * 3;
+                Vector3::new(
+                    parameters[offset],
+                    parameters[offset + 1],
+                    parameters[offset + 2],
+                )
+            }
+        };
+        let (linear, translation) = self.view(residual.image, parameters);
+        let camera = linear * world + translation;
+        // the camera looks down -Z
+        if camera.z >= -f64::EPSILON {
+            return None;
+        }
+        let intrinsics = self.intrinsics(residual.image, parameters);
+        let projected =
+            intrinsics.principal_point + camera.xy() * (intrinsics.focal_length / -camera.z);
+        let pixels = self.cameras[residual.image].image_size.width as f64 / 2.0;
+        Some((projected - residual.observed) * pixels)
+    }
+
+    fn errors(&self, parameters: &DVector<f64>) -> DVector<f64> {
+        let mut errors = DVector::zeros(self.residuals.len() * 2);
+        self.residuals
+            .iter()
+            .enumerate()
+            .for_each(|(index, residual)| {
+                // a point behind the camera weighs like a far off projection
+                let error = self
+                    .error(residual, parameters)
+                    .unwrap_or_else(|| Vector2::repeat(1e4));
+                errors[index * 2] = error.x;
+                errors[index * 2 + 1] = error.y;
+            });
+        errors
+    }
+
+    /// Forward difference Jacobian of the errors.
+    fn jacobian(&self, parameters: &DVector<f64>, errors: &DVector<f64>) -> DMatrix<f64> {
+        let mut jacobian = DMatrix::zeros(errors.len(), parameters.len());
+        let mut shifted = parameters.clone();
+        for column in 0..parameters.len() {
+            let step = 1e-7 * (1.0 + parameters[column].abs());
+            shifted[column] += step;
+            let column_errors = (self.errors(&shifted) - errors) / step;
+            jacobian.set_column(column, &column_errors);
+            shifted[column] = parameters[column];
+        }
+        jacobian
+    }
+}
+
+/// Jointly refines the poses of all images, the shared intrinsics and the world `points`
+/// by minimizing the reprojection error of every observation and control point.
+pub fn bundle_adjust(
+    images: &[BundleImage],
+    points: &BTreeMap<usize, Point3<f32>>,
+) -> Result<BundleAdjustment> {
+    // only points seen by an image are free parameters
+    let ids: Vec<usize> = points
+        .keys()
+        .copied()
+        .filter(|id| {
+            images
+                .iter()
+                .any(|image| image.observations.iter().any(|(item, _)| item == id))
+        })
+        .collect();
+
+    let image_point = |image: &BundleImage, x: f32, y: f32| -> Vector2<f64> {
+        relative_to_image_plane(
+            image.image_size.width / image.image_size.height,
+            &Vector2::new(x, y),
+        )
+        .cast()
+    };
+    let mut residuals = Vec::new();
+    images.iter().enumerate().for_each(|(index, image)| {
+        image.control_points.iter().for_each(|(world, point)| {
+            residuals.push(Residual {
+                image: index,
+                point: Target::Fixed(world.coords.cast()),
+                observed: image_point(image, point.x, point.y),
+            });
+        });
+        image.observations.iter().for_each(|(id, point)| {
+            if let Some(free) = ids.iter().position(|item| item == id) {
+                residuals.push(Residual {
+                    image: index,
+                    point: Target::Free(free),
+                    observed: image_point(image, point.x, point.y),
+                });
+            }
+        });
+    });
+    if residuals.is_empty() {
+        return Err(anyhow::anyhow!(
+            "no control points or observed world points"
+        ));
+    }
+
+    let sharing: Vec<bool> = images.iter().map(|image| image.shared_intrinsics).collect();
+    let shared_start = images.iter().find(|image| image.shared_intrinsics);
+    let mut parameters = Vec::new();
+    let cameras: Vec<Camera> = images
+        .iter()
+        .map(|image| {
+            let view_transform = image.compute_solution.view_transform().cast::<f64>();
+            parameters.extend([0.0, 0.0, 0.0]);
+            parameters.extend(view_transform.fixed_view::<3, 1>(0, 3).iter());
+            Camera {
+                linear: view_transform.fixed_view::<3, 3>(0, 0).into_owned(),
+                intrinsics: Intrinsics::from_solution(&image.compute_solution),
+                image_size: image.image_size,
+            }
+        })
+        .collect();
+    let shared = shared_start.map(|image| {
+        let intrinsics = Intrinsics::from_solution(&image.compute_solution);
+        parameters.extend([
+            intrinsics.focal_length,
+            intrinsics.principal_point.x,
+            intrinsics.principal_point.y,
+        ]);
+        cameras.len() * 6
+    });
+    ids.iter().for_each(|id| {
+        parameters.extend(points[id].coords.cast::<f64>().iter());
+    });
+    let problem = Problem {
+        cameras,
+        residuals,
+        shared,
+        sharing,
+    };
+
+    let mut parameters = DVector::from_vec(parameters);
+    let mut errors = problem.errors(&parameters);
+    let mut cost = errors.norm_squared();
+    let mut damping = 1e-3;
+    for _ in 0..MAX_ITERATIONS {
+        let jacobian = problem.jacobian(&parameters, &errors);
+        let jtj = jacobian.transpose() * &jacobian;
+        let jtr = jacobian.transpose() * &errors;
+        let mut improved = false;
+        // raise the damping until a step lowers the cost
+        while damping < 1e10 {
+            let mut damped = jtj.clone();
+            for index in 0..damped.nrows() {
+                damped[(index, index)] += damping * (1.0 + jtj[(index, index)]);
+            }
+            let Some(step) = damped.cholesky().map(|cholesky| cholesky.solve(&jtr)) else {
+                damping *= 10.0;
+                continue;
+            };
+            let candidate = &parameters - &step;
+            let candidate_errors = problem.errors(&candidate);
+            let candidate_cost = candidate_errors.norm_squared();
+            if candidate_cost < cost {
+                let converged = cost - candidate_cost < 1e-12 * (1.0 + cost);
+                parameters = candidate;
+                errors = candidate_errors;
+                cost = candidate_cost;
+                damping = (damping / 10.0).max(1e-12);
+                improved = !converged;
+                break;
+            }
+            damping *= 10.0;
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    let cameras = (0..images.len())
+        .map(|image| {
+            let (linear, translation) = problem.view(image, &parameters);
+            let mut view_transform = Matrix4::identity();
+            view_transform
+                .fixed_view_mut::<3, 3>(0, 0)
+                .copy_from(&linear);
+            view_transform
+                .fixed_view_mut::<3, 1>(0, 3)
+                .copy_from(&translation);
+            let intrinsics = problem.intrinsics(image, &parameters);
+            ComputeSolution::new(
+                view_transform.cast(),
+                intrinsics.principal_point.cast(),
+                (2.0 * (1.0 / intrinsics.focal_length).atan()) as f32,
+            )
+        })
+        .collect();
+
+    let offset = problem.point_offset();
+    let points = ids
+        .iter()
+        .enumerate()
+        .map(|(index, id)| {
+            let at = offset + index * 3;
+            (
+                *id,
+                Point3::new(parameters[at], parameters[at + 1], parameters[at + 2]).cast(),
+            )
+        })
+        .collect();
+
+    let norms: Vec<f32> = problem
+        .residuals
+        .iter()
+        .map(|residual| {
+            problem
+                .error(residual, &parameters)
+                .map_or(f32::INFINITY, |error| error.norm() as f32)
+        })
+        .collect();
+    let image_residuals = (0..images.len())
+        .map(|image| {
+            root_mean_square(
+                problem
+                    .residuals
+                    .iter()
+                    .zip(&norms)
+                    .filter(|(residual, _)| residual.image == image)
+                    .map(|(_, norm)| *norm),
+            )
+        })
+        .collect();
+    let mut point_residuals: BTreeMap<usize, Vec<f32>> = BTreeMap::new();
+    problem
+        .residuals
+        .iter()
+        .zip(&norms)
+        .for_each(|(residual, norm)| {
+            if let Target::Free(index) = residual.point {
+                point_residuals.entry(ids[index]).or_default().push(*norm);
+            }
+        });
+
+    Ok(BundleAdjustment {
+        cameras,
+        points,
+        image_residuals,
+        point_residuals,
+        root_mean_square: root_mean_square(norms.iter().copied()).unwrap_or_default(),
+    })
+}
+
+fn root_mean_square(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| {
+        (sum + value * value, count + 1)
+    });
+    (count > 0).then(|| (sum / count as f32).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Isometry3;
+
+    use super::*;
+
+    const IMAGE_SIZE: Size<f32> = Size::new(1200.0, 800.0);
+
+    fn camera(eye: Point3<f32>) -> ComputeSolution<f32> {
+        let view = Isometry3::look_at_rh(&eye, &Point3::origin(), &Vector3::z());
+        ComputeSolution::new(view.to_homogeneous(), Vector2::zeros(), 50f32.to_radians())
+    }
+
+    /// Where `camera` sees `position`, relative to the image size.
+    fn observe(camera: &ComputeSolution<f32>, position: &Point3<f32>) -> Point2<f32> {
+        let image_plane = camera
+            .calculate_location_position_to_2d(&position.coords)
+            .unwrap();
+        let ratio = IMAGE_SIZE.width / IMAGE_SIZE.height;
+        Point2::new(
+            (image_plane.x + 1.0) / 2.0,
+            (1.0 - image_plane.y * ratio) / 2.0,
+        )
+    }
+
+    /// Root mean square reprojection error in pixels of `points` seen from `cameras`.
+    fn reprojection_error(
+        cameras: &[ComputeSolution<f32>],
+        observed: &[Vec<Point2<f32>>],
+        points: &[Point3<f32>],
+    ) -> f32 {
+        let squares: Vec<f32> = cameras
+            .iter()
+            .zip(observed)
+            .flat_map(|(camera, observed)| {
+                points.iter().zip(observed).map(|(point, observed)| {
+                    let offset = observe(camera, point) - observed;
+                    (offset.x * IMAGE_SIZE.width).powi(2) + (offset.y * IMAGE_SIZE.height).powi(2)
+                })
+            })
+            .collect();
+        (squares.iter().sum::<f32>() / squares.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn adjustment_reduces_the_reprojection_error() {
+        let eyes = [Point3::new(5.0, -4.0, 2.0), Point3::new(-3.0, -5.0, 1.5)];
+        let true_cameras = eyes.map(camera);
+        let control_points = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(-1.0, 0.0, 0.5),
+        ];
+        let world_points = [
+            Point3::new(0.4, -0.3, 0.8),
+            Point3::new(-0.6, 0.5, 0.2),
+            Point3::new(0.7, 0.6, -0.4),
+            Point3::new(-0.2, -0.7, 0.3),
+        ];
+        let observed: Vec<Vec<Point2<f32>>> = true_cameras
+            .iter()
+            .map(|camera| {
+                world_points
+                    .iter()
+                    .map(|point| observe(camera, point))
+                    .collect()
+            })
+            .collect();
+
+        // the solved poses and the triangulated points are slightly off
+        let cameras = [
+            camera(eyes[0] + Vector3::new(0.05, -0.03, 0.04)),
+            camera(eyes[1] + Vector3::new(-0.04, 0.02, -0.05)),
+        ];
+        let points: BTreeMap<usize, Point3<f32>> = world_points
+            .iter()
+            .enumerate()
+            .map(|(id, point)| (id, point + Vector3::new(0.03, -0.02, 0.02)))
+            .collect();
+        let images: Vec<BundleImage> = cameras
+            .iter()
+            .zip(&true_cameras)
+            .zip(&observed)
+            .map(|((compute_solution, true_camera), observed)| BundleImage {
+                compute_solution: compute_solution.clone(),
+                image_size: IMAGE_SIZE,
+                shared_intrinsics: false,
+                control_points: control_points
+                    .iter()
+                    .map(|point| (*point, observe(true_camera, point)))
+                    .collect(),
+                observations: observed
+                    .iter()
+                    .enumerate()
+                    .map(|(id, point)| (id, Point::new(point.x, point.y)))
+                    .collect(),
+            })
+            .collect();
+        let before = reprojection_error(
+            &cameras,
+            &observed,
+            &points.values().copied().collect::<Vec<_>>(),
+        );
+
+        let adjustment = bundle_adjust(&images, &points).unwrap();
+        let after = reprojection_error(
+            &adjustment.cameras,
+            &observed,
+            &adjustment.points.values().copied().collect::<Vec<_>>(),
+        );
+        assert!(before > 5.0, "{before}");
+        assert!(after < 0.1, "{after}");
+        assert!(adjustment.root_mean_square < 0.1);
+        adjustment
+            .points
+            .iter()
+            .for_each(|(id, point)| assert!((point - world_points[*id]).norm() < 1e-2));
+    }
+
+    #[test]
+    fn needs_something_to_adjust() {
+        let images = [BundleImage {
+            compute_solution: camera(Point3::new(5.0, -4.0, 2.0)),
+            image_size: IMAGE_SIZE,
+            shared_intrinsics: false,
+            control_points: Vec::new(),
+            observations: Vec::new(),
+        }];
+        assert!(bundle_adjust(&images, &BTreeMap::new()).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use iced::{Point, Size};
+use nalgebra::{Matrix3, Point3, Rotation3, UnitQuaternion, Vector2, Vector3};
+
+use crate::compute::data::ComputeSolution;
+
+/// Image written to the COLMAP model, `name` is relative to the image folder.
+pub struct ColmapImage<'a> {
+    pub name: &'a str,
+    pub compute_solution: &'a ComputeSolution<f32>,
+    pub image_size: Size<f32>,
+    /// World point id and image point relative to the image size.
+    pub observations: &'a [(usize, Point)],
+}
+
+/// `cameras.txt`, `images.txt` and `points3D.txt` of a COLMAP text model,
+/// one PINHOLE camera per image. `errors` are the mean reprojection errors in pixels.
+pub fn to_colmap(
+    images: &[ColmapImage],
+    points: &BTreeMap<usize, Point3<f32>>,
+    errors: &BTreeMap<usize, f32>,
+) -> [String; 3] {
+    let mut cameras = String::from("# CAMERA_ID, MODEL, WIDTH, HEIGHT, PARAMS[]\n");
+    let mut image_lines = String::from(
+        "# IMAGE_ID, QW, QX, QY, QZ, TX, TY, TZ, CAMERA_ID, NAME\n# POINTS2D[] as (X, Y, POINT3D_ID)\n",
+    );
+    let mut tracks: BTreeMap<usize, Vec<(usize, usize)>> = BTreeMap::new();
+
+    images.iter().enumerate().for_each(|(index, image)| {
+        let id = index + 1;
+        let compute_solution = image.compute_solution;
+        let half_width = image.image_size.width / 2.0;
+        let ratio = image.image_size.width / image.image_size.height;
+        // image plane coordinates to pixels from the top left corner
+        let pixel = |point: &Vector2<f32>| {
+            Vector2::new(
+                (point.x + 1.0) * half_width,
+                (1.0 / ratio - point.y) * half_width,
+            )
+        };
+
+        let focal_length = 1.0 / (compute_solution.field_of_view() / 2.0).tan() * half_width;
+        let principal_point = pixel(&compute_solution.ortho_center());
+        let _ = writeln!(
+            cameras,
+            "{id} PINHOLE {} {} {focal_length} {focal_length} {} {}",
+            image.image_size.width, image.image_size.height, principal_point.x, principal_point.y
+        );
+
+        // COLMAP cameras look down +Z with Y down, the solution looks down -Z with Y up
+        let view_transform = compute_solution.view_transform();
+        let flip = Matrix3::from_diagonal(&Vector3::new(1.0, -1.0, -1.0));
+        let rotation =
+            Rotation3::from_matrix(&(flip * view_transform.fixed_view::<3, 3>(0, 0).into_owned()));
+        let translation = flip * view_transform.fixed_view::<3, 1>(0, 3).into_owned();
+        let quaternion = UnitQuaternion::from_rotation_matrix(&rotation);
+        let _ = writeln!(
+            image_lines,
+            "{id} {} {} {} {} {} {} {} {id} {}",
+            quaternion.w,
+            quaternion.i,
+            quaternion.j,
+            quaternion.k,
+            translation.x,
+            translation.y,
+            translation.z,
+            image.name
+        );
+        let observations: Vec<String> = image
+            .observations
+            .iter()
+            .enumerate()
+            .map(|(index, (point_id, point))| {
+                let point = Vector2::new(
+                    point.x * image.image_size.width,
+                    point.y * image.image_size.height,
+                );
+                if points.contains_key(point_id) {
+                    tracks.entry(*point_id).or_default().push((id, index));
+                    format!("{} {} {point_id}", point.x, point.y)
+                } else {
+                    format!("{} {} -1", point.x, point.y)
+                }
+            })
+            .collect();
+        let _ = writeln!(image_lines, "{}", observations.join(" "));
+    });
+
+    let mut point_lines =
+        String::from("# POINT3D_ID, X, Y, Z, R, G, B, ERROR, TRACK[] as (IMAGE_ID, POINT2D_IDX)\n");
+    tracks.iter().for_each(|(id, track)| {
+        let point = points[id];
+        let track: Vec<String> = track
+            .iter()
+            .map(|(image, index)| format!("{image} {index}"))
+            .collect();
+        let _ = writeln!(
+            point_lines,
+            "{id} {} {} {} 255 255 255 {} {}",
+            point.x,
+            point.y,
+            point.z,
+            errors.get(id).copied().unwrap_or_default(),
+            track.join(" ")
+        );
+    });
+    [cameras, image_lines, point_lines]
+}
@@ -1,32 +1,42 @@
 use std::{
+    cmp::Ordering,
     fs::File,
     io::Read,
     ops::{AddAssign, DivAssign, MulAssign, SubAssign},
 };
 
 use anyhow::Result;
+use cv::FeatureWorldMatch;
 use data::ComputeSolution;
 use iced::{Point, Size};
+use lambda_twist::LambdaTwist;
 use nalgebra::{
-    ComplexField, Matrix3, Point2, Point3, RealField, RowVector3, Scalar, SimdComplexField,
-    Vector2, Vector3,
+    ComplexField, Matrix2, Matrix3, Matrix4, Point2, Point3, RealField, RowVector3, Scalar,
+    SimdComplexField, Vector2, Vector3,
 };
 use num_traits::Float;
 use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio_util::{bytes::BytesMut, codec::Encoder};
+use tracing::trace;
 
 use crate::{
-    AxisData, FSpyData, SceneSettings, encoder::FSpyEncoder,
-    fspy::compute_solution_to_scene_settings, utils::relative_to_image_plane,
+    AxisData, Drawing, EditAxis, FSpyData, Measurement, Observation, ReferenceDistance,
+    ReferenceDistanceUnit, SceneSettings, Shape, TwistLabel,
+    encoder::FSpyEncoder,
+    fspy::compute_solution_to_scene_settings,
+    utils::{
+        calculate_cursor_position_to_3d, closest_point_on_line_to_ray, line_insert_with_plane,
+        relative_to_image_plane,
+    },
 };
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct StorePoint {
     pub x: f32,
     pub y: f32,
 }
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Lines {
     pub control_point: StorePoint,
     pub lines: Vec<StoreLine>,
@@ -36,15 +46,107 @@ pub struct Lines {
     pub custom_scale: Option<f32>,
     pub twist_points: Option<Vec<StorePoint3d>>,
     pub twist_points_2d: Option<Vec<StorePoint>>,
+    pub twist_labels: Option<Vec<TwistLabel>>,
     pub field_of_view: Option<f32>,
+    pub lock_field_of_view: Option<bool>,
+    pub shared_intrinsics: Option<bool>,
+    pub principal_point: Option<StorePoint>,
+    #[serde(alias = "scale")]
+    pub reference_distance: Option<StoreReferenceDistance>,
+    pub reference_distance_unit: Option<ReferenceDistanceUnit>,
+    pub shapes: Option<Vec<StoreShape>>,
+    pub measurements: Option<Vec<StoreMeasurement>>,
+    pub observations: Option<Vec<StoreObservation>>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoreObservation {
+    pub id: usize,
+    pub point: StorePoint,
+}
+
+impl From<&Observation> for StoreObservation {
+    fn from(observation: &Observation) -> Self {
+        StoreObservation {
+            id: observation.id,
+            point: StorePoint {
+                x: observation.point.x,
+                y: observation.point.y,
+            },
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoreMeasurement {
+    pub a: StorePoint,
+    pub b: StorePoint,
+    pub axis: EditAxis,
+}
+
+impl From<&Measurement> for StoreMeasurement {
+    fn from(measurement: &Measurement) -> Self {
+        StoreMeasurement {
+            a: StorePoint {
+                x: measurement.line.0.x,
+                y: measurement.line.0.y,
+            },
+            b: StorePoint {
+                x: measurement.line.1.x,
+                y: measurement.line.1.y,
+            },
+            axis: measurement.axis,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoreReferenceDistance {
+    pub a: StorePoint,
+    pub b: StorePoint,
+    pub axis: Option<EditAxis>,
+    pub length: Option<f32>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoreShape {
+    pub points: Vec<StorePoint3d>,
+    pub closed: bool,
+    pub extrusion: Option<StorePoint3d>,
+}
+
+impl From<&Shape> for StoreShape {
+    fn from(shape: &Shape) -> Self {
+        let store = |point: &Vector3<f32>| StorePoint3d {
+            x: point.x,
+            y: point.y,
+            z: point.z,
+        };
+        StoreShape {
+            points: shape.points.iter().map(store).collect(),
+            closed: shape.closed,
+            extrusion: shape.extrusion.as_ref().map(store),
+        }
+    }
+}
+
+impl From<&StoreShape> for Shape {
+    fn from(shape: &StoreShape) -> Self {
+        let load = |point: &StorePoint3d| Vector3::new(point.x, point.y, point.z);
+        Shape {
+            points: shape.points.iter().map(load).collect(),
+            closed: shape.closed,
+            extrusion: shape.extrusion.as_ref().map(load),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct StoreLine {
     pub a: StorePoint,
     pub b: StorePoint,
 }
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct StorePoint3d {
     pub x: f32,
     pub y: f32,
@@ -60,7 +162,7 @@ impl From<&(Point, Point)> for StoreLine {
 }
 
 pub mod data {
-    use nalgebra::{Matrix4, Perspective3, Point3, RealField, Vector2, Vector3};
+    use nalgebra::{Matrix4, Perspective3, Point3, RealField, Vector2, Vector3, Vector4};
     use num_traits::Float;
     use tracing::trace;
 
@@ -149,6 +251,58 @@ pub mod data {
             Some(point.xy().coords)
         }
 
+        /// Camera center and direction in world space of the ray through `image_point`,
+        /// given in image plane coordinates (see `relative_to_image_plane`).
+        pub fn camera_ray(&self, image_point: &Vector2<T>) -> Option<(Vector3<T>, Vector3<T>)> {
+            let camera_center = self.view_transform.try_inverse()?
+                * Point3::new(
+                    T::from(0.0).unwrap(),
+                    T::from(0.0).unwrap(),
+                    T::from(0.0).unwrap(),
+                )
+                .to_homogeneous();
+            let camera_center = Point3::from_homogeneous(camera_center)?;
+
+            let unproject = self.transform.try_inverse()?;
+            let far = unproject
+                * Point3::new(image_point.x, image_point.y, T::from(1.0).unwrap()).to_homogeneous();
+            let far = Point3::from_homogeneous(far)?;
+            Some((camera_center.coords, (far - camera_center).normalize()))
+        }
+
+        /// Vanishing points of the world X, Y and Z axes in image plane coordinates,
+        /// `None` for an axis parallel to the image plane.
+        pub fn vanishing_points(&self) -> [Option<Vector2<T>>; 3] {
+            [0, 1, 2].map(|axis| {
+                let point = self.project_direction(axis);
+                if Float::abs(point.z) < T::from(1e-6).unwrap() {
+                    None
+                } else {
+                    Some(point.xy() / point.z)
+                }
+            })
+        }
+
+        /// Horizon as the homogeneous image line `a x + b y + c = 0` the XY plane vanishes to.
+        pub fn horizon_line(&self) -> Vector3<T> {
+            self.project_direction(0).cross(&self.project_direction(1))
+        }
+
+        /// Homogeneous image point (x, y, w) of the world axis direction at infinity.
+        fn project_direction(&self, axis: usize) -> Vector3<T> {
+            let zero = T::from(0.0).unwrap();
+            let mut direction = Vector4::new(zero, zero, zero, zero);
+            direction[axis] = T::from(1.0).unwrap();
+            let point = self.transform * direction;
+            Vector3::new(point.x, point.y, point.w)
+        }
+
+        /// Whether `location` lies in front of the camera, which looks along -Z in view space.
+        pub fn is_in_front(&self, location: &Vector3<T>) -> bool {
+            let view = self.view_transform * Point3::from(*location).to_homogeneous();
+            view.z < T::from(0.0).unwrap()
+        }
+
         pub fn view_transform(&self) -> Matrix4<T> {
             self.view_transform
         }
@@ -195,12 +349,19 @@ pub mod data {
         }
     }
 }
-pub fn read_points_from_file(points: &String) -> Result<(AxisData, Option<Vec<Vector3<f32>>>)> {
+pub fn read_points_from_file(points: &String) -> Result<(AxisData, Drawing)> {
+    Ok(read_points(read_lines_from_file(points)?))
+}
+
+pub fn read_lines_from_file(points: &String) -> Result<Lines> {
     let mut file = File::open(points)?;
     let mut content = String::new();
     file.read_to_string(&mut content)?;
-    let data: Lines = serde_json::from_str(&content)?;
+    Ok(serde_json::from_str(&content)?)
+}
 
+/// Axis data and drawing of an image, from a points file or a project.
+pub fn read_points(data: Lines) -> (AxisData, Drawing) {
     let lines = data
         .lines
         .iter()
@@ -223,11 +384,53 @@ pub fn read_points_from_file(points: &String) -> Result<(AxisData, Option<Vec<Ve
         y: data.control_point.y,
     };
 
-    let points = data.points.map(|item| {
-        item.iter()
-            .map(|point| Vector3::new(point.x, point.y, point.z))
-            .collect()
-    });
+    // files written before shapes existed only store a single polyline
+    let shapes = match data.shapes {
+        Some(shapes) => shapes.iter().map(Into::into).collect(),
+        None => data
+            .points
+            .filter(|points| points.len() > 1)
+            .map(|points| {
+                vec![Shape {
+                    points: points
+                        .iter()
+                        .map(|point| Vector3::new(point.x, point.y, point.z))
+                        .collect(),
+                    ..Shape::default()
+                }]
+            })
+            .unwrap_or_default(),
+    };
+    let observations = data
+        .observations
+        .unwrap_or_default()
+        .iter()
+        .map(|item| Observation {
+            id: item.id,
+            point: Point {
+                x: item.point.x,
+                y: item.point.y,
+            },
+        })
+        .collect();
+    let measurements = data
+        .measurements
+        .unwrap_or_default()
+        .iter()
+        .map(|item| Measurement {
+            line: (
+                Point {
+                    x: item.a.x,
+                    y: item.a.y,
+                },
+                Point {
+                    x: item.b.x,
+                    y: item.b.y,
+                },
+            ),
+            axis: item.axis,
+        })
+        .collect();
 
     let flip = if let Some(flip) = data.flip {
         (flip[0], flip[1], flip[2])
@@ -253,7 +456,30 @@ pub fn read_points_from_file(points: &String) -> Result<(AxisData, Option<Vec<Ve
             .collect()
     });
     let field_of_view = data.field_of_view;
-    Ok((
+    let lock_field_of_view = data.lock_field_of_view.unwrap_or(false);
+    let shared_intrinsics = data.shared_intrinsics.unwrap_or(false);
+    let principal_point = data
+        .principal_point
+        .map(|item| Vector2::new(item.x, item.y));
+    // the `scale` segment of older files has no length, their `custom_scale` stays in charge
+    let reference_distance = data.reference_distance.and_then(|item| {
+        Some(ReferenceDistance {
+            line: (
+                Point {
+                    x: item.a.x,
+                    y: item.a.y,
+                },
+                Point {
+                    x: item.b.x,
+                    y: item.b.y,
+                },
+            ),
+            axis: item.axis.unwrap_or(EditAxis::EditX),
+            length: item.length.filter(|length| *length > 0.0)?,
+        })
+    });
+    let reference_distance_unit = data.reference_distance_unit.unwrap_or_default();
+    (
         AxisData {
             control_point,
             axis_lines: lines,
@@ -262,10 +488,21 @@ pub fn read_points_from_file(points: &String) -> Result<(AxisData, Option<Vec<Ve
             custom_scale,
             twist_points,
             twist_points_2d,
+            twist_labels: data.twist_labels.unwrap_or_default(),
             field_of_view,
+            lock_field_of_view,
+            shared_intrinsics,
+            principal_point,
+            reference_distance,
+            reference_distance_unit,
         },
-        points,
-    ))
+        Drawing {
+            shapes,
+            active: None,
+            measurements,
+            observations,
+        },
+    )
 }
 
 pub fn adaptor_compute_solution_to_scene_settings<
@@ -274,8 +511,14 @@ pub fn adaptor_compute_solution_to_scene_settings<
     image_width: u32,
     image_height: u32,
     compute_solution: &ComputeSolution<T>,
+    reference_distance_unit: ReferenceDistanceUnit,
 ) -> Result<SceneSettings> {
-    compute_solution_to_scene_settings(image_width, image_height, compute_solution)
+    compute_solution_to_scene_settings(
+        image_width,
+        image_height,
+        compute_solution,
+        reference_distance_unit,
+    )
 }
 
 pub async fn store_scene_data_to_file<T: Float + ComplexField + Into<f32> + RealField>(
@@ -284,12 +527,17 @@ pub async fn store_scene_data_to_file<T: Float + ComplexField + Into<f32> + Real
     image_height: u32,
     image_path: String,
     export_file_name: String,
+    reference_distance_unit: ReferenceDistanceUnit,
 ) -> Result<SceneSettings> {
     let mut image_file = tokio::fs::File::open(image_path).await?;
     let mut contents = vec![];
     image_file.read_to_end(&mut contents).await?;
-    let data =
-        adaptor_compute_solution_to_scene_settings(image_width, image_height, compute_solution)?;
+    let data = adaptor_compute_solution_to_scene_settings(
+        image_width,
+        image_height,
+        compute_solution,
+        reference_distance_unit,
+    )?;
     let to_export = FSpyData {
         data: data.clone(),
         image: contents,
@@ -304,6 +552,7 @@ pub async fn store_scene_data_to_file<T: Float + ComplexField + Into<f32> + Real
     repackage_file.write_all(&dst).await?;
     Ok(data)
 }
+#[allow(clippy::too_many_arguments)]
 pub fn compute_ui_adapter<
     T: Float + SubAssign + MulAssign + DivAssign + AddAssign + ComplexField + Scalar + RealField,
 >(
@@ -315,21 +564,9 @@ pub fn compute_ui_adapter<
     flip: (bool, bool, bool),
     translate_origin: &Option<Vector3<T>>,
     scale: &Option<T>,
-) -> Result<ComputeSolution<T>> {
-    let points: [Vector2<T>; 12] = [
-        Vector2::new(x_lines[0].0.x, x_lines[0].0.y),
-        Vector2::new(x_lines[0].1.x, x_lines[0].1.y),
-        Vector2::new(x_lines[1].0.x, x_lines[1].0.y),
-        Vector2::new(x_lines[1].1.x, x_lines[1].1.y),
-        Vector2::new(y_lines[0].0.x, y_lines[0].0.y),
-        Vector2::new(y_lines[0].1.x, y_lines[0].1.y),
-        Vector2::new(y_lines[1].0.x, y_lines[1].0.y),
-        Vector2::new(y_lines[1].1.x, y_lines[1].1.y),
-        Vector2::new(z_lines[0].0.x, z_lines[0].0.y),
-        Vector2::new(z_lines[0].1.x, z_lines[0].1.y),
-        Vector2::new(z_lines[1].0.x, z_lines[1].0.y),
-        Vector2::new(z_lines[1].1.x, z_lines[1].1.y),
-    ];
+    field_of_view: &Option<T>,
+    principal_point: &Option<Vector2<T>>,
+) -> Result<(ComputeSolution<T>, Option<FocalLengthDeviation<T>>)> {
     let control_point: Vector2<T> = Vector2::new(control_point.x, control_point.y);
 
     let x = if flip.0 { 1.0 } else { -1.0 };
@@ -356,17 +593,30 @@ pub fn compute_ui_adapter<
     let ratio = image_size.width / image_size.height;
     let user_selected_origin = relative_to_image_plane(ratio, &control_point);
 
-    let vanishing_points = points
-        .chunks(4)
-        .map(|lines| find_vanishing_point_for_lines(&lines[0], &lines[1], &lines[2], &lines[3]))
-        .collect::<Vec<Vector2<T>>>();
-
-    let vanishing_points = vanishing_points
-        .iter()
-        .map(|point| relative_to_image_plane(ratio, point))
-        .collect::<Vec<Vector2<T>>>();
+    let vanishing_points = image_plane_vanishing_points(
+        &[
+            x_lines[0], x_lines[1], y_lines[0], y_lines[1], z_lines[0], z_lines[1],
+        ],
+        image_size,
+    );
 
-    let compute_solution = compute_camera_pose(&vanishing_points, &user_selected_origin, axis);
+    let (compute_solution, deviation) = if let Some(field_of_view) = field_of_view {
+        match compute_camera_pose_with_field_of_view(
+            &vanishing_points,
+            &user_selected_origin,
+            axis,
+            *field_of_view,
+            principal_point,
+        ) {
+            Ok((compute_solution, deviation)) => (Ok(compute_solution), Some(deviation)),
+            Err(error) => (Err(error), None),
+        }
+    } else {
+        (
+            compute_camera_pose(&vanishing_points, &user_selected_origin, axis),
+            None,
+        )
+    };
 
     let compute_solution = if let Ok(compute_solution) = compute_solution {
         if let Some(scale) = scale {
@@ -377,7 +627,7 @@ pub fn compute_ui_adapter<
     } else {
         compute_solution
     };
-    if let Some(translate_origin) = translate_origin {
+    let compute_solution = if let Some(translate_origin) = translate_origin {
         if let Ok(compute_solution) = compute_solution {
             compute_camera_pose_translation(compute_solution, translate_origin)
         } else {
@@ -385,9 +635,313 @@ pub fn compute_ui_adapter<
         }
     } else {
         compute_solution
+    };
+    compute_solution.map(|compute_solution| (compute_solution, deviation))
+}
+
+/// Length in world units of the reference segment `a`-`b` (image plane coordinates).
+/// `a` is placed on the XY plane through the origin, `b` on the line from `a` along `axis`.
+pub fn measure_reference_distance<
+    T: Float + AddAssign + MulAssign + SubAssign + Scalar + RealField,
+>(
+    compute_solution: &ComputeSolution<T>,
+    a: &Vector2<T>,
+    b: &Vector2<T>,
+    axis: &EditAxis,
+) -> Option<T> {
+    let direction = axis.direction::<T>()?;
+    let (camera_center, ray_a) = compute_solution.camera_ray(a)?;
+    let (_, ray_b) = compute_solution.camera_ray(b)?;
+    let ground = Vector3::new(
+        T::from(0.0).unwrap(),
+        T::from(0.0).unwrap(),
+        T::from(1.0).unwrap(),
+    );
+    if Float::abs(ground.dot(&ray_a)) < T::from(1e-6).unwrap() {
+        return None;
+    }
+    let start = line_insert_with_plane(
+        &Vector3::zeros(),
+        &ground,
+        &camera_center,
+        &(camera_center + ray_a),
+    );
+    let end = closest_point_on_line_to_ray(&start, &direction, &camera_center, &ray_b)?;
+    Some((end - start).norm())
+}
+
+/// World space end points of a measurement between the image points `a` and `b`, relative
+/// to the image size. `a` lies on the ground plane, `b` too for `EditAxis::None`,
+/// otherwise on the line from `a` along `axis`.
+pub fn measure_points<
+    T: Float + AddAssign + MulAssign + SubAssign + DivAssign + Scalar + RealField,
+>(
+    compute_solution: &ComputeSolution<T>,
+    ratio: T,
+    a: &Vector2<T>,
+    b: &Vector2<T>,
+    axis: &EditAxis,
+) -> Option<(Vector3<T>, Vector3<T>)> {
+    let start = calculate_cursor_position_to_3d(
+        &EditAxis::None,
+        compute_solution,
+        ratio,
+        a,
+        Vector3::zeros(),
+    )?;
+    let end = match axis.direction::<T>() {
+        None => calculate_cursor_position_to_3d(
+            &EditAxis::None,
+            compute_solution,
+            ratio,
+            b,
+            Vector3::zeros(),
+        )?,
+        Some(direction) => {
+            let (camera_center, ray) =
+                compute_solution.camera_ray(&relative_to_image_plane(ratio, b))?;
+            closest_point_on_line_to_ray(&start, &direction, &camera_center, &ray)?
+        }
+    };
+    Some((start, end))
+}
+
+/// Signed length of a measurement, heights are negative below the first point.
+pub fn measurement_length<T: Float + Scalar + RealField>(
+    (start, end): &(Vector3<T>, Vector3<T>),
+    axis: &EditAxis,
+) -> T {
+    match axis.direction::<T>() {
+        Some(direction) => (end - start).dot(&direction),
+        None => (end - start).norm(),
     }
 }
 
+/// Scale making the reference segment measure its real length, `None` while it is unusable.
+pub fn reference_distance_scale<
+    T: Float + AddAssign + MulAssign + SubAssign + DivAssign + Scalar + RealField,
+>(
+    compute_solution: &ComputeSolution<T>,
+    image_size: Size<T>,
+    line: &(Point<T>, Point<T>),
+    axis: &EditAxis,
+    length: T,
+) -> Option<T> {
+    if length <= T::from(0.0).unwrap() {
+        return None;
+    }
+    let ratio = image_size.width / image_size.height;
+    let a = relative_to_image_plane(ratio, &Vector2::new(line.0.x, line.0.y));
+    let b = relative_to_image_plane(ratio, &Vector2::new(line.1.x, line.1.y));
+    let measured = measure_reference_distance(compute_solution, &a, &b, axis)?;
+    if measured > T::from(0.0).unwrap() {
+        Some(measured / length)
+    } else {
+        None
+    }
+}
+
+/// Vanishing points of the X, Y and Z line pairs in image plane coordinates,
+/// `axis_lines` holds two lines per axis relative to the image size.
+pub fn image_plane_vanishing_points<
+    T: Float + AddAssign + MulAssign + DivAssign + Scalar + RealField,
+>(
+    axis_lines: &[(Point<T>, Point<T>)],
+    image_size: Size<T>,
+) -> Vec<Vector2<T>> {
+    let ratio = image_size.width / image_size.height;
+    axis_lines
+        .chunks(2)
+        .map(|lines| {
+            let point = |point: &Point<T>| Vector2::new(point.x, point.y);
+            let vanishing_point = find_vanishing_point_for_lines(
+                &point(&lines[0].0),
+                &point(&lines[0].1),
+                &point(&lines[1].0),
+                &point(&lines[1].1),
+            );
+            relative_to_image_plane(ratio, &vanishing_point)
+        })
+        .collect()
+}
+
+/// Solves the vanishing point mode from `axis_data`, refreshing `custom_scale` from the
+/// reference segment, which is measured on the unscaled solution before the origin offset.
+pub fn compute_vanishing_points_pose(
+    axis_data: &mut AxisData,
+    image_size: Size<f32>,
+    field_of_view: &Option<f32>,
+) -> Result<(ComputeSolution<f32>, Option<FocalLengthDeviation<f32>>)> {
+    let principal_point = axis_data
+        .principal_point
+        .filter(|_| axis_data.shared_intrinsics);
+    let solve =
+        |axis_data: &AxisData, translate_origin: &Option<Vector3<f32>>, scale: &Option<f32>| {
+            let lines = &axis_data.axis_lines;
+            compute_ui_adapter(
+                [lines[0], lines[1]],
+                [lines[2], lines[3]],
+                [lines[4], lines[5]],
+                image_size,
+                &axis_data.control_point,
+                axis_data.flip,
+                translate_origin,
+                scale,
+                field_of_view,
+                &principal_point,
+            )
+        };
+
+    if let Some(reference) = axis_data.reference_distance.as_ref() {
+        let reference_scale =
+            solve(axis_data, &None, &None)
+                .ok()
+                .and_then(|(compute_solution, _)| {
+                    reference_distance_scale(
+                        &compute_solution,
+                        image_size,
+                        &reference.line,
+                        &reference.axis,
+                        reference.length,
+                    )
+                });
+        // a length being typed keeps the last scale
+        if reference_scale.is_some() {
+            axis_data.custom_scale = reference_scale;
+        }
+    }
+
+    solve(
+        axis_data,
+        &axis_data.custom_origin_translation,
+        &axis_data.custom_scale,
+    )
+}
+
+/// Camera pose from three 2D/3D point correspondences with lambda twist, `None` without a solution.
+/// Further correspondences pick the candidate pose with the smallest reprojection error.
+/// `twist_points_2d` are relative to the image size, `field_of_view` is in radians and
+/// `principal_point` is in image plane coordinates.
+pub fn compute_camera_pose_twist(
+    twist_points: &[Point3<f32>],
+    twist_points_2d: &[Point2<f32>],
+    image_size: Size<f32>,
+    field_of_view: f32,
+    principal_point: Vector2<f32>,
+) -> Option<ComputeSolution<f32>> {
+    let fx = image_size.width as f64;
+    let fy = image_size.height as f64;
+    let cx = image_size.width as f64 / 2.0;
+    let cy = image_size.height as f64 / 2.0;
+
+    let unprojection =
+        cv::nalgebra::Perspective3::new(1.0, field_of_view as f64, 0.1, 1000.0).inverse();
+    let to_device_coord_transform = cv::nalgebra::Matrix3::new_nonuniform_scaling(
+        &cv::nalgebra::Vector2::new(fx / 2.0, -fx / 2.0),
+    )
+    .append_translation(&cv::nalgebra::Vector2::new(cx, cy))
+    .try_inverse()?;
+    trace!("3d: {:?}", twist_points);
+    trace!("2d: {:?}", twist_points_2d);
+    let bearings: Vec<cv::nalgebra::Point3<f64>> = twist_points_2d
+        .iter()
+        .map(|item| {
+            let item = cv::nalgebra::Point2::new(item.x as f64 * fx, item.y as f64 * fy);
+            // image plane coordinates centered on the principal point
+            let device = to_device_coord_transform * item.to_homogeneous()
+                - cv::nalgebra::Vector3::new(
+                    principal_point.x as f64,
+                    principal_point.y as f64,
+                    0.0,
+                );
+            cv::nalgebra::Point3::from(
+                (unprojection * cv::nalgebra::Point3::from(device).to_homogeneous()).xyz(),
+            )
+        })
+        .map(|item| item / item.z)
+        .collect();
+    trace!("bearings: {:?}", bearings);
+    let features: Vec<FeatureWorldMatch<_>> = twist_points
+        .iter()
+        .zip(&bearings)
+        .map(|(&world, &image)| {
+            //INFO: in Blender camera looks at -Z, in computer vision camera looks at +Z, inverting all coordinates
+            let world =
+                cv::nalgebra::Point3::new(-world.x as f64, -world.y as f64, -world.z as f64);
+            let bearing = cv::nalgebra::Unit::new_normalize(cv::nalgebra::Vector3::new(
+                image.x, image.y, 1.0,
+            ));
+            FeatureWorldMatch(bearing, cv::WorldPoint(world.to_homogeneous()))
+        })
+        .collect();
+
+    let solver = LambdaTwist::new();
+    use cv::Estimator;
+    let mut candidates = solver.estimate(features.iter().take(3).cloned());
+
+    //sort by Y rotation, most vertical position
+    candidates.sort_by(|a, b| {
+        if a.0.rotation.inverse().euler_angles().1.abs()
+            < b.0.rotation.inverse().euler_angles().1.abs()
+        {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    });
+
+    candidates
+        .iter()
+        .for_each(|item| trace!("solution: {}", item.0.to_homogeneous()));
+    if features.len() > 3 {
+        // squared distance of every projected world point to its bearing on the z = 1 plane
+        let reprojection_error = |solution: &cv::nalgebra::Matrix4<f64>| -> f64 {
+            features
+                .iter()
+                .zip(&bearings)
+                .map(|(feature, bearing)| {
+                    let camera = solution * feature.1.0;
+                    if camera.z <= 0.0 {
+                        return f64::INFINITY;
+                    }
+                    (camera.x / camera.z - bearing.x).powi(2)
+                        + (camera.y / camera.z - bearing.y).powi(2)
+                })
+                .sum()
+        };
+        candidates.sort_by(|a, b| {
+            reprojection_error(&a.0.to_homogeneous())
+                .total_cmp(&reprojection_error(&b.0.to_homogeneous()))
+        });
+    }
+
+    let solution = candidates.first()?.0.to_homogeneous();
+    trace!("using the first solution {solution}");
+    //INFO: invert returned translation vector (world = -camera)
+    Some(ComputeSolution::new(
+        Matrix4::new(
+            solution.m11 as f32,
+            solution.m12 as f32,
+            solution.m13 as f32,
+            -solution.m14 as f32,
+            solution.m21 as f32,
+            solution.m22 as f32,
+            solution.m23 as f32,
+            -solution.m24 as f32,
+            solution.m31 as f32,
+            solution.m32 as f32,
+            solution.m33 as f32,
+            -solution.m34 as f32,
+            solution.m41 as f32,
+            solution.m42 as f32,
+            solution.m43 as f32,
+            solution.m44 as f32,
+        ),
+        principal_point,
+        field_of_view,
+    ))
+}
+
 pub fn compute_camera_pose_scale<T: Float + MulAssign + AddAssign + Scalar + RealField>(
     mut compute_solution: ComputeSolution<T>,
     scale: T,
@@ -458,16 +1012,158 @@ pub fn compute_camera_pose<
         (ortho_center - vanishing_points[1]).dot(&(ortho_center - vanishing_points[2])),
     ));
 
-    let x_rotation = vanishing_points[0] - ortho_center;
+    let rotation_matrix =
+        rotation_from_vanishing_points(vanishing_points, &ortho_center, focal_length);
+    Ok(camera_pose_from_rotation(
+        rotation_matrix,
+        user_selected_origin,
+        axis,
+        ortho_center,
+        focal_length,
+    ))
+}
+
+/// How far the unconstrained vanishing point solution is from one with a locked focal length.
+#[derive(Debug, Clone, Copy)]
+pub struct FocalLengthDeviation<T> {
+    /// field of view derived from the orthocenter of the vanishing points
+    pub free_field_of_view: T,
+    /// orthocenter minus the principal point solved for the locked focal length
+    pub principal_point_offset: Vector2<T>,
+    /// largest deviation from a right angle between the axes before orthonormalization
+    pub orthogonality_error: T,
+}
+
+/// Same as [`compute_camera_pose`] but with a known (e.g. from EXIF) horizontal field of view.
+/// Without a known `principal_point` it is solved so the three vanishing directions are as close
+/// to orthogonal as the focal length allows, the rotation is then snapped to the nearest orthonormal one.
+pub fn compute_camera_pose_with_field_of_view<
+    T: Float
+        + std::ops::SubAssign
+        + AddAssign
+        + MulAssign
+        + SimdComplexField
+        + DivAssign
+        + MulAssign
+        + Scalar
+        + RealField
+        + 'static,
+>(
+    vanishing_points: &[Vector2<T>],
+    user_selected_origin: &Vector2<T>,
+    axis: Matrix3<T>,
+    field_of_view: T,
+    principal_point: &Option<Vector2<T>>,
+) -> Result<(ComputeSolution<T>, FocalLengthDeviation<T>)> {
+    let ortho_center = triangle_ortho_center(
+        &vanishing_points[0],
+        &vanishing_points[1],
+        &vanishing_points[2],
+    );
+    let free_focal_length = Float::sqrt(Float::abs(
+        (ortho_center - vanishing_points[1]).dot(&(ortho_center - vanishing_points[2])),
+    ));
+    let free_field_of_view =
+        T::from(2.0).unwrap() * Float::atan(T::from(1.0).unwrap() / free_focal_length);
+
+    let focal_length = T::from(1.0).unwrap() / Float::tan(field_of_view / T::from(2.0).unwrap());
+    let principal_point = principal_point.unwrap_or_else(|| {
+        principal_point_for_focal_length(vanishing_points, focal_length, ortho_center)
+    });
+
+    let rotation_matrix =
+        rotation_from_vanishing_points(vanishing_points, &principal_point, focal_length);
+    let orthogonality_error = [(0, 1), (0, 2), (1, 2)]
+        .iter()
+        .map(|&(i, j)| {
+            Float::asin(Float::min(
+                Float::abs(rotation_matrix.column(i).dot(&rotation_matrix.column(j))),
+                T::from(1.0).unwrap(),
+            ))
+        })
+        .fold(T::from(0.0).unwrap(), Float::max);
+
+    let svd = rotation_matrix.svd(true, true);
+    let (Some(u), Some(v_t)) = (svd.u, svd.v_t) else {
+        return Err(anyhow::anyhow!("could not orthonormalize rotation"));
+    };
+    let rotation_matrix = u * v_t;
+
+    Ok((
+        camera_pose_from_rotation(
+            rotation_matrix,
+            user_selected_origin,
+            axis,
+            principal_point,
+            focal_length,
+        ),
+        FocalLengthDeviation {
+            free_field_of_view,
+            principal_point_offset: ortho_center - principal_point,
+            orthogonality_error,
+        },
+    ))
+}
+
+/// Gauss-Newton on (v_i - p)·(v_j - p) + f² = 0 for the three vanishing point pairs,
+/// starting from the orthocenter which is exact when f is free.
+fn principal_point_for_focal_length<
+    T: Float + SubAssign + AddAssign + MulAssign + Scalar + RealField,
+>(
+    vanishing_points: &[Vector2<T>],
+    focal_length: T,
+    initial: Vector2<T>,
+) -> Vector2<T> {
+    let focal_length_squared = focal_length * focal_length;
+    let mut principal_point = initial;
+    for _ in 0..50 {
+        let mut jtj = Matrix2::<T>::zeros();
+        let mut jtr = Vector2::<T>::zeros();
+        for (i, j) in [(0, 1), (0, 2), (1, 2)] {
+            let a = vanishing_points[i] - principal_point;
+            let b = vanishing_points[j] - principal_point;
+            let residual = a.dot(&b) + focal_length_squared;
+            let jacobian = -(a + b);
+            jtj += jacobian * jacobian.transpose();
+            jtr += jacobian * residual;
+        }
+        let Some(inverse) = jtj.try_inverse() else {
+            break;
+        };
+        let step = inverse * jtr;
+        principal_point -= step;
+        if step.norm() < T::from(1e-7).unwrap() {
+            break;
+        }
+    }
+    principal_point
+}
+
+fn rotation_from_vanishing_points<T: Float + Scalar + RealField>(
+    vanishing_points: &[Vector2<T>],
+    principal_point: &Vector2<T>,
+    focal_length: T,
+) -> Matrix3<T> {
+    let x_rotation = vanishing_points[0] - principal_point;
     let x_rotation = Vector3::new(x_rotation.x, x_rotation.y, -focal_length).normalize();
-    let y_rotation = vanishing_points[1] - ortho_center;
+    let y_rotation = vanishing_points[1] - principal_point;
     let y_rotation = Vector3::new(y_rotation.x, y_rotation.y, -focal_length).normalize();
-    let z_rotation = vanishing_points[2] - ortho_center;
+    let z_rotation = vanishing_points[2] - principal_point;
     let z_rotation = Vector3::new(z_rotation.x, z_rotation.y, -focal_length).normalize();
     //let x_rotation = y_rotation.cross(&z_rotation);
     //let z_rotation = x_rotation.cross(&y_rotation);
-    let rotation_matrix = Matrix3::from_columns(&[x_rotation, y_rotation, z_rotation]);
+    Matrix3::from_columns(&[x_rotation, y_rotation, z_rotation])
+}
 
+fn camera_pose_from_rotation<
+    T: Float + SubAssign + AddAssign + MulAssign + DivAssign + Scalar + RealField,
+>(
+    rotation_matrix: Matrix3<T>,
+    user_selected_origin: &Vector2<T>,
+    axis: Matrix3<T>,
+    ortho_center: Vector2<T>,
+    focal_length: T,
+) -> ComputeSolution<T> {
     let view_transform = rotation_matrix * axis;
     let mut view_transform = view_transform.to_homogeneous();
 
@@ -486,11 +1182,7 @@ pub fn compute_camera_pose<
     // to ckeck in blender
     // bpy.data.objects["<name>.fspy"].matrix_world
     let field_of_view = T::from(2.0).unwrap() * Float::atan(T::from(1.0).unwrap() / focal_length);
-    Ok(ComputeSolution::new(
-        view_transform,
-        ortho_center,
-        field_of_view,
-    ))
+    ComputeSolution::new(view_transform, ortho_center, field_of_view)
 }
 
 pub fn find_vanishing_point_for_lines<T: Float + Scalar + 'static>(
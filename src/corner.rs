@@ -0,0 +1,148 @@
+use image::GrayImage;
+use nalgebra::{Matrix2, Point2, Vector2};
+
+/// Image pixels around a dropped point searched for a corner.
+pub const SNAP_RADIUS: i32 = 8;
+/// Half size of the window summing the gradients of a corner candidate.
+const WINDOW: i32 = 2;
+/// Half size of the window of the subpixel refinement.
+const REFINE_WINDOW: i32 = 4;
+const REFINE_ITERATIONS: usize = 20;
+/// Smallest eigenvalue of the structure tensor per window pixel, for intensities in [0, 1],
+/// below which the neighbourhood has no corner to snap to.
+const MIN_RESPONSE: f32 = 1e-3;
+
+/// Strongest Shi-Tomasi corner within [`SNAP_RADIUS`] of `point`, refined to subpixel accuracy.
+/// Points are in image pixels from the top left corner of the image, pixel centers sit at
+/// half pixels. `None` when no clear corner is near.
+pub fn snap_to_corner(image: &GrayImage, point: Point2<f32>) -> Option<Point2<f32>> {
+    let (width, height) = image.dimensions();
+    if width < 3 || height < 3 {
+        return None;
+    }
+    let intensity = |x: i32, y: i32| {
+        let x = x.clamp(0, width as i32 - 1) as u32;
+        let y = y.clamp(0, height as i32 - 1) as u32;
+        image.get_pixel(x, y).0[0] as f32 / 255.0
+    };
+    let gradient = |x: i32, y: i32| {
+        Vector2::new(
+            (intensity(x + 1, y) - intensity(x - 1, y)) / 2.0,
+            (intensity(x, y + 1) - intensity(x, y - 1)) / 2.0,
+        )
+    };
+
+    let center = (point.x.floor() as i32, point.y.floor() as i32);
+    let mut best: Option<(f32, i32, i32)> = None;
+    for y in center.1 - SNAP_RADIUS..=center.1 + SNAP_RADIUS {
+        for x in center.0 - SNAP_RADIUS..=center.0 + SNAP_RADIUS {
+            let (dx, dy) = (x - center.0, y - center.1);
+            if dx * dx + dy * dy > SNAP_RADIUS * SNAP_RADIUS
+                || x < 0
+                || y < 0
+                || x >= width as i32
+                || y >= height as i32
+            {
+                continue;
+            }
+            let mut tensor = Matrix2::<f32>::zeros();
+            for wy in y - WINDOW..=y + WINDOW {
+                for wx in x - WINDOW..=x + WINDOW {
+                    let gradient = gradient(wx, wy);
+                    tensor += gradient * gradient.transpose();
+                }
+            }
+            let response = min_eigenvalue(&tensor);
+            if best.is_none_or(|(best, _, _)| response > best) {
+                best = Some((response, x, y));
+            }
+        }
+    }
+    let (response, x, y) = best?;
+    let window_pixels = ((2 * WINDOW + 1) * (2 * WINDOW + 1)) as f32;
+    if response / window_pixels < MIN_RESPONSE {
+        return None;
+    }
+
+    // the corner is where every gradient of the window is orthogonal to the direction to it
+    let corner = Point2::new(x as f32 + 0.5, y as f32 + 0.5);
+    let mut refined = corner;
+    let sigma = REFINE_WINDOW as f32 / 2.0;
+    for _ in 0..REFINE_ITERATIONS {
+        let (cx, cy) = (refined.x.floor() as i32, refined.y.floor() as i32);
+        let mut normal = Matrix2::<f32>::zeros();
+        let mut target = Vector2::<f32>::zeros();
+        for wy in cy - REFINE_WINDOW..=cy + REFINE_WINDOW {
+            for wx in cx - REFINE_WINDOW..=cx + REFINE_WINDOW {
+                let pixel = Vector2::new(wx as f32 + 0.5, wy as f32 + 0.5);
+                let distance = (pixel - refined.coords).norm_squared();
+                let weight = (-distance / (2.0 * sigma * sigma)).exp();
+                let gradient = gradient(wx, wy);
+                let outer = gradient * gradient.transpose() * weight;
+                normal += outer;
+                target += outer * pixel;
+            }
+        }
+        let Some(next) = normal.try_inverse().map(|inverse| inverse * target) else {
+            break;
+        };
+        let shift = (next - refined.coords).norm();
+        refined = Point2::from(next);
+        if shift < 0.01 {
+            break;
+        }
+    }
+    // the response peaks anywhere in the window around a corner, a refinement leaving that
+    // window found no corner at subpixel level
+    if (refined - corner).abs().max() > WINDOW as f32 {
+        return Some(corner);
+    }
+    Some(refined)
+}
+
+fn min_eigenvalue(tensor: &Matrix2<f32>) -> f32 {
+    let (a, b, c) = (tensor[(0, 0)], tensor[(0, 1)], tensor[(1, 1)]);
+    (a + c) / 2.0 - (((a - c) / 2.0).powi(2) + b * b).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checkerboard with a square edge of `square` pixels and a corner at `origin`,
+    /// anti-aliased by averaging 8 x 8 samples per pixel.
+    fn checkerboard(size: u32, origin: Point2<f32>, square: f32) -> GrayImage {
+        const SAMPLES: u32 = 8;
+        GrayImage::from_fn(size, size, |x, y| {
+            let white = (0..SAMPLES * SAMPLES)
+                .filter(|sample| {
+                    let offset = Vector2::new(sample % SAMPLES, sample / SAMPLES).cast::<f32>();
+                    let position = Vector2::new(x as f32, y as f32)
+                        + (offset.add_scalar(0.5)) / SAMPLES as f32
+                        - origin.coords;
+                    let cell = (position / square).map(f32::floor);
+                    (cell.x + cell.y).rem_euclid(2.0) == 0.0
+                })
+                .count();
+            image::Luma([(white * 255 / (SAMPLES * SAMPLES) as usize) as u8])
+        })
+    }
+
+    #[test]
+    fn snaps_to_the_subpixel_corner() {
+        let corner = Point2::new(20.3, 17.6);
+        let image = checkerboard(40, corner, 10.0);
+        let snapped = snap_to_corner(&image, Point2::new(23.0, 14.5)).unwrap();
+        assert!((snapped - corner).norm() < 0.15, "{snapped}");
+    }
+
+    #[test]
+    fn stays_put_without_a_corner() {
+        let image = GrayImage::from_pixel(40, 40, image::Luma([128]));
+        assert_eq!(snap_to_corner(&image, Point2::new(20.0, 20.0)), None);
+
+        // a straight edge has no corner either
+        let image = GrayImage::from_fn(40, 40, |x, _| image::Luma([if x < 20 { 0 } else { 255 }]));
+        assert_eq!(snap_to_corner(&image, Point2::new(20.0, 20.0)), None);
+    }
+}
@@ -0,0 +1,640 @@
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+
+use iced::{
+    Color, Element,
+    Length::{self},
+    Pixels, Point, Rectangle, Size, Vector,
+    advanced::{
+        Clipboard, Layout, Shell, Widget,
+        graphics::geometry::{self},
+        layout, mouse,
+        renderer::Style,
+        widget::{
+            Tree,
+            tree::{self},
+        },
+    },
+    event::Status,
+    widget::canvas::{self, Event, Fill, Stroke, Text},
+};
+use nalgebra::{Point3, Vector2, Vector3};
+
+use crate::{
+    Drawing, Edit, EditAxis, Measurement, Observation, ReferenceDistanceUnit, Shape,
+    compute::{data::ComputeSolution, measure_points, measurement_length},
+    utils::{
+        calculate_cursor_position_to_3d, closest_point_on_line_to_ray, relative_to_image_plane,
+        scale_point, scale_point_to_canvas, to_canvas,
+    },
+};
+
+const SHAPE_COLOR: Color = Color::from_rgba(0.3, 0.85, 0.9, 1.0);
+const ACTIVE_COLOR: Color = Color::from_rgba(0.8, 0.8, 0.2, 1.0);
+const MEASUREMENT_COLOR: Color = Color::from_rgba(0.95, 0.45, 0.8, 1.0);
+const OBSERVATION_COLOR: Color = Color::from_rgba(0.5, 0.9, 0.4, 1.0);
+
+/// Places 3D points on the solved planes and extrudes the resulting polylines along an axis.
+pub struct DrawTool<'a, Message, Theme = iced::Theme, Renderer = iced::Renderer>
+where
+    Renderer: geometry::Renderer,
+{
+    width: Length,
+    height: Length,
+    message_: PhantomData<Message>,
+    renderer_: PhantomData<Renderer>,
+    theme_: PhantomData<Theme>,
+    shapes_cache: geometry::Cache<Renderer>,
+    preview_cache: geometry::Cache<Renderer>,
+
+    compute_solution: RefCell<Option<ComputeSolution<f32>>>,
+    drawing: Rc<RefCell<Drawing>>,
+    edit: Edit,
+    draw_plane: EditAxis,
+    unit: ReferenceDistanceUnit,
+    on_change: Box<dyn Fn() -> Message + 'a>,
+}
+
+impl<'a, M, Theme, Renderer> DrawTool<'a, M, Theme, Renderer>
+where
+    Renderer: geometry::Renderer,
+{
+    const DEFAULT_SIZE: f32 = 100.0;
+    pub fn new(
+        compute_solution: &'a Option<ComputeSolution<f32>>,
+        drawing: Rc<RefCell<Drawing>>,
+        edit: Edit,
+        on_change: impl Fn() -> M + 'a,
+    ) -> Self {
+        DrawTool {
+            width: Length::Fixed(Self::DEFAULT_SIZE),
+            height: Length::Fixed(Self::DEFAULT_SIZE),
+            message_: PhantomData,
+            renderer_: PhantomData,
+            theme_: PhantomData,
+            shapes_cache: geometry::Cache::default(),
+            preview_cache: geometry::Cache::default(),
+            compute_solution: RefCell::new(compute_solution.clone()),
+            drawing,
+            edit,
+            draw_plane: EditAxis::EditZ,
+            unit: ReferenceDistanceUnit::default(),
+            on_change: Box::new(on_change),
+        }
+    }
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    /// Sets the height of the [`Canvas`].
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = height.into();
+        self
+    }
+
+    /// Normal of the plane new points are placed on, through the last point of the active shape.
+    pub fn draw_plane(mut self, draw_plane: EditAxis) -> Self {
+        self.draw_plane = draw_plane;
+        self
+    }
+
+    /// Unit the measurements are labeled with.
+    pub fn unit(mut self, unit: ReferenceDistanceUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    fn project(&self, location: &Vector3<f32>, size: Size) -> Option<Point> {
+        let compute_solution = self.compute_solution.borrow();
+        let compute_solution = compute_solution.as_ref()?;
+        if !compute_solution.is_in_front(location) {
+            return None;
+        }
+        let point = compute_solution.calculate_location_position_to_2d(location)?;
+        let point = to_canvas(size, &point);
+        Some(Point::new(point.x, point.y))
+    }
+
+    /// Point under the cursor on the draw plane through the last point of the active shape.
+    fn cursor_to_3d(&self, scale_cursor: Point, size: Size) -> Option<Vector3<f32>> {
+        let compute_solution = self.compute_solution.borrow();
+        let compute_solution = compute_solution.as_ref()?;
+        let drawing = self.drawing.borrow();
+        let last_point = drawing
+            .active
+            .and_then(|active| drawing.shapes.get(active))
+            .and_then(|shape| shape.points.last().copied())
+            .unwrap_or_else(Vector3::zeros);
+        calculate_cursor_position_to_3d(
+            &self.draw_plane,
+            compute_solution,
+            size.width / size.height,
+            &Vector2::new(scale_cursor.x, scale_cursor.y),
+            last_point,
+        )
+    }
+
+    /// Whether the cursor is over the first point of an active shape that can be closed.
+    fn closes_active_shape(&self, cursor: Point, size: Size) -> bool {
+        let drawing = self.drawing.borrow();
+        drawing
+            .active
+            .and_then(|active| drawing.shapes.get(active))
+            .filter(|shape| shape.points.len() > 2)
+            .and_then(|shape| shape.points.first())
+            .and_then(|first| self.project(first, size))
+            .is_some_and(|first| first.distance(cursor) < 10.0)
+    }
+
+    fn update_inner(
+        &self,
+        state: &mut State,
+        event: &Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Status {
+        // a drag ends wherever the button is released, also outside the canvas
+        let released = match (&self.edit, event) {
+            (Edit::Measure(_), Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))) => {
+                let Some(index) = state.measuring.take() else {
+                    return Status::Ignored;
+                };
+                let mut drawing = self.drawing.borrow_mut();
+                // a click without a drag measures nothing
+                if drawing.measurements[index]
+                    .line
+                    .0
+                    .distance(drawing.measurements[index].line.1)
+                    * bounds.width
+                    < 3.0
+                {
+                    drawing.measurements.remove(index);
+                }
+                state.publish = true;
+                self.shapes_cache.clear();
+                Some(Status::Captured)
+            }
+            (Edit::Extrude(_), Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))) => {
+                if state.extruding.take().is_some() {
+                    state.publish = true;
+                    Some(Status::Captured)
+                } else {
+                    Some(Status::Ignored)
+                }
+            }
+            _ => None,
+        };
+        if let Some(status) = released {
+            return status;
+        }
+        let Some(cursor) = cursor.position_over(bounds) else {
+            return Status::Ignored;
+        };
+        let adjusted_cursor = cursor - bounds.position();
+        let scale_cursor = scale_point(adjusted_cursor, bounds.size());
+        let cursor = Point::new(adjusted_cursor.x, adjusted_cursor.y);
+        match (&self.edit, event) {
+            (Edit::Draw, Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))) => {
+                if self.closes_active_shape(cursor, bounds.size()) {
+                    let mut drawing = self.drawing.borrow_mut();
+                    if let Some(active) = drawing.active.take() {
+                        drawing.shapes[active].closed = true;
+                    }
+                } else if let Some(point) = self.cursor_to_3d(scale_cursor, bounds.size()) {
+                    let mut drawing = self.drawing.borrow_mut();
+                    match drawing.active {
+                        Some(active) => drawing.shapes[active].points.push(point),
+                        None => {
+                            drawing.shapes.push(Shape {
+                                points: vec![point],
+                                ..Shape::default()
+                            });
+                            drawing.active = Some(drawing.shapes.len() - 1);
+                        }
+                    }
+                }
+                state.preview = None;
+                state.publish = true;
+                self.shapes_cache.clear();
+                self.preview_cache.clear();
+                Status::Captured
+            }
+            (Edit::Draw, Event::Mouse(mouse::Event::CursorMoved { position: _ })) => {
+                state.preview = self.cursor_to_3d(scale_cursor, bounds.size());
+                self.preview_cache.clear();
+                Status::Captured
+            }
+            (
+                Edit::Extrude(axis),
+                Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)),
+            ) => {
+                let Some(direction) = axis.direction::<f32>() else {
+                    return Status::Ignored;
+                };
+                let drawing = self.drawing.borrow();
+                state.extruding = drawing
+                    .shapes
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(index, shape)| {
+                        let extrusion = shape.extrusion.unwrap_or_else(Vector3::zeros);
+                        shape.points.iter().flat_map(move |point| {
+                            [(index, *point, *point), (index, *point, point + extrusion)]
+                        })
+                    })
+                    .find(|(_, _, handle)| {
+                        self.project(handle, bounds.size())
+                            .is_some_and(|handle| handle.distance(cursor) < 10.0)
+                    })
+                    .map(|(index, anchor, _)| (index, anchor, direction));
+                if state.extruding.is_some() {
+                    Status::Captured
+                } else {
+                    Status::Ignored
+                }
+            }
+            (Edit::Extrude(_), Event::Mouse(mouse::Event::CursorMoved { position: _ })) => {
+                let Some((index, anchor, direction)) = state.extruding else {
+                    return Status::Ignored;
+                };
+                let compute_solution = self.compute_solution.borrow();
+                let Some(compute_solution) = compute_solution.as_ref() else {
+                    return Status::Ignored;
+                };
+                let ratio = bounds.width / bounds.height;
+                let ray = compute_solution.camera_ray(&relative_to_image_plane(
+                    ratio,
+                    &Vector2::new(scale_cursor.x, scale_cursor.y),
+                ));
+                if let Some((camera_center, ray)) = ray
+                    && let Some(point) =
+                        closest_point_on_line_to_ray(&anchor, &direction, &camera_center, &ray)
+                    && let Some(shape) = self.drawing.borrow_mut().shapes.get_mut(index)
+                {
+                    shape.extrusion = Some(direction * (point - anchor).dot(&direction));
+                    self.shapes_cache.clear();
+                }
+                Status::Captured
+            }
+            (
+                Edit::Measure(axis),
+                Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)),
+            ) => {
+                let mut drawing = self.drawing.borrow_mut();
+                drawing.measurements.push(Measurement {
+                    line: (scale_cursor, scale_cursor),
+                    axis: *axis,
+                });
+                state.measuring = Some(drawing.measurements.len() - 1);
+                Status::Captured
+            }
+            (Edit::Measure(_), Event::Mouse(mouse::Event::CursorMoved { position: _ })) => {
+                let Some(index) = state.measuring else {
+                    return Status::Ignored;
+                };
+                if let Some(measurement) = self.drawing.borrow_mut().measurements.get_mut(index) {
+                    measurement.line.1 = scale_cursor;
+                    self.shapes_cache.clear();
+                }
+                Status::Captured
+            }
+            (Edit::Observe(id), Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))) => {
+                let mut drawing = self.drawing.borrow_mut();
+                drawing
+                    .observations
+                    .retain(|observation| observation.id != *id);
+                drawing.observations.push(Observation {
+                    id: *id,
+                    point: scale_cursor,
+                });
+                state.publish = true;
+                self.shapes_cache.clear();
+                Status::Captured
+            }
+            _ => Status::Ignored,
+        }
+    }
+
+    fn stroke_segment(
+        &self,
+        builder: &mut canvas::path::Builder,
+        (a, b): &(Vector3<f32>, Vector3<f32>),
+        size: Size,
+    ) {
+        let compute_solution = self.compute_solution.borrow();
+        let Some(compute_solution) = compute_solution.as_ref() else {
+            return;
+        };
+        compute_solution
+            .calculate_location_position_to_2d_frustum(&[Point3::from(*a), Point3::from(*b)])
+            .iter()
+            .for_each(|(start, end)| {
+                let start = to_canvas(size, &start.coords.xy());
+                let end = to_canvas(size, &end.coords.xy());
+                builder.move_to(Point::new(start.x, start.y));
+                builder.line_to(Point::new(end.x, end.y));
+            });
+    }
+
+    fn draw_inner(
+        &self,
+        state: &State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Renderer::Geometry> {
+        let shapes = self.shapes_cache.draw(renderer, bounds.size(), |frame| {
+            let drawing = self.drawing.borrow();
+            // observations are plain image points, drawn before a camera is solved
+            drawing.observations.iter().for_each(|observation| {
+                let point = scale_point_to_canvas(&observation.point, bounds.size());
+                let color = match self.edit {
+                    Edit::Observe(id) if id == observation.id => ACTIVE_COLOR,
+                    _ => OBSERVATION_COLOR,
+                };
+                let mut builder = canvas::path::Builder::new();
+                builder.circle(point, 5.0);
+                builder.move_to(Point::new(point.x - 8.0, point.y));
+                builder.line_to(Point::new(point.x + 8.0, point.y));
+                builder.move_to(Point::new(point.x, point.y - 8.0));
+                builder.line_to(Point::new(point.x, point.y + 8.0));
+                frame.stroke(
+                    &builder.build(),
+                    Stroke {
+                        style: canvas::Style::Solid(color),
+                        width: 1.5,
+                        ..Stroke::default()
+                    },
+                );
+                frame.fill_text(Text {
+                    content: format!("P{}", observation.id),
+                    position: Point::new(point.x + 7.0, point.y + 4.0),
+                    color,
+                    size: Pixels(11.0),
+                    ..Default::default()
+                });
+            });
+            if self.compute_solution.borrow().is_none() {
+                return;
+            }
+            drawing
+                .shapes
+                .iter()
+                .enumerate()
+                .for_each(|(index, shape)| {
+                    let color = if drawing.active == Some(index) {
+                        ACTIVE_COLOR
+                    } else {
+                        SHAPE_COLOR
+                    };
+
+                    shape.walls().iter().for_each(|wall| {
+                        let corners: Option<Vec<Point>> = wall
+                            .iter()
+                            .map(|corner| self.project(corner, bounds.size()))
+                            .collect();
+                        if let Some(corners) = corners {
+                            let mut builder = canvas::path::Builder::new();
+                            builder.move_to(corners[0]);
+                            corners[1..]
+                                .iter()
+                                .for_each(|corner| builder.line_to(*corner));
+                            builder.close();
+                            frame.fill(
+                                &builder.build(),
+                                Fill {
+                                    style: canvas::Style::Solid(Color { a: 0.15, ..color }),
+                                    ..Fill::default()
+                                },
+                            );
+                        }
+                    });
+
+                    let mut builder = canvas::path::Builder::new();
+                    shape
+                        .edges()
+                        .iter()
+                        .for_each(|edge| self.stroke_segment(&mut builder, edge, bounds.size()));
+                    shape
+                        .points
+                        .iter()
+                        .filter_map(|point| self.project(point, bounds.size()))
+                        .for_each(|point| builder.circle(point, 3.0));
+                    frame.stroke(
+                        &builder.build(),
+                        Stroke {
+                            style: canvas::Style::Solid(color),
+                            width: 2.0,
+                            ..Stroke::default()
+                        },
+                    );
+                });
+
+            let ratio = bounds.width / bounds.height;
+            drawing.measurements.iter().for_each(|measurement| {
+                let a = scale_point_to_canvas(&measurement.line.0, bounds.size());
+                let b = scale_point_to_canvas(&measurement.line.1, bounds.size());
+                let mut builder = canvas::path::Builder::new();
+                builder.circle(a, 3.0);
+                builder.circle(b, 3.0);
+                let compute_solution = self.compute_solution.borrow();
+                let points = compute_solution.as_ref().and_then(|compute_solution| {
+                    measure_points(
+                        compute_solution,
+                        ratio,
+                        &Vector2::new(measurement.line.0.x, measurement.line.0.y),
+                        &Vector2::new(measurement.line.1.x, measurement.line.1.y),
+                        &measurement.axis,
+                    )
+                });
+                let label = match points {
+                    Some(points) => {
+                        self.stroke_segment(&mut builder, &points, bounds.size());
+                        format!(
+                            "{:.2} {}",
+                            measurement_length(&points, &measurement.axis),
+                            self.unit
+                        )
+                    }
+                    None => "?".to_string(),
+                };
+                frame.stroke(
+                    &builder.build(),
+                    Stroke {
+                        style: canvas::Style::Solid(MEASUREMENT_COLOR),
+                        width: 2.0,
+                        ..Stroke::default()
+                    },
+                );
+                frame.fill_rectangle(
+                    Point::new(b.x + 6.0, b.y - 8.0),
+                    Size::new(label.len() as f32 * 6.5 + 4.0, 15.0),
+                    Fill {
+                        style: canvas::Style::Solid(Color::from_rgba(0.3, 0.3, 0.3, 0.9)),
+                        ..Fill::default()
+                    },
+                );
+                frame.fill_text(Text {
+                    content: label,
+                    position: Point::new(b.x + 8.0, b.y - 6.0),
+                    color: MEASUREMENT_COLOR,
+                    size: Pixels(11.0),
+                    ..Default::default()
+                });
+            });
+        });
+
+        let preview = self.preview_cache.draw(renderer, bounds.size(), |frame| {
+            let Some(preview) = state.preview else {
+                return;
+            };
+            let drawing = self.drawing.borrow();
+            let mut builder = canvas::path::Builder::new();
+            if let Some(last) = drawing
+                .active
+                .and_then(|active| drawing.shapes.get(active))
+                .and_then(|shape| shape.points.last())
+            {
+                self.stroke_segment(&mut builder, &(*last, preview), bounds.size());
+            }
+            if let Some(preview) = self.project(&preview, bounds.size()) {
+                builder.circle(preview, 4.0);
+            }
+            frame.stroke(
+                &builder.build(),
+                Stroke {
+                    style: canvas::Style::Solid(Color {
+                        a: 0.6,
+                        ..ACTIVE_COLOR
+                    }),
+                    width: 1.0,
+                    ..Stroke::default()
+                },
+            );
+        });
+
+        vec![shapes, preview]
+    }
+}
+
+impl<Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for DrawTool<'_, Message, Theme, Renderer>
+where
+    Renderer: geometry::Renderer,
+{
+    fn tag(&self) -> tree::Tag {
+        struct Tag<T>(T);
+        tree::Tag::of::<Tag<State>>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn size(&self) -> Size<Length> {
+        Size {
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _tree: &mut Tree,
+        _renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        layout::atomic(limits, self.width, self.height)
+    }
+
+    fn update(
+        &mut self,
+        tree: &mut Tree,
+        event: &Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _renderer: &Renderer,
+        _clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        _viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+
+        let state = tree.state.downcast_mut::<State>();
+
+        let event_status = self.update_inner(state, event, bounds, cursor);
+        if let Status::Captured = event_status {
+            shell.capture_event();
+            if std::mem::take(&mut state.publish) {
+                shell.publish((self.on_change)());
+            } else {
+                shell.request_redraw();
+            }
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        _tree: &Tree,
+        _layout: Layout<'_>,
+        _cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+        _renderer: &Renderer,
+    ) -> mouse::Interaction {
+        match self.edit {
+            Edit::Draw | Edit::Measure(_) | Edit::Observe(_) => mouse::Interaction::Crosshair,
+            Edit::Extrude(_) => mouse::Interaction::ResizingVertically,
+            _ => mouse::Interaction::default(),
+        }
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        _style: &Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+
+        if bounds.width < 1.0 || bounds.height < 1.0 {
+            return;
+        }
+        let state = tree.state.downcast_ref::<State>();
+
+        renderer.with_translation(Vector::new(bounds.x, bounds.y), |renderer| {
+            let layers = self.draw_inner(state, renderer, theme, bounds, cursor);
+
+            for layer in layers {
+                renderer.draw_geometry(layer);
+            }
+        });
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct State {
+    preview: Option<Vector3<f32>>,
+    /// shape index, grabbed base point and extrusion direction
+    extruding: Option<(usize, Vector3<f32>, Vector3<f32>)>,
+    measuring: Option<usize>,
+    publish: bool,
+}
+
+impl<'a, Message, Theme, Renderer> From<DrawTool<'a, Message, Theme, Renderer>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: 'a,
+    Renderer: 'a + geometry::Renderer,
+{
+    fn from(
+        draw_tool: DrawTool<'a, Message, Theme, Renderer>,
+    ) -> Element<'a, Message, Theme, Renderer> {
+        Element::new(draw_tool)
+    }
+}
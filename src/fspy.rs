@@ -3,8 +3,8 @@ use num_traits::Float;
 use tracing::trace;
 
 use crate::{
-    CalibrationSettingsBase, CameraParameters, CameraTransform, PrincipalPoint, SceneSettings,
-    compute::data::ComputeSolution,
+    CalibrationSettingsBase, CameraParameters, CameraTransform, PrincipalPoint,
+    ReferenceDistanceUnit, SceneSettings, compute::data::ComputeSolution,
 };
 use anyhow::Result;
 
@@ -24,6 +24,7 @@ pub fn compute_solution_to_scene_settings<
     image_width: u32,
     image_height: u32,
     compute_solution: &ComputeSolution<T>,
+    reference_distance_unit: ReferenceDistanceUnit,
 ) -> Result<SceneSettings> {
     let view_transform = compute_solution.view_transform().try_inverse().unwrap();
     trace!("view transform inverse: {view_transform}");
@@ -49,7 +50,7 @@ pub fn compute_solution_to_scene_settings<
         },
 
         calibration_settings_base: CalibrationSettingsBase {
-            reference_distance_unit: "Meters".to_string(),
+            reference_distance_unit: reference_distance_unit.to_string(),
         },
     };
 
@@ -0,0 +1,234 @@
+use std::{collections::HashMap, fmt::Display};
+
+use iced::{
+    Color, Point, Size,
+    advanced::graphics::geometry::{self, Frame},
+    widget::canvas::{self, LineDash, Stroke},
+};
+use nalgebra::{Point3, Vector2, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::data::ComputeSolution,
+    utils::{polygon_contains, polygon_normal, to_canvas},
+};
+
+/// Samples along an edge tested against the occluding faces in depth mode.
+const DEPTH_SAMPLES: usize = 16;
+/// Hits closer to the sample than this fraction of its distance count as the sample's own face.
+const DEPTH_EPSILON: f32 = 1e-3;
+const HIDDEN_DASH: [f32; 2] = [4.0, 4.0];
+
+/// How occluded edges of the overlay wireframes are found.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HiddenLineMode {
+    #[default]
+    Off,
+    /// Edges whose faces all point away from the camera are hidden, exact for convex objects.
+    BackFace,
+    /// Points along every edge are ray cast against all faces.
+    Depth,
+}
+
+impl HiddenLineMode {
+    pub const ALL: [HiddenLineMode; 3] = [
+        HiddenLineMode::Off,
+        HiddenLineMode::BackFace,
+        HiddenLineMode::Depth,
+    ];
+}
+
+impl Display for HiddenLineMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HiddenLineMode::Off => write!(f, "All lines"),
+            HiddenLineMode::BackFace => write!(f, "Back faces"),
+            HiddenLineMode::Depth => write!(f, "Depth"),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HiddenLines {
+    pub mode: HiddenLineMode,
+    /// Strokes hidden edges dashed instead of leaving them out.
+    pub dash_hidden: bool,
+}
+
+/// Planar polygon that may hide edges, prepared for ray casting.
+struct Occluder {
+    normal: Vector3<f32>,
+    origin: Vector3<f32>,
+    /// The two coordinates kept when dropping the dominant axis of the normal.
+    axes: (usize, usize),
+    outline: Vec<Vector2<f32>>,
+}
+
+impl Occluder {
+    fn new(polygon: &[Vector3<f32>]) -> Option<Self> {
+        let normal = polygon_normal(polygon)?;
+        let dominant = normal.iamax();
+        let axes = ((dominant + 1) % 3, (dominant + 2) % 3);
+        Some(Self {
+            normal,
+            origin: *polygon.first()?,
+            axes,
+            outline: polygon
+                .iter()
+                .map(|point| Vector2::new(point[axes.0], point[axes.1]))
+                .collect(),
+        })
+    }
+
+    /// Whether the segment from `camera` to `point` passes through the polygon before reaching `point`.
+    fn hides(&self, camera: &Vector3<f32>, point: &Vector3<f32>) -> bool {
+        let direction = point - camera;
+        let denominator = self.normal.dot(&direction);
+        if denominator.abs() < f32::EPSILON {
+            return false;
+        }
+        let along = self.normal.dot(&(self.origin - camera)) / denominator;
+        if along <= 0.0 || along >= 1.0 - DEPTH_EPSILON {
+            return false;
+        }
+        let hit = camera + direction * along;
+        polygon_contains(
+            &self.outline,
+            &Vector2::new(hit[self.axes.0], hit[self.axes.1]),
+        )
+    }
+}
+
+/// Splits edges given as consecutive point pairs into visible and hidden pairs,
+/// `faces` are the polygons that can hide them.
+pub fn split_hidden_edges(
+    compute_solution: &ComputeSolution<f32>,
+    segments: &[Point3<f32>],
+    faces: &[Vec<Vector3<f32>>],
+    mode: HiddenLineMode,
+) -> (Vec<Point3<f32>>, Vec<Point3<f32>>) {
+    let camera = compute_solution
+        .camera_ray(&Vector2::zeros())
+        .map(|(camera, _)| camera);
+    let Some(camera) = camera.filter(|_| !faces.is_empty()) else {
+        return (segments.to_vec(), Vec::new());
+    };
+
+    let mut visible = Vec::new();
+    let mut hidden = Vec::new();
+    match mode {
+        HiddenLineMode::Off => visible.extend_from_slice(segments),
+        HiddenLineMode::BackFace => {
+            // faces sharing an edge, found by the exact positions of its ends
+            let key = |a: &Vector3<f32>, b: &Vector3<f32>| {
+                let (a, b) = (a.map(f32::to_bits).into(), b.map(f32::to_bits).into());
+                if a < b { (a, b) } else { (b, a) }
+            };
+            let mut adjacent: HashMap<([u32; 3], [u32; 3]), Vec<bool>> = HashMap::new();
+            faces.iter().for_each(|face| {
+                let Some(normal) = polygon_normal(face) else {
+                    return;
+                };
+                let front = normal.dot(&(camera - face[0])) > 0.0;
+                face.iter()
+                    .zip(face.iter().cycle().skip(1))
+                    .for_each(|(a, b)| {
+                        adjacent.entry(key(a, b)).or_default().push(front);
+                    });
+            });
+            segments.chunks_exact(2).for_each(|edge| {
+                let back = adjacent
+                    .get(&key(&edge[0].coords, &edge[1].coords))
+                    .is_some_and(|fronts| fronts.iter().all(|front| !front));
+                if back { &mut hidden } else { &mut visible }.extend_from_slice(edge);
+            });
+        }
+        HiddenLineMode::Depth => {
+            let occluders: Vec<Occluder> = faces
+                .iter()
+                .filter_map(|face| Occluder::new(face))
+                .collect();
+            segments.chunks_exact(2).for_each(|edge| {
+                let at = |t: f32| edge[0] + (edge[1] - edge[0]) * t;
+                let occluded: Vec<bool> = (0..DEPTH_SAMPLES)
+                    .map(|sample| {
+                        let point = at((sample as f32 + 0.5) / DEPTH_SAMPLES as f32);
+                        occluders
+                            .iter()
+                            .any(|occluder| occluder.hides(&camera, &point.coords))
+                    })
+                    .collect();
+                // merge runs of samples with the same visibility into sub segments
+                let mut start = 0;
+                for end in 1..=DEPTH_SAMPLES {
+                    if end < DEPTH_SAMPLES && occluded[end] == occluded[start] {
+                        continue;
+                    }
+                    let target = if occluded[start] {
+                        &mut hidden
+                    } else {
+                        &mut visible
+                    };
+                    target.push(at(start as f32 / DEPTH_SAMPLES as f32));
+                    target.push(at(end as f32 / DEPTH_SAMPLES as f32));
+                    start = end;
+                }
+            });
+        }
+    }
+    (visible, hidden)
+}
+
+/// Strokes the edges clipped to the camera frustum, with the hidden ones left out or dashed.
+pub fn draw_wireframe<Renderer: geometry::Renderer>(
+    frame: &mut Frame<Renderer>,
+    compute_solution: &ComputeSolution<f32>,
+    segments: &[Point3<f32>],
+    faces: &[Vec<Vector3<f32>>],
+    hidden_lines: HiddenLines,
+    size: Size,
+    color: Color,
+) {
+    let (visible, hidden) =
+        split_hidden_edges(compute_solution, segments, faces, hidden_lines.mode);
+    let path = |segments: &[Point3<f32>]| {
+        let mut builder = canvas::path::Builder::new();
+        segments.chunks(2).for_each(|points| {
+            compute_solution
+                .calculate_location_position_to_2d_frustum(points)
+                .iter()
+                .for_each(|&(start, end)| {
+                    let start = to_canvas(size, &start.coords.xy());
+                    let end = to_canvas(size, &end.coords.xy());
+                    builder.move_to(Point::new(start.x, start.y));
+                    builder.line_to(Point::new(end.x, end.y));
+                });
+        });
+        builder.build()
+    };
+    frame.stroke(
+        &path(&visible),
+        Stroke {
+            style: canvas::Style::Solid(color),
+            width: 1.0,
+            ..Stroke::default()
+        },
+    );
+    if hidden_lines.dash_hidden && !hidden.is_empty() {
+        frame.stroke(
+            &path(&hidden),
+            Stroke {
+                style: canvas::Style::Solid(Color {
+                    a: color.a * 0.5,
+                    ..color
+                }),
+                width: 1.0,
+                line_dash: LineDash {
+                    segments: &HIDDEN_DASH,
+                    offset: 0,
+                },
+                ..Stroke::default()
+            },
+        );
+    }
+}
@@ -0,0 +1,143 @@
+/// Snapshots kept for undo, the oldest are dropped first.
+const MAX_UNDO: usize = 100;
+
+/// Undo and redo stacks of snapshots of the edited state.
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    /// Snapshot of the state as last recorded or restored.
+    current: Option<T>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            current: None,
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> History<T> {
+    /// Records `snapshot` when the state changed since the last one, which drops the redo stack,
+    /// and tells whether it did. The first snapshot only becomes the state to return to.
+    pub fn checkpoint(&mut self, snapshot: T) -> bool {
+        match self.current.replace(snapshot) {
+            Some(previous) if Some(&previous) != self.current.as_ref() => {
+                self.undo.push(previous);
+                if self.undo.len() > MAX_UNDO {
+                    self.undo.remove(0);
+                }
+                self.redo.clear();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Replaces the state reached by the last recorded edit with `snapshot`, so a change
+    /// following the edit on its own is undone together with it.
+    pub fn amend(&mut self, snapshot: T) {
+        self.current = Some(snapshot);
+    }
+
+    /// Snapshot to restore to go one edit back.
+    pub fn undo(&mut self) -> Option<T> {
+        let snapshot = self.undo.pop()?;
+        if let Some(current) = self.current.take() {
+            self.redo.push(current);
+        }
+        Some(snapshot)
+    }
+
+    /// Snapshot to restore to go one undone edit forward.
+    pub fn redo(&mut self) -> Option<T> {
+        let snapshot = self.redo.pop()?;
+        if let Some(current) = self.current.take() {
+            self.undo.push(current);
+        }
+        Some(snapshot)
+    }
+
+    /// Takes the state reached after restoring a snapshot as the current one, keeping both stacks.
+    pub fn restored(&mut self, snapshot: T) {
+        self.current = Some(snapshot);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo_walk_the_edits_in_order() {
+        let mut history = History::default();
+        assert!(!history.checkpoint(0));
+        assert!(history.checkpoint(1));
+        assert!(history.checkpoint(2));
+        // an unchanged state is not an edit
+        assert!(!history.checkpoint(2));
+
+        let snapshot = history.undo().unwrap();
+        assert_eq!(snapshot, 1);
+        history.restored(snapshot);
+        let snapshot = history.undo().unwrap();
+        assert_eq!(snapshot, 0);
+        history.restored(snapshot);
+        assert!(!history.can_undo());
+        assert_eq!(history.undo(), None);
+
+        let snapshot = history.redo().unwrap();
+        assert_eq!(snapshot, 1);
+        history.restored(snapshot);
+        assert_eq!(history.redo(), Some(2));
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn a_new_edit_drops_the_redo_stack() {
+        let mut history = History::default();
+        history.checkpoint(0);
+        history.checkpoint(1);
+        let snapshot = history.undo().unwrap();
+        history.restored(snapshot);
+        assert!(history.can_redo());
+
+        assert!(history.checkpoint(3));
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(), Some(0));
+    }
+
+    #[test]
+    fn amend_merges_into_the_last_edit() {
+        let mut history = History::default();
+        history.checkpoint(0);
+        history.checkpoint(1);
+        history.amend(2);
+        assert_eq!(history.undo(), Some(0));
+        assert_eq!(history.redo(), Some(2));
+    }
+
+    #[test]
+    fn only_the_latest_edits_are_kept() {
+        let mut history = History::default();
+        for snapshot in 0..=MAX_UNDO + 10 {
+            history.checkpoint(snapshot);
+        }
+        let mut oldest = None;
+        while let Some(snapshot) = history.undo() {
+            history.restored(snapshot);
+            oldest = Some(snapshot);
+        }
+        assert_eq!(oldest, Some(10));
+    }
+}
@@ -0,0 +1,151 @@
+use iced::{
+    Color, Pixels, Point, Size,
+    advanced::graphics::geometry::{self, Frame},
+    widget::canvas::{self, Fill, Stroke, Text},
+};
+use nalgebra::Vector2;
+
+use crate::{
+    compute::data::ComputeSolution,
+    utils::{get_extension_for_line_within_bounds, to_canvas},
+};
+
+pub const AXIS_COLORS: [Color; 3] = [
+    Color::from_rgba(0.9, 0.3, 0.3, 1.0),
+    Color::from_rgba(0.3, 0.9, 0.3, 1.0),
+    Color::from_rgba(0.3, 0.5, 0.9, 1.0),
+];
+const HORIZON_COLOR: Color = Color::from_rgba(0.95, 0.85, 0.3, 0.9);
+const EDGE_OFFSET: f32 = 20.0;
+
+/// Canvas positions of the X, Y and Z vanishing points, may lie outside of the canvas.
+pub fn vanishing_points_to_canvas(
+    compute_solution: &ComputeSolution<f32>,
+    size: Size,
+) -> [Option<Vector2<f32>>; 3] {
+    compute_solution
+        .vanishing_points()
+        .map(|point| point.map(|point| to_canvas(size, &point)))
+}
+
+/// Draws the horizon line, the principal point and the vanishing points, the ones
+/// outside of the canvas as an arrow on the border pointing towards them.
+pub fn draw_horizon<Renderer: geometry::Renderer>(
+    frame: &mut Frame<Renderer>,
+    compute_solution: &ComputeSolution<f32>,
+    vanishing_points: &[Option<Vector2<f32>>; 3],
+    size: Size,
+) {
+    let horizon = compute_solution.horizon_line();
+    let normal = horizon.xy().norm_squared();
+    if normal > f32::EPSILON {
+        let on_line = -horizon.xy() * horizon.z / normal;
+        let along = Vector2::new(-horizon.y, horizon.x);
+        let a = to_canvas(size, &on_line);
+        let b = to_canvas(size, &(on_line + along));
+        if let Some(extension) = get_extension_for_line_within_bounds(
+            &(Point::new(a.x, a.y), Point::new(b.x, b.y)),
+            size,
+        ) {
+            let mut builder = canvas::path::Builder::new();
+            builder.move_to(extension[0]);
+            builder.line_to(extension[1]);
+            frame.stroke(
+                &builder.build(),
+                Stroke {
+                    style: canvas::Style::Solid(HORIZON_COLOR),
+                    width: 1.5,
+                    ..Stroke::default()
+                },
+            );
+            frame.fill_text(Text {
+                content: "horizon".to_string(),
+                position: Point::new(extension[0].x + 4.0, extension[0].y - 14.0),
+                color: HORIZON_COLOR,
+                size: Pixels(11.0),
+                ..Default::default()
+            });
+        }
+    }
+
+    let principal_point = to_canvas(size, &compute_solution.ortho_center());
+    let principal_point = Point::new(principal_point.x, principal_point.y);
+    let mut builder = canvas::path::Builder::new();
+    builder.move_to(Point::new(principal_point.x - 6.0, principal_point.y - 6.0));
+    builder.line_to(Point::new(principal_point.x + 6.0, principal_point.y + 6.0));
+    builder.move_to(Point::new(principal_point.x + 6.0, principal_point.y - 6.0));
+    builder.line_to(Point::new(principal_point.x - 6.0, principal_point.y + 6.0));
+    frame.stroke(
+        &builder.build(),
+        Stroke {
+            style: canvas::Style::Solid(Color::WHITE),
+            width: 1.5,
+            ..Stroke::default()
+        },
+    );
+
+    let center = Point::new(size.width / 2.0, size.height / 2.0);
+    vanishing_points
+        .iter()
+        .zip(AXIS_COLORS)
+        .zip(["X", "Y", "Z"])
+        .for_each(|((point, color), label)| {
+            let Some(point) = point else {
+                return;
+            };
+            let inside =
+                point.x >= 0.0 && point.x <= size.width && point.y >= 0.0 && point.y <= size.height;
+            let mut builder = canvas::path::Builder::new();
+            let label_position = if inside {
+                let point = Point::new(point.x, point.y);
+                builder.circle(point, 7.0);
+                builder.circle(point, 2.0);
+                point
+            } else {
+                let direction = Vector2::new(point.x - center.x, point.y - center.y);
+                let reach = ((size.width / 2.0 - EDGE_OFFSET) / direction.x.abs())
+                    .min((size.height / 2.0 - EDGE_OFFSET) / direction.y.abs());
+                let tip = Point::new(
+                    center.x + direction.x * reach,
+                    center.y + direction.y * reach,
+                );
+                let direction = direction.normalize();
+                let side = Vector2::new(-direction.y, direction.x);
+                let base = Vector2::new(tip.x, tip.y) - direction * 14.0;
+                builder.move_to(tip);
+                builder.line_to(Point::new(base.x + side.x * 6.0, base.y + side.y * 6.0));
+                builder.line_to(Point::new(base.x - side.x * 6.0, base.y - side.y * 6.0));
+                builder.close();
+                Point::new(base.x, base.y)
+            };
+            let path = builder.build();
+            if inside {
+                frame.stroke(
+                    &path,
+                    Stroke {
+                        style: canvas::Style::Solid(color),
+                        width: 2.0,
+                        ..Stroke::default()
+                    },
+                );
+            } else {
+                frame.fill(
+                    &path,
+                    Fill {
+                        style: canvas::Style::Solid(color),
+                        ..Fill::default()
+                    },
+                );
+            }
+            frame.fill_text(Text {
+                content: format!("VP {label}"),
+                position: Point::new(
+                    (label_position.x + 8.0).min(size.width - 40.0),
+                    (label_position.y + 8.0).min(size.height - 14.0),
+                ),
+                color,
+                size: Pixels(11.0),
+                ..Default::default()
+            });
+        });
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use iced::Size;
+use nalgebra::{Matrix3, Vector2, Vector3};
+
+use crate::{AxisData, compute::image_plane_vanishing_points};
+
+/// Field of view and principal point of a camera estimated from several of its photos.
+#[derive(Debug, Clone)]
+pub struct SharedIntrinsics {
+    /// Horizontal field of view in radians.
+    pub field_of_view: f32,
+    /// Principal point in image plane coordinates.
+    pub principal_point: Vector2<f32>,
+    /// Largest deviation from a right angle between the axes of each image, in radians,
+    /// `None` for images without usable vanishing points.
+    pub residuals: Vec<Option<f32>>,
+}
+
+/// Jointly estimates focal length and principal point from the vanishing points of all images.
+///
+/// Every pair of orthogonal vanishing points gives (v_i - p)·(v_j - p) + f² = 0, which is linear
+/// in p and c = |p|² + f². One image gives three equations and the orthocenter, more images
+/// are solved in the least squares sense. Images must share the aspect ratio (and orientation)
+/// for image plane coordinates to match.
+///
+/// Only vanishing points are used, twist correspondences do not constrain the estimate, so
+/// images should have their axis lines placed.
+pub fn estimate_shared_intrinsics(images: &[(&AxisData, Size<f32>)]) -> Result<SharedIntrinsics> {
+    let vanishing_points: Vec<Vec<Vector2<f64>>> = images
+        .iter()
+        .map(|(axis_data, image_size)| {
+            image_plane_vanishing_points(&axis_data.axis_lines, *image_size)
+                .iter()
+                .map(|point| point.cast::<f64>())
+                .collect()
+        })
+        .collect();
+
+    let mut normal = Matrix3::<f64>::zeros();
+    let mut right = Vector3::<f64>::zeros();
+    let mut equations = 0;
+    vanishing_points.iter().for_each(|points| {
+        for (i, j) in [(0, 1), (0, 2), (1, 2)] {
+            let (a, b) = (points[i], points[j]);
+            if !(a.iter().chain(b.iter()).all(|value| value.is_finite())) {
+                continue;
+            }
+            // far vanishing points would dominate the algebraic residual, this weight
+            // brings every equation close to the cosine of the angle between the axes
+            let weight = 1.0 / (1.0 + a.norm() * b.norm());
+            let sum = a + b;
+            let row = Vector3::new(-sum.x, -sum.y, 1.0) * weight;
+            normal += row * row.transpose();
+            right -= row * a.dot(&b) * weight;
+            equations += 1;
+        }
+    });
+    if equations < 3 {
+        return Err(anyhow::anyhow!("not enough vanishing points"));
+    }
+
+    let solution = normal
+        .try_inverse()
+        .ok_or_else(|| anyhow::anyhow!("vanishing points do not constrain the camera"))?
+        * right;
+    let principal_point = Vector2::new(solution.x, solution.y);
+    let focal_length_squared = solution.z - principal_point.norm_squared();
+    if focal_length_squared <= 0.0 {
+        return Err(anyhow::anyhow!(
+            "vanishing points do not meet at a real focal length"
+        ));
+    }
+    let focal_length = focal_length_squared.sqrt();
+
+    let residuals = vanishing_points
+        .iter()
+        .map(|points| {
+            let directions: Vec<Vector3<f64>> = points
+                .iter()
+                .map(|point| {
+                    let offset = point - principal_point;
+                    Vector3::new(offset.x, offset.y, -focal_length).normalize()
+                })
+                .collect();
+            let error = [(0, 1), (0, 2), (1, 2)]
+                .iter()
+                .map(|&(i, j)| directions[i].dot(&directions[j]).abs().min(1.0).asin())
+                .fold(0.0, f64::max);
+            error.is_finite().then_some(error as f32)
+        })
+        .collect();
+
+    Ok(SharedIntrinsics {
+        field_of_view: (2.0 * (1.0 / focal_length).atan()) as f32,
+        principal_point: principal_point.cast(),
+        residuals,
+    })
+}
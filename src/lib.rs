@@ -1,15 +1,36 @@
+pub mod adjust;
+pub mod bundle;
+pub mod colmap;
 pub mod compute;
+pub mod corner;
 pub mod decoder;
+pub mod draw_tool;
 pub mod encoder;
 pub mod frustum;
 pub mod fspy;
+pub mod hidden_lines;
+pub mod history;
+pub mod horizon;
+pub mod intrinsics;
+pub mod mesh;
+pub mod nudge;
+pub mod obj;
+pub mod project;
 pub mod read_state;
+pub mod reference;
+pub mod render;
+pub mod safe_write;
+pub mod texture;
+pub mod triangulation;
 pub mod twist_pose_all;
+pub mod uncertainty;
 pub mod utils;
-use std::fmt::Debug;
+pub mod vanishing_points;
+use std::fmt::{Debug, Display};
 
 use iced::Point;
-use nalgebra::{Point2, Point3, Vector3};
+use nalgebra::{Point2, Point3, Scalar, Vector2, Vector3};
+use num_traits::Float;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,6 +86,9 @@ pub enum Edit {
     ControlPoint(EditAxis),
     Draw,
     Extrude(EditAxis),
+    Measure(EditAxis),
+    /// Places the observation of the world point with this id.
+    Observe(usize),
     Scale(EditAxis),
     VanishingPoint(EditAxis),
     VanishingLines(EditAxis),
@@ -72,7 +96,7 @@ pub enum Edit {
     None,
 }
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EditAxis {
     EditX,
     EditY,
@@ -80,11 +104,202 @@ pub enum EditAxis {
     #[default]
     None,
 }
+
+impl EditAxis {
+    pub const ALL: [EditAxis; 3] = [EditAxis::EditX, EditAxis::EditY, EditAxis::EditZ];
+
+    pub fn direction<T: Float + Scalar>(&self) -> Option<Vector3<T>> {
+        let zero = T::from(0.0).unwrap();
+        let one = T::from(1.0).unwrap();
+        match self {
+            EditAxis::EditX => Some(Vector3::new(one, zero, zero)),
+            EditAxis::EditY => Some(Vector3::new(zero, one, zero)),
+            EditAxis::EditZ => Some(Vector3::new(zero, zero, one)),
+            EditAxis::None => None,
+        }
+    }
+}
+
+impl Display for EditAxis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditAxis::EditX => write!(f, "X"),
+            EditAxis::EditY => write!(f, "Y"),
+            EditAxis::EditZ => write!(f, "Z"),
+            EditAxis::None => write!(f, "None"),
+        }
+    }
+}
+
+/// Units known by fSpy, written to `CalibrationSettingsBase.reference_distance_unit`.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReferenceDistanceUnit {
+    NoUnit,
+    Millimeters,
+    Centimeters,
+    #[default]
+    Meters,
+    Kilometers,
+    Inches,
+    Feet,
+    Miles,
+}
+
+impl ReferenceDistanceUnit {
+    pub const ALL: [ReferenceDistanceUnit; 8] = [
+        ReferenceDistanceUnit::NoUnit,
+        ReferenceDistanceUnit::Millimeters,
+        ReferenceDistanceUnit::Centimeters,
+        ReferenceDistanceUnit::Meters,
+        ReferenceDistanceUnit::Kilometers,
+        ReferenceDistanceUnit::Inches,
+        ReferenceDistanceUnit::Feet,
+        ReferenceDistanceUnit::Miles,
+    ];
+}
+
+impl Display for ReferenceDistanceUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceDistanceUnit::NoUnit => write!(f, "No unit"),
+            ReferenceDistanceUnit::Millimeters => write!(f, "Millimeters"),
+            ReferenceDistanceUnit::Centimeters => write!(f, "Centimeters"),
+            ReferenceDistanceUnit::Meters => write!(f, "Meters"),
+            ReferenceDistanceUnit::Kilometers => write!(f, "Kilometers"),
+            ReferenceDistanceUnit::Inches => write!(f, "Inches"),
+            ReferenceDistanceUnit::Feet => write!(f, "Feet"),
+            ReferenceDistanceUnit::Miles => write!(f, "Miles"),
+        }
+    }
+}
+
+/// Segment drawn in the image along one of the axes, starting on the XY plane
+/// through the origin, with its real world length.
+#[derive(Debug, Clone)]
+pub struct ReferenceDistance {
+    pub line: (Point, Point),
+    pub axis: EditAxis,
+    pub length: f32,
+}
+/// Polyline placed on the solved planes, optionally extruded along an axis into walls.
+#[derive(Debug, Clone, Default)]
+pub struct Shape {
+    pub points: Vec<Vector3<f32>>,
+    pub closed: bool,
+    pub extrusion: Option<Vector3<f32>>,
+}
+
+impl Shape {
+    /// Segments of the polyline, including the closing one.
+    pub fn segments(&self) -> Vec<(Vector3<f32>, Vector3<f32>)> {
+        let mut segments: Vec<_> = self
+            .points
+            .windows(2)
+            .map(|points| (points[0], points[1]))
+            .collect();
+        if self.closed
+            && self.points.len() > 2
+            && let (Some(first), Some(last)) = (self.points.first(), self.points.last())
+        {
+            segments.push((*last, *first));
+        }
+        segments
+    }
+
+    /// Wall quads, one per segment, empty without an extrusion.
+    pub fn walls(&self) -> Vec<[Vector3<f32>; 4]> {
+        let Some(extrusion) = self.extrusion else {
+            return Vec::new();
+        };
+        self.segments()
+            .into_iter()
+            .map(|(a, b)| [a, b, b + extrusion, a + extrusion])
+            .collect()
+    }
+
+    /// Planar faces: the closed polyline, its extruded copy and the walls.
+    pub fn faces(&self) -> Vec<Vec<Vector3<f32>>> {
+        let mut faces: Vec<Vec<Vector3<f32>>> =
+            self.walls().into_iter().map(|wall| wall.to_vec()).collect();
+        if self.closed && self.points.len() > 2 {
+            faces.push(self.points.clone());
+            if let Some(extrusion) = self.extrusion {
+                faces.push(self.points.iter().map(|point| point + extrusion).collect());
+            }
+        }
+        faces
+    }
+
+    /// Every edge to draw: the polyline, its extruded copy and the vertical edges between them.
+    pub fn edges(&self) -> Vec<(Vector3<f32>, Vector3<f32>)> {
+        let mut edges = self.segments();
+        if let Some(extrusion) = self.extrusion {
+            edges.extend(
+                self.segments()
+                    .into_iter()
+                    .map(|(a, b)| (a + extrusion, b + extrusion)),
+            );
+            edges.extend(self.points.iter().map(|point| (*point, point + extrusion)));
+        }
+        edges
+    }
+}
+
+/// Two image points measured against the ground plane: a ground distance for
+/// `EditAxis::None`, otherwise the height of the second point above the first along `axis`.
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub line: (Point, Point),
+    pub axis: EditAxis,
+}
+
+/// Image point of a feature seen in several images, `id` names the world point across images.
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub id: usize,
+    pub point: Point,
+}
+
+/// Shapes and measurements placed on the image, `active` is the shape new points are appended to.
+#[derive(Debug, Clone, Default)]
+pub struct Drawing {
+    pub shapes: Vec<Shape>,
+    pub active: Option<usize>,
+    pub measurements: Vec<Measurement>,
+    pub observations: Vec<Observation>,
+}
+
 #[derive(Default)]
 pub struct PerspectiveState {
     pub edit: Edit,
 }
 
+/// Name and state of a twist correspondence, in the order of the twist points.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TwistLabel {
+    pub name: String,
+    /// Left out of the solve but kept in the list.
+    pub disabled: bool,
+    /// Added on the image and disabled until its 3D coordinate is entered.
+    #[serde(skip)]
+    pub awaiting_coordinate: bool,
+}
+
+/// The twist correspondences taking part in the solve, a point without a label is enabled.
+pub fn enabled_twist_points(
+    twist_points: &[Point3<f32>],
+    twist_points_2d: &[Point2<f32>],
+    twist_labels: &[TwistLabel],
+) -> (Vec<Point3<f32>>, Vec<Point2<f32>>) {
+    twist_points
+        .iter()
+        .zip(twist_points_2d)
+        .enumerate()
+        .filter(|(index, _)| !twist_labels.get(*index).is_some_and(|label| label.disabled))
+        .map(|(_, (point, point_2d))| (*point, *point_2d))
+        .unzip()
+}
+
 #[derive(Debug, Clone)]
 pub struct AxisData {
     pub axis_lines: Vec<(Point, Point)>,
@@ -94,7 +309,16 @@ pub struct AxisData {
     pub custom_scale: Option<f32>,
     pub twist_points: Option<Vec<Point3<f32>>>,
     pub twist_points_2d: Option<Vec<Point2<f32>>>,
+    /// Names and disabled flags of the twist correspondences, may be shorter than the points.
+    pub twist_labels: Vec<TwistLabel>,
     pub field_of_view: Option<f32>,
+    pub lock_field_of_view: bool,
+    /// Takes the field of view and principal point estimated jointly with the other images of the same camera.
+    pub shared_intrinsics: bool,
+    /// Principal point in image plane coordinates, used with a locked field of view.
+    pub principal_point: Option<Vector2<f32>>,
+    pub reference_distance: Option<ReferenceDistance>,
+    pub reference_distance_unit: ReferenceDistanceUnit,
 }
 
 impl Default for AxisData {
@@ -141,7 +365,28 @@ impl Default for AxisData {
                 Point2::new(0.49192706, 0.41689816),
                 Point2::new(0.6676042, 0.6798148),
             ]),
+            twist_labels: Vec::new(),
             field_of_view: Some(35.0),
+            lock_field_of_view: false,
+            shared_intrinsics: false,
+            principal_point: None,
+            reference_distance: None,
+            reference_distance_unit: ReferenceDistanceUnit::default(),
         }
     }
 }
+
+impl AxisData {
+    /// The axis lines were placed by the user, the default lines only show where to start.
+    pub fn has_vanishing_lines(&self) -> bool {
+        self.axis_lines != AxisData::default().axis_lines
+    }
+
+    /// Principal point in image plane coordinates the pose solvers take, the image center
+    /// unless the image takes the shared intrinsics.
+    pub fn solver_principal_point(&self) -> Vector2<f32> {
+        self.principal_point
+            .filter(|_| self.shared_intrinsics)
+            .unwrap_or_else(Vector2::zeros)
+    }
+}
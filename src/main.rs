@@ -1,40 +1,111 @@
-use clap::Parser;
-use cv::FeatureWorldMatch;
+use ::image::GrayImage;
+use clap::{Parser, Subcommand, ValueEnum};
 use iced::Alignment::{self};
-use iced::Length::Fill;
 use iced::alignment::{Horizontal, Vertical};
 use iced::futures::executor::block_on;
-use iced::widget::scrollable::{Direction, Scrollbar};
+use iced::widget::image::FilterMethod;
 use iced::widget::{
-    button, center, column, container, image, mouse_area, row, scrollable, slider, stack, text,
+    button, center, column, container, image, mouse_area, pick_list, row, scrollable, slider,
+    stack, text, text_input,
 };
-use iced::{Element, Length, Size, Task, Theme, keyboard};
-use lambda_twist::LambdaTwist;
-use nalgebra::{Matrix4, Point2, Point3, Vector2, Vector3};
-use perspective::AxisData;
+use iced::{
+    Element, Length, Point, Size, Subscription, Task, Theme, Vector, event, keyboard, mouse, window,
+};
+use nalgebra::{Point2, Point3, Vector2, Vector3};
+use perspective::adjust::{DisplayAdjustments, DisplayPhoto};
+use perspective::bundle::{BundleAdjustment, BundleImage, bundle_adjust};
+use perspective::colmap::{ColmapImage, to_colmap};
 use perspective::compute::data::ComputeSolution;
-use perspective::compute::{Lines, StoreLine, StorePoint, StorePoint3d, store_scene_data_to_file};
-use perspective::read_state::{ImageData, load};
+use perspective::compute::{
+    FocalLengthDeviation, Lines, StoreLine, StorePoint, StorePoint3d, StoreReferenceDistance,
+    compute_camera_pose_twist, compute_vanishing_points_pose, measure_points, measurement_length,
+    read_lines_from_file, read_points, read_points_from_file, store_scene_data_to_file,
+};
+use perspective::corner::snap_to_corner;
+use perspective::draw_tool::DrawTool;
+use perspective::hidden_lines::{HiddenLineMode, HiddenLines};
+use perspective::history::History;
+use perspective::intrinsics::{SharedIntrinsics, estimate_shared_intrinsics};
+use perspective::mesh::Mesh;
+use perspective::obj::{ObjTexture, to_mtl, to_obj};
+use perspective::project::{
+    ExportSettings, PROJECT_EXTENSION, Project, ProjectImage, StoreReference, StoreSolution,
+    StoreWorldPoint, read_project, relative_image_path, resolve_image_path, write_project,
+};
+use perspective::read_state::{ImageData, load, load_image};
+use perspective::reference::{ReferenceBox, ReferenceGeometry, ReferencePlane};
+use perspective::render::{Overlay, render_overlay};
+use perspective::safe_write::write_atomic;
+use perspective::texture::extract_plane_texture;
+use perspective::triangulation::{TriangulatedPoint, View, triangulate};
 use perspective::twist_pose_all::ComputeCameraPoseTwist;
+use perspective::uncertainty::{
+    CalibrationUncertainty, Interval, twist_uncertainty, vanishing_points_uncertainty,
+};
+use perspective::vanishing_points::ComputeCameraPoseVanishingPoints;
+use perspective::{
+    AxisData, Drawing, Edit, EditAxis, Observation, ReferenceDistance, ReferenceDistanceUnit,
+    TwistLabel, enabled_twist_points,
+};
 use std::cell::RefCell;
-use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
-use std::fs::File;
-use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use tracing::{info, trace};
+use std::time::{Duration, Instant};
+use tracing::{error, info, trace};
 use tracing_subscriber::EnvFilter;
 use zoomer::context_menu::ContextMenu;
 use zoomer::editor_component::{Action, EditorComponent};
+use zoomer::loupe::Loupe;
+use zoomer::pan_zoom::{MAX_ZOOM, MIN_ZOOM, PanZoom};
 
 use anyhow::Result;
 
+/// Solves per uncertainty estimate.
+const UNCERTAINTY_SAMPLES: usize = 200;
+/// Quiet time after the last edit before it is saved.
+const AUTOSAVE_DELAY: Duration = Duration::from_secs(2);
+/// Pause in the display adjustments after which the preview is replaced by the full photo.
+const DISPLAY_DELAY: Duration = Duration::from_millis(250);
+/// Magnification of the loupe shown while a handle is dragged.
+const LOUPE_ZOOM: f32 = 4.0;
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
     #[arg(short, long, value_delimiter = ' ', num_args = 0..)]
     images: Vec<String>,
+    /// `.perspective` project to open instead of the images
+    #[arg(short, long)]
+    project: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Solves the camera of an image from its `.points` file and writes the overlay to PNG
+    Render {
+        image: String,
+        /// Output file, `<image>.overlay.png` next to the image by default
+        #[arg(short, long)]
+        output: Option<String>,
+        #[arg(short, long, value_enum, default_value_t = Solver::Twist)]
+        solver: Solver,
+        /// Length of the drawn X, Y and Z axes, the project's or 1 by default
+        #[arg(short, long)]
+        axis_length: Option<f32>,
+        /// Project of the image, its data, reference geometry and hidden lines are used
+        #[arg(short, long)]
+        project: Option<PathBuf>,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Solver {
+    Twist,
+    VanishingPoints,
 }
 
 pub fn main() -> iced::Result {
@@ -42,59 +113,287 @@ pub fn main() -> iced::Result {
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
+    if let Some(Command::Render {
+        image,
+        output,
+        solver,
+        axis_length,
+        project,
+    }) = Cli::parse().command
+    {
+        if let Err(error) = render_headless(&image, output, solver, axis_length, project) {
+            error!("could not render {image}: {error}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     iced::application(Perspective::new, Perspective::update, Perspective::view)
         .theme(Perspective::theme)
         .antialiasing(true)
         .centered()
-        .subscription(|_state| {
-            keyboard::on_key_release(|key, _modifiers| {
-                let keyboard::Key::Character(c) = key else {
-                    return None;
-                };
-
-                let c = c.as_str();
-
-                match c {
-                    "'" => Some(Message::ChangeMode(UiMod::Twist)),
+        .subscription(|state| {
+            Subscription::batch([
+                if state.unsaved_since.is_some() {
+                    iced::time::every(Duration::from_millis(500)).map(|_| Message::Autosave)
+                } else {
+                    Subscription::none()
+                },
+                if state.display_changed_since.is_some() {
+                    iced::time::every(Duration::from_millis(100)).map(|_| Message::RefreshDisplay)
+                } else {
+                    Subscription::none()
+                },
+                keyboard::on_key_release(|key, modifiers| match key.as_ref() {
+                    keyboard::Key::Character(character)
+                        if modifiers.command() && character.eq_ignore_ascii_case("z") =>
+                    {
+                        Some(if modifiers.shift() {
+                            Message::Redo
+                        } else {
+                            Message::Undo
+                        })
+                    }
+                    // a held arrow key nudges a handle until it is released
+                    keyboard::Key::Named(
+                        keyboard::key::Named::ArrowLeft
+                        | keyboard::key::Named::ArrowRight
+                        | keyboard::key::Named::ArrowUp
+                        | keyboard::key::Named::ArrowDown,
+                    ) => Some(Message::Checkpoint),
+                    // leaving a text field records what was typed in it
+                    keyboard::Key::Named(keyboard::key::Named::Tab) => Some(Message::Checkpoint),
                     _ => None,
-                }
-            })
+                }),
+                // drags of the handles end with the button release, whichever widget had them
+                event::listen_with(|event, _status, _window| match event {
+                    iced::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                        Some(Message::DragEnded)
+                    }
+                    _ => None,
+                }),
+                // keys typed into a text field are not shortcuts
+                event::listen_with(|event, status, _window| match (event, status) {
+                    (
+                        iced::Event::Keyboard(keyboard::Event::KeyPressed { key, .. }),
+                        event::Status::Ignored,
+                    ) => match key.as_ref() {
+                        keyboard::Key::Named(keyboard::key::Named::Enter) => {
+                            Some(Message::FinishShape)
+                        }
+                        keyboard::Key::Named(keyboard::key::Named::Escape) => {
+                            Some(Message::ChangeEdit(Edit::None))
+                        }
+                        keyboard::Key::Character("'") => Some(Message::ChangeMode(UiMod::Twist)),
+                        keyboard::Key::Character(";") => {
+                            Some(Message::ChangeMode(UiMod::VanishingPoints))
+                        }
+                        keyboard::Key::Named(keyboard::key::Named::Space) => {
+                            Some(Message::SpaceHeld(true))
+                        }
+                        _ => None,
+                    },
+                    (
+                        iced::Event::Keyboard(keyboard::Event::KeyReleased {
+                            key: keyboard::Key::Named(keyboard::key::Named::Space),
+                            ..
+                        }),
+                        _,
+                    )
+                    | (iced::Event::Window(window::Event::Unfocused), _) => {
+                        Some(Message::SpaceHeld(false))
+                    }
+                    _ => None,
+                }),
+            ])
         })
         .run()
 }
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, PartialEq)]
 enum UiMod {
     #[default]
     Twist,
+    VanishingPoints,
+}
+
+/// Numeric inputs of the reference geometry panel.
+#[derive(Clone, Copy, Debug)]
+enum ReferenceField {
+    GridSpacing,
+    GridExtent,
+    BoxX,
+    BoxY,
+    BoxZ,
+    AxisLength,
 }
 
 #[derive(Debug, Clone)]
 enum Message {
     Save,
+    Autosave,
+    /// An image could not be opened, the error is shown.
+    LoadFailed(String),
+    DismissError,
+    /// Saves an image whose data could not be read, replacing the unreadable file.
+    SaveAnyway,
+    Checkpoint,
+    /// The left button was released, ending a drag of a handle.
+    DragEnded,
+    Undo,
+    Redo,
     CalculatePose,
     LoadApplicationState {
-        image_data: Option<ImageData>,
+        image_data: Option<Box<ImageData>>,
         image_size: Size<u32>,
     },
     SelectImage(u8),
     ChangeMode(UiMod),
     ExportToFSpy,
+    ExportToObj {
+        textured: bool,
+    },
+    ExtractTextures,
+    RenderOverlay,
+    TexturePixelsPerUnitChanged(String),
+    ReferencePlaneChanged(ReferencePlane),
+    ReferenceBoxChanged(ReferenceBox),
+    ReferenceFieldChanged(ReferenceField, String),
+    ToggleReferenceAxes,
+    HiddenLineModeChanged(HiddenLineMode),
+    ToggleDashHidden,
+    LoadMesh,
+    ClearMesh,
+    PickTwistPoint(usize),
+    MeshVertexPicked(usize, usize),
+    NewWorldPoint,
+    RemoveObservation(usize),
+    Triangulate,
+    UseWorldPoint(usize, usize),
+    BundleAdjust,
+    ExportColmap,
+    ExportAllFSpy,
     ZoomChanged(f32),
+    /// Zoom and offset of the canvas after zooming with the wheel or panning.
+    ViewChanged(f32, Vector),
+    /// Space is pressed outside a text field or released, the left button pans meanwhile.
+    SpaceHeld(bool),
+    LoupeZoomChanged(f32),
+    DisplayAdjustmentsChanged(DisplayAdjustments),
+    /// Adjusts the full photo once the display adjustments rest for `DISPLAY_DELAY`.
+    RefreshDisplay,
     FieldOfViewChanged(f32),
     PoseLambdaTwist,
+    PoseVanishingPoints,
+    ToggleLockFieldOfView,
+    ToggleSharedIntrinsics,
+    EstimateSharedIntrinsics,
+    ToggleHorizon,
+    PixelErrorChanged(f32),
+    ChangeEdit(Edit),
+    DrawPlaneChanged(EditAxis),
+    FinishShape,
+    ClearShapes,
+    RemoveMeasurement(usize),
+    ShapesChanged,
+    EstimateUncertainty,
+    ToggleReferenceDistance,
+    ReferenceDistanceAxisChanged(EditAxis),
+    ReferenceDistanceLengthChanged(String),
+    ReferenceDistanceUnitChanged(ReferenceDistanceUnit),
     EditPoint(usize, zoomer::editor_component::Message),
+    AddTwistPoint,
+    TwistPointAdded(Point2<f32>),
+    SnapTwistPoint(usize),
+    RemoveTwistPoint(usize),
+    ToggleTwistPoint(usize),
+    TwistPointNameChanged(usize, String),
+    MoveTwistPointUp(usize),
     LoadImage,
+    RemoveImage,
+    OpenProject(PathBuf),
+    PickProject,
+    SaveProjectAs,
     NoImage,
 }
 
 #[derive(Default)]
 struct Perspective {
     mode: UiMod,
+    show_horizon: bool,
+    /// Magnification of the loupe, off at 1.
+    loupe_zoom: f32,
+    space_held: bool,
+    display_adjustments: DisplayAdjustments,
+    pixel_error: f32,
+    edit: Edit,
+    draw_plane: EditAxis,
+    texture_pixels_per_unit: String,
+    reference_geometry: ReferenceGeometry,
+    reference_inputs: [String; 6],
+    mesh: Option<Rc<Mesh>>,
+    hidden_lines: HiddenLines,
+    occluders: Rc<Vec<Vec<Vector3<f32>>>>,
+    pick_twist_point: Option<usize>,
+    /// The next click on the image adds a twist correspondence.
+    adding_twist_point: bool,
+    /// Joint estimate over `shared_images`, taken by every image sharing intrinsics.
+    shared_intrinsics: Option<SharedIntrinsics>,
+    shared_images: Vec<String>,
+    /// Last solution of every image visited this session.
+    solved_cameras: HashMap<String, SolvedCamera>,
+    /// Triangulated world points by observation id.
+    world_points: BTreeMap<usize, TriangulatedPoint>,
+    /// Result of the last bundle adjustment over `bundle_images`.
+    bundle_adjustment: Option<BundleAdjustment>,
+    bundle_images: Vec<String>,
+    /// Open project, images are saved to it instead of their points files.
+    project_path: Option<PathBuf>,
+    /// Data of the project images by image path, updated when the selected image is saved.
+    project_data: HashMap<String, Lines>,
+    history: History<Snapshot>,
+    /// Time of the last edit not saved yet, saved once edits pause for `AUTOSAVE_DELAY`.
+    unsaved_since: Option<Instant>,
+    /// Why the last save failed.
+    save_error: Option<String>,
+    /// Why the last image could not be opened or the overlay rendered, shown until dismissed.
+    error: Option<String>,
+    /// Time of the last display adjustment, the preview is shown until `DISPLAY_DELAY` later.
+    display_changed_since: Option<Instant>,
+    /// Set while a snapshot is restored, the intermediate states are not recorded.
+    restoring: bool,
+    /// The last step was recorded by the end of a drag, a snap of the dropped point joins it.
+    drop_recorded: bool,
+    /// Twist point whose name is being typed, the following keys join its step.
+    name_recorded: Option<usize>,
     image_state: Option<ImageState>,
     images: Vec<String>,
 }
 
+/// Edited state returned to by undo and redo.
+#[derive(Clone, PartialEq)]
+struct Snapshot {
+    images: Vec<String>,
+    selected_image: u8,
+    mode: UiMod,
+    /// Correspondences, field of view and drawing of the selected image.
+    data: Option<Lines>,
+}
+
+/// Solution of an image kept when another image is selected.
+struct SolvedCamera {
+    compute_solution: ComputeSolution<f32>,
+    image_size: Size<f32>,
+    shared_intrinsics: bool,
+    /// Twist correspondences when the image was solved in twist mode.
+    control_points: Vec<(Point3<f32>, Point2<f32>)>,
+    reference_distance_unit: ReferenceDistanceUnit,
+    /// Data the pose was solved from, see [`Perspective::pose_inputs`].
+    inputs: Option<Lines>,
+    /// The pose comes from a bundle adjustment and stays while `inputs` are unchanged.
+    adjusted: bool,
+}
+
 #[derive(Default)]
 struct ImageState {
     axis_data: Option<Rc<RefCell<AxisData>>>,
@@ -103,32 +402,214 @@ struct ImageState {
     export_file_name: String,
     compute_solution: Option<ComputeSolution<f32>>,
     image_size: Size<f32>,
-    draw_lines: Rc<RefCell<Vec<Vector3<f32>>>>,
+    drawing: Rc<RefCell<Drawing>>,
     reference_cube: Rc<RefCell<Vec<Point3<f32>>>>,
     selected_image: u8,
     zoom: f32,
+    /// Position of the zoomed image in the canvas.
+    offset: Vector,
     twist_points: Rc<RefCell<Vec<Point3<f32>>>>,
     twist_points_2d: Rc<RefCell<Vec<Point2<f32>>>>,
-    editor_component_1: EditorComponent,
-    editor_component_2: EditorComponent,
-    editor_component_3: EditorComponent,
+    twist_labels: Vec<TwistLabel>,
+    /// 3D coordinate inputs, one per twist point.
+    editor_components: Vec<EditorComponent>,
     field_of_view: f32,
+    focal_length_deviation: Option<FocalLengthDeviation<f32>>,
+    reference_distance_length: String,
+    uncertainty: Option<CalibrationUncertainty>,
+    /// The data of the image could not be read, saving would overwrite it with defaults.
+    save_blocked: bool,
+    /// Grayscale photo searched for corners, loaded on the first snap.
+    corner_image: Option<GrayImage>,
+    /// Photo the display adjustments start from, loaded with the first adjustment.
+    display_photo: Option<DisplayPhoto>,
+    /// Adjusted photo shown in the canvas with the adjustments it was made with and whether
+    /// it is the preview.
+    display_image: Option<(DisplayAdjustments, bool, image::Handle)>,
+}
+
+impl ImageState {
+    /// Twist correspondences that are not disabled.
+    fn enabled_twist_points(&self) -> (Vec<Point3<f32>>, Vec<Point2<f32>>) {
+        enabled_twist_points(
+            &self.twist_points.borrow(),
+            &self.twist_points_2d.borrow(),
+            &self.twist_labels,
+        )
+    }
+
+    /// Enables a twist point added on the image once its 3D coordinate is entered, true when
+    /// it was waiting for it.
+    fn coordinate_entered(&mut self, index: usize) -> bool {
+        match self.twist_labels.get_mut(index) {
+            Some(twist_label) if twist_label.awaiting_coordinate => {
+                twist_label.awaiting_coordinate = false;
+                twist_label.disabled = false;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Principal point of the twist solver, see [`AxisData::solver_principal_point`].
+    fn principal_point(&self) -> Vector2<f32> {
+        self.axis_data
+            .as_ref()
+            .map(|axis_data| axis_data.borrow().solver_principal_point())
+            .unwrap_or_else(Vector2::zeros)
+    }
+
+    fn refresh_editor_components(&mut self) {
+        self.editor_components = self
+            .twist_points
+            .borrow()
+            .iter()
+            .enumerate()
+            .map(|(index, point)| EditorComponent::new(format!("#{}", index + 1), point))
+            .collect();
+    }
+}
+
+/// Burns the overlay of the image solved with `solver` into the photo, with the reference
+/// settings of `project_path` when given and the defaults otherwise.
+fn render_headless(
+    image_path: &str,
+    output: Option<String>,
+    solver: Solver,
+    axis_length: Option<f32>,
+    project_path: Option<PathBuf>,
+) -> Result<()> {
+    let project = project_path
+        .as_ref()
+        .map(|project_path| read_project(project_path))
+        .transpose()?;
+    let same_file = |a: &Path, b: &Path| match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    };
+    let project_data = project.as_ref().and_then(|project| {
+        project.images.iter().find_map(|image| {
+            let path = resolve_image_path(project_path.as_ref().unwrap(), &image.path);
+            same_file(Path::new(&path), Path::new(image_path))
+                .then(|| image.data.clone())
+                .flatten()
+        })
+    });
+    let (mut axis_data, _) = match project_data {
+        Some(data) => read_points(data),
+        None => {
+            let points_file_name = Path::new(image_path).with_extension("points");
+            read_points_from_file(&points_file_name.to_string_lossy().to_string())?
+        }
+    };
+    let axis_length = match (axis_length, &project) {
+        (Some(axis_length), _) => Some(axis_length),
+        (None, Some(project)) => project.export.axis_length,
+        (None, None) => Some(1.0),
+    };
+    let reference_geometry = project
+        .as_ref()
+        .and_then(|project| project.export.reference.as_ref())
+        .map(|reference| reference.reference_geometry(axis_length))
+        .unwrap_or_default();
+    let hidden_lines = project
+        .as_ref()
+        .and_then(|project| project.export.hidden_lines)
+        .unwrap_or_default();
+    let mut photo = ::image::open(image_path)?.to_rgba8();
+    let image_size = Size::new(photo.width() as f32, photo.height() as f32);
+    let (twist_points, twist_points_2d) = enabled_twist_points(
+        &axis_data.twist_points.clone().unwrap_or_default(),
+        &axis_data.twist_points_2d.clone().unwrap_or_default(),
+        &axis_data.twist_labels,
+    );
+    let compute_solution = match solver {
+        Solver::Twist => compute_camera_pose_twist(
+            &twist_points,
+            &twist_points_2d,
+            image_size,
+            axis_data.field_of_view.unwrap_or(102.0).to_radians(),
+            axis_data.solver_principal_point(),
+        ),
+        Solver::VanishingPoints => {
+            let field_of_view = axis_data
+                .field_of_view
+                .filter(|_| axis_data.lock_field_of_view)
+                .map(f32::to_radians);
+            compute_vanishing_points_pose(&mut axis_data, image_size, &field_of_view)
+                .ok()
+                .map(|(compute_solution, _)| compute_solution)
+        }
+    }
+    .ok_or_else(|| anyhow::anyhow!("camera could not be solved"))?;
+
+    let reference = reference_geometry.segments(&twist_points);
+    let faces = reference_geometry.faces(&twist_points);
+    let (twist_points, twist_points_2d, axis_lines) = match solver {
+        Solver::Twist => (twist_points, twist_points_2d, Vec::new()),
+        Solver::VanishingPoints => (Vec::new(), Vec::new(), axis_data.axis_lines.clone()),
+    };
+    render_overlay(
+        &mut photo,
+        &Overlay {
+            compute_solution: &compute_solution,
+            reference: &reference,
+            mesh: None,
+            faces: &faces,
+            hidden_lines,
+            axis_length,
+            twist_points: &twist_points,
+            twist_points_2d: &twist_points_2d,
+            axis_lines: &axis_lines,
+        },
+    );
+    let output = output.unwrap_or_else(|| {
+        Path::new(image_path)
+            .with_extension("overlay.png")
+            .to_string_lossy()
+            .to_string()
+    });
+    trace!("render overlay to {output}");
+    photo.save(&output)?;
+    Ok(())
 }
 
 fn extract_state(state: Result<(Option<ImageData>, Size<u32>)>) -> Message {
-    let state = state.unwrap();
-    Message::LoadApplicationState {
-        image_data: state.0,
-        image_size: state.1,
+    match state {
+        Ok((image_data, image_size)) => Message::LoadApplicationState {
+            image_data: image_data.map(Box::new),
+            image_size,
+        },
+        Err(error) => Message::LoadFailed(error.to_string()),
     }
 }
 
 impl Perspective {
+    /// State before an image is loaded, with the settings that do not start at zero.
+    fn initial() -> Self {
+        Perspective {
+            loupe_zoom: LOUPE_ZOOM,
+            pixel_error: 1.0,
+            draw_plane: EditAxis::EditZ,
+            texture_pixels_per_unit: "100".to_string(),
+            reference_inputs: ["1", "5", "1", "1", "1", "1"].map(String::from),
+            ..Default::default()
+        }
+    }
+
     fn new() -> (Self, Task<Message>) {
         let args = Cli::parse();
-        if let Some(first_image) = args.images.first() {
+        if let Some(project) = args.project {
+            let mut init = Perspective::initial();
+            init.update(Message::OpenProject(PathBuf::from(project)));
+            let task = if init.image_state.is_some() {
+                Task::none()
+            } else {
+                Task::done(Message::NoImage)
+            };
+            (init, task)
+        } else if let Some(first_image) = args.images.first() {
             let first_image = first_image.clone();
-            let draw_lines = Rc::new(RefCell::new(vec![Vector3::<f32>::zeros()]));
             let image_name = Path::new(&first_image)
                 .file_stem()
                 .unwrap()
@@ -151,59 +632,93 @@ impl Perspective {
                 Point2::new(0.6676042, 0.6798148),
             ]));
 
-            let editor_component_1 =
-                EditorComponent::new("Point #1", twist_points.borrow().first().unwrap());
-            let editor_component_2 =
-                EditorComponent::new("Point #2", twist_points.borrow().get(1).unwrap());
-            let editor_component_3 =
-                EditorComponent::new("Point #3", twist_points.borrow().get(2).unwrap());
-            let image_state = ImageState {
+            let mut image_state = ImageState {
                 image_path: first_image.clone(),
-                draw_lines,
                 reference_cube: reference_cub,
                 export_file_name,
                 points_file_name: points.clone(),
                 zoom: 0.5,
                 twist_points,
                 twist_points_2d,
-                editor_component_1,
-                editor_component_2,
-                editor_component_3,
                 field_of_view: 102.0,
                 ..ImageState::default()
             };
+            image_state.refresh_editor_components();
             let init = Perspective {
                 image_state: Some(image_state),
                 images: args.images,
-                ..Default::default()
+                ..Perspective::initial()
             };
             (
                 init,
                 Task::perform(load(first_image, points, true), extract_state),
             )
         } else {
-            let init = Perspective::default();
-            (init, Task::done(Message::NoImage))
+            (Perspective::initial(), Task::done(Message::NoImage))
         }
     }
 
     fn update(&mut self, message: Message) {
         match message {
             Message::Save => {
-                if self.image_state.as_ref().unwrap().axis_data.is_none() {
+                if self.image_state.as_ref().unwrap().axis_data.is_none()
+                    || self.image_state.as_ref().unwrap().save_blocked
+                {
                     return;
                 };
-                if !Path::new(&self.image_state.as_ref().unwrap().points_file_name).exists() {
-                    trace!(
-                        "create file {}",
-                        self.image_state.as_ref().unwrap().points_file_name
-                    );
-                }
-                let mut file =
-                    File::create(self.image_state.as_ref().unwrap().points_file_name.clone())
-                        .unwrap();
+                self.unsaved_since = None;
                 let out = <Lines as From<&Perspective>>::from(self);
-                file.write_all(&serde_json::to_vec(&out).unwrap()).unwrap();
+                let (path, result) = if let Some(project_path) = self.project_path.clone() {
+                    self.project_data
+                        .insert(self.image_state.as_ref().unwrap().image_path.clone(), out);
+                    self.remember_camera();
+                    trace!("save project {}", project_path.display());
+                    let result = write_project(&project_path, &self.project());
+                    (project_path, result)
+                } else {
+                    let points_file_name =
+                        PathBuf::from(&self.image_state.as_ref().unwrap().points_file_name);
+                    if !points_file_name.exists() {
+                        trace!("create file {}", points_file_name.display());
+                    }
+                    let result = serde_json::to_vec(&out)
+                        .map_err(Into::into)
+                        .and_then(|content| write_atomic(&points_file_name, &content));
+                    (points_file_name, result)
+                };
+                self.save_error = result.err().map(|error| {
+                    info!("could not save {}: {error}", path.display());
+                    format!("Could not save {}: {error}", path.display())
+                });
+            }
+            Message::Autosave => {
+                if self
+                    .unsaved_since
+                    .is_some_and(|unsaved_since| unsaved_since.elapsed() >= AUTOSAVE_DELAY)
+                {
+                    self.update(Message::Save);
+                }
+            }
+            Message::SaveAnyway => {
+                self.image_state.as_mut().unwrap().save_blocked = false;
+                self.save_error = None;
+                self.update(Message::Save);
+            }
+            Message::Checkpoint => {
+                self.checkpoint();
+            }
+            Message::DragEnded => self.drop_recorded = self.checkpoint(),
+            Message::Undo => {
+                // edits not closed by a checkpoint yet, e.g. typed coordinates
+                self.checkpoint();
+                if let Some(snapshot) = self.history.undo() {
+                    self.restore(snapshot);
+                }
+            }
+            Message::Redo => {
+                if let Some(snapshot) = self.history.redo() {
+                    self.restore(snapshot);
+                }
             }
             Message::CalculatePose => {
                 info!("does nothing");
@@ -217,10 +732,8 @@ impl Perspective {
                 if let Some(image_data) = image_data {
                     self.image_state.as_mut().unwrap().axis_data =
                         Some(Rc::new(RefCell::new(image_data.axis_data)));
-                    if let Some(lines) = image_data.lines {
-                        self.image_state.as_mut().unwrap().draw_lines =
-                            Rc::new(RefCell::new(lines));
-                    }
+                    self.image_state.as_mut().unwrap().drawing =
+                        Rc::new(RefCell::new(image_data.drawing.unwrap_or_default()));
                 } else {
                     self.image_state.as_mut().unwrap().axis_data =
                         Some(Rc::new(RefCell::new(AxisData::default())));
@@ -266,42 +779,81 @@ impl Perspective {
                     102.0
                 };
                 self.refresh_reference_cub();
-                let twist_points = self
-                    .image_state
+                let image_state = self.image_state.as_mut().unwrap();
+                let mut twist_labels = image_state
+                    .axis_data
                     .as_ref()
                     .unwrap()
-                    .twist_points
                     .borrow()
+                    .twist_labels
                     .clone();
-                let point = twist_points.first().unwrap();
-                self.image_state.as_mut().unwrap().editor_component_1 =
-                    EditorComponent::new("Point #1", point);
-                let point = twist_points.get(1).unwrap();
-                self.image_state.as_mut().unwrap().editor_component_2 =
-                    EditorComponent::new("Point #2", point);
-                let point = twist_points.get(2).unwrap();
-                self.image_state.as_mut().unwrap().editor_component_3 =
-                    EditorComponent::new("Point #3", point);
+                twist_labels.resize(
+                    image_state.twist_points.borrow().len(),
+                    TwistLabel::default(),
+                );
+                image_state.twist_labels = twist_labels;
+                image_state.refresh_editor_components();
+                let reference_distance_length = self
+                    .image_state
+                    .as_ref()
+                    .unwrap()
+                    .axis_data
+                    .as_ref()
+                    .unwrap()
+                    .borrow()
+                    .reference_distance
+                    .as_ref()
+                    .map(|reference| reference.length.to_string())
+                    .unwrap_or_default();
+                self.image_state.as_mut().unwrap().reference_distance_length =
+                    reference_distance_length;
+                self.apply_shared_intrinsics();
+                self.refresh_display_image(false);
 
                 match self.mode {
                     UiMod::Twist => self.update(Message::PoseLambdaTwist),
+                    UiMod::VanishingPoints => self.update(Message::PoseVanishingPoints),
                 }
+                self.checkpoint();
             }
             Message::ChangeMode(mode) => {
                 self.mode = mode;
                 match self.mode {
                     UiMod::Twist => self.update(Message::PoseLambdaTwist),
+                    UiMod::VanishingPoints => self.update(Message::PoseVanishingPoints),
                 }
+                self.checkpoint();
             }
             Message::SelectImage(selected) => {
                 self.update(Message::Save);
+                self.remember_camera();
+                let selected_image_name = self.images.get(selected as usize).unwrap().clone();
+                let (image_data, read_error) = match self.read_image_data(&selected_image_name) {
+                    Ok(image_data) => (
+                        image_data.map(|(axis_data, drawing)| ImageData {
+                            axis_data,
+                            drawing: Some(drawing),
+                        }),
+                        None,
+                    ),
+                    Err(error) => (None, Some(error)),
+                };
+                // the current image stays when the selected one cannot be opened
+                let loaded = extract_state(
+                    block_on(load_image(selected_image_name.clone(), image_data)).map_err(
+                        |error| anyhow::anyhow!("could not open {selected_image_name}: {error}"),
+                    ),
+                );
+                if let Message::LoadFailed(_) = loaded {
+                    self.update(loaded);
+                    return;
+                }
+                self.error = None;
                 self.image_state.as_mut().unwrap().selected_image = selected;
-                let selected_image_name = self
-                    .images
-                    .get(self.image_state.as_ref().unwrap().selected_image as usize)
-                    .unwrap()
-                    .clone();
                 self.image_state.as_mut().unwrap().image_path = selected_image_name.clone();
+                self.image_state.as_mut().unwrap().corner_image = None;
+                self.image_state.as_mut().unwrap().display_photo = None;
+                self.image_state.as_mut().unwrap().display_image = None;
                 let name_without_extension = Path::new(&selected_image_name)
                     .file_stem()
                     .unwrap()
@@ -317,14 +869,15 @@ impl Perspective {
                 self.image_state.as_mut().unwrap().export_file_name =
                     format!("{parent}/{}.fspy", name_without_extension);
 
-                self.update(extract_state(block_on(async {
-                    load(
-                        selected_image_name,
-                        self.image_state.as_ref().unwrap().points_file_name.clone(),
-                        false,
-                    )
-                    .await
-                })));
+                // saving the defaults shown instead would overwrite the unreadable file
+                if let Some(error) = &read_error {
+                    info!("{error}");
+                }
+                self.image_state.as_mut().unwrap().save_blocked = read_error.is_some();
+                self.save_error = read_error.map(|error| {
+                    format!("{error}. Saving is paused for this image, fix the file or save anyway")
+                });
+                self.update(loaded);
                 self.update(Message::CalculatePose);
             }
             Message::ExportToFSpy => {
@@ -344,377 +897,1562 @@ impl Perspective {
                         self.image_state.as_ref().unwrap().image_size.height as u32,
                         self.image_state.as_ref().unwrap().image_path.clone(),
                         self.image_state.as_ref().unwrap().export_file_name.clone(),
+                        self.image_state
+                            .as_ref()
+                            .unwrap()
+                            .axis_data
+                            .as_ref()
+                            .map(|axis_data| axis_data.borrow().reference_distance_unit)
+                            .unwrap_or_default(),
                     )
                     .await;
                     trace!("scene data: {:?}", data);
                 });
             }
-            Message::ZoomChanged(zoom) => self.image_state.as_mut().unwrap().zoom = zoom,
-            Message::FieldOfViewChanged(field_of_view) => {
-                self.image_state.as_mut().unwrap().field_of_view = field_of_view;
-                self.update(Message::PoseLambdaTwist);
+            Message::ExportToObj { textured } => {
+                let image_state = self.image_state.as_ref().unwrap();
+                let obj_file = Path::new(&image_state.points_file_name).with_extension("obj");
+                let mtl_file = obj_file.with_extension("mtl");
+                let mtl_file_name = mtl_file
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or_default();
+                let texture = match (textured, &image_state.compute_solution) {
+                    (true, Some(compute_solution)) => Some(ObjTexture {
+                        compute_solution,
+                        image_size: image_state.image_size,
+                        material_file_name: mtl_file_name,
+                    }),
+                    _ => None,
+                };
+                let obj = to_obj(
+                    &image_state.drawing.borrow().shapes,
+                    &image_state.twist_points.borrow(),
+                    &image_state.reference_cube.borrow(),
+                    texture.as_ref(),
+                );
+                trace!("export to file {}", obj_file.display());
+                if let Err(error) = std::fs::write(&obj_file, obj) {
+                    info!("could not write {}: {error}", obj_file.display());
+                }
+                if texture.is_some() {
+                    let image_file_name = Path::new(&image_state.image_path)
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .unwrap_or_default();
+                    if let Err(error) = std::fs::write(&mtl_file, to_mtl(image_file_name)) {
+                        info!("could not write {}: {error}", mtl_file.display());
+                    }
+                }
             }
-            Message::PoseLambdaTwist => {
-                let fx = self.image_state.as_ref().unwrap().image_size.width as f64;
-                let fy = self.image_state.as_ref().unwrap().image_size.height as f64;
-                let cx = self.image_state.as_ref().unwrap().image_size.width as f64 / 2.0;
-                let cy = self.image_state.as_ref().unwrap().image_size.height as f64 / 2.0;
-                let field_of_view = self
-                    .image_state
+            Message::RenderOverlay => {
+                let image_state = self.image_state.as_ref().unwrap();
+                let Some(compute_solution) = &image_state.compute_solution else {
+                    self.error =
+                        Some("Could not render the overlay, the camera is not solved".to_string());
+                    return;
+                };
+                let mut photo = match ::image::open(&image_state.image_path) {
+                    Ok(photo) => photo.to_rgba8(),
+                    Err(error) => {
+                        info!("could not open {}: {error}", image_state.image_path);
+                        self.error = Some(format!(
+                            "Could not render the overlay, {} could not be opened: {error}",
+                            image_state.image_path
+                        ));
+                        return;
+                    }
+                };
+                let twist_mode = matches!(self.mode, UiMod::Twist);
+                let (twist_points, twist_points_2d) = image_state.enabled_twist_points();
+                let axis_lines = image_state
+                    .axis_data
                     .as_ref()
-                    .unwrap()
-                    .field_of_view
-                    .to_radians();
-
-                let unprojection =
-                    cv::nalgebra::Perspective3::new(1.0, field_of_view as f64, 0.1, 1000.0)
-                        .inverse();
-                let to_device_coord_transform = cv::nalgebra::Matrix3::new_nonuniform_scaling(
-                    &cv::nalgebra::Vector2::new(fx / 2.0, -fx / 2.0),
-                )
-                .append_translation(&cv::nalgebra::Vector2::new(cx, cy))
-                .try_inverse()
-                .unwrap();
-                info!(
-                    "3d: {:?}",
-                    self.image_state.as_ref().unwrap().twist_points.borrow()
-                );
-                info!(
-                    "2d: {:?}",
-                    self.image_state.as_ref().unwrap().twist_points_2d.borrow()
-                );
-                info!(
-                    "2d: {:?}",
-                    self.image_state
-                        .as_ref()
-                        .unwrap()
-                        .twist_points_2d
-                        .borrow()
-                        .iter()
-                        .map(|item| {
-                            cv::nalgebra::Point2::new(item.x as f64 * fx, item.y as f64 * fy)
-                        })
-                        .collect::<Vec<_>>()
+                    .filter(|_| !twist_mode)
+                    .map(|axis_data| axis_data.borrow().axis_lines.clone())
+                    .unwrap_or_default();
+                render_overlay(
+                    &mut photo,
+                    &Overlay {
+                        compute_solution,
+                        reference: &image_state.reference_cube.borrow(),
+                        mesh: self.mesh.as_deref(),
+                        faces: &self.occluders,
+                        hidden_lines: self.hidden_lines,
+                        axis_length: Some(self.reference_geometry.axis_length.unwrap_or(1.0)),
+                        twist_points: if twist_mode { &twist_points } else { &[] },
+                        twist_points_2d: if twist_mode { &twist_points_2d } else { &[] },
+                        axis_lines: &axis_lines,
+                    },
                 );
-                let bearings: Vec<cv::nalgebra::Point3<f64>> = self
-                    .image_state
-                    .as_ref()
-                    .unwrap()
-                    .twist_points_2d
-                    .borrow()
-                    .iter()
-                    .map(|item| {
-                        let item =
-                            cv::nalgebra::Point2::new(item.x as f64 * fx, item.y as f64 * fy);
-                        cv::nalgebra::Point3::from(
-                            (unprojection
-                                * cv::nalgebra::Point3::from(
-                                    to_device_coord_transform * item.to_homogeneous(),
-                                )
-                                .to_homogeneous())
-                            .xyz(),
-                        )
-                    })
-                    .map(|item| item / item.z)
-                    .collect();
-                info!("bearings: {:?}", bearings);
-                let features: Vec<FeatureWorldMatch<_>> = self
-                    .image_state
-                    .as_ref()
-                    .unwrap()
-                    .twist_points
-                    .borrow()
-                    .iter()
-                    .zip(&bearings)
-                    .map(|(&world, &image)| {
-                        //INFO: in Blender camera looks at -Z, in computer vision camera looks at +Z, inverting all coordinates
-                        let world = cv::nalgebra::Point3::new(
-                            -world.x as f64,
-                            -world.y as f64,
-                            -world.z as f64,
-                        );
-                        let bearing = cv::nalgebra::Unit::new_normalize(
-                            cv::nalgebra::Vector3::new(image.x, image.y, 1.0),
-                        );
-                        FeatureWorldMatch(bearing, cv::WorldPoint(world.to_homogeneous()))
-                    })
-                    .collect();
-
-                let solver = LambdaTwist::new();
-                use cv::Estimator;
-                let mut candidates = solver.estimate(features.iter().cloned());
-
-                //sort by Y rotation, most vertical position
-                candidates.sort_by(|a, b| {
-                    if a.0.rotation.inverse().euler_angles().1.abs()
-                        < b.0.rotation.inverse().euler_angles().1.abs()
-                    {
-                        Ordering::Less
-                    } else {
-                        Ordering::Greater
-                    }
+                let output = Path::new(&image_state.points_file_name).with_extension("overlay.png");
+                trace!("render overlay to {}", output.display());
+                self.error = photo.save(&output).err().map(|error| {
+                    info!("could not write {}: {error}", output.display());
+                    format!("Could not write {}: {error}", output.display())
                 });
-
-                candidates
-                    .iter()
-                    .for_each(|item| info!("solution: {}", item.0.to_homogeneous()));
-
-                if !candidates.is_empty() {
-                    let item = candidates.iter().next().unwrap();
-                    let solution = item.0.to_homogeneous();
-                    info!("using the first solution {solution}");
-                    //INFO: invert returned translation vector (world = -camera)
-                    self.image_state.as_mut().unwrap().compute_solution =
-                        Some(ComputeSolution::new(
-                            Matrix4::new(
-                                solution.m11 as f32,
-                                solution.m12 as f32,
-                                solution.m13 as f32,
-                                -solution.m14 as f32,
-                                solution.m21 as f32,
-                                solution.m22 as f32,
-                                solution.m23 as f32,
-                                -solution.m24 as f32,
-                                solution.m31 as f32,
-                                solution.m32 as f32,
-                                solution.m33 as f32,
-                                -solution.m34 as f32,
-                                solution.m41 as f32,
-                                solution.m42 as f32,
-                                solution.m43 as f32,
-                                solution.m44 as f32,
-                            ),
-                            Vector2::new(0.0, 0.0),
-                            self.image_state
-                                .as_ref()
-                                .unwrap()
-                                .field_of_view
-                                .to_radians(),
-                        ));
-                }
+            }
+            Message::TexturePixelsPerUnitChanged(pixels_per_unit) => {
+                self.texture_pixels_per_unit = pixels_per_unit;
+            }
+            Message::ReferencePlaneChanged(grid_plane) => {
+                self.reference_geometry.grid_plane = grid_plane;
                 self.refresh_reference_cub();
             }
-            Message::EditPoint(index, edit_component_message) => match index {
-                0 => match self
-                    .image_state
-                    .as_mut()
-                    .unwrap()
-                    .editor_component_1
-                    .update(edit_component_message)
+            Message::ReferenceBoxChanged(box_kind) => {
+                self.reference_geometry.box_kind = box_kind;
+                self.refresh_reference_cub();
+            }
+            Message::ReferenceFieldChanged(field, value) => {
+                if let Ok(parsed) = value.parse::<f32>()
+                    && parsed > 0.0
                 {
-                    Action::Valid(point) => {
-                        self.image_state.as_ref().unwrap().twist_points.borrow_mut()[index] = point;
+                    let geometry = &mut self.reference_geometry;
+                    match field {
+                        ReferenceField::GridSpacing => geometry.grid_spacing = parsed,
+                        ReferenceField::GridExtent => geometry.grid_extent = parsed,
+                        ReferenceField::BoxX => geometry.box_size.x = parsed,
+                        ReferenceField::BoxY => geometry.box_size.y = parsed,
+                        ReferenceField::BoxZ => geometry.box_size.z = parsed,
+                        ReferenceField::AxisLength => {
+                            if geometry.axis_length.is_some() {
+                                geometry.axis_length = Some(parsed);
+                            }
+                        }
                     }
-
-                    Action::Invalid => {}
-                },
-                1 => match self
-                    .image_state
-                    .as_mut()
-                    .unwrap()
-                    .editor_component_2
-                    .update(edit_component_message)
-                {
-                    Action::Valid(point) => {
-                        self.image_state.as_ref().unwrap().twist_points.borrow_mut()[index] = point;
+                    self.refresh_reference_cub();
+                }
+                self.reference_inputs[field as usize] = value;
+            }
+            Message::HiddenLineModeChanged(mode) => self.hidden_lines.mode = mode,
+            Message::ToggleDashHidden => {
+                self.hidden_lines.dash_hidden = !self.hidden_lines.dash_hidden;
+            }
+            Message::ToggleReferenceAxes => {
+                self.reference_geometry.axis_length = match self.reference_geometry.axis_length {
+                    Some(_) => None,
+                    None => Some(
+                        self.reference_inputs[ReferenceField::AxisLength as usize]
+                            .parse::<f32>()
+                            .ok()
+                            .filter(|length| *length > 0.0)
+                            .unwrap_or(1.0),
+                    ),
+                };
+                self.refresh_reference_cub();
+            }
+            Message::ExtractTextures => {
+                let image_state = self.image_state.as_ref().unwrap();
+                let Some(compute_solution) = &image_state.compute_solution else {
+                    return;
+                };
+                let Some(pixels_per_unit) = self
+                    .texture_pixels_per_unit
+                    .trim()
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|pixels_per_unit| *pixels_per_unit > 0.0)
+                else {
+                    info!(
+                        "invalid texture resolution {}",
+                        self.texture_pixels_per_unit
+                    );
+                    return;
+                };
+                let photo = match ::image::open(&image_state.image_path) {
+                    Ok(photo) => photo.to_rgba8(),
+                    Err(error) => {
+                        info!("could not open {}: {error}", image_state.image_path);
+                        return;
                     }
-
-                    Action::Invalid => {}
-                },
-                2 => match self
-                    .image_state
-                    .as_mut()
-                    .unwrap()
-                    .editor_component_3
-                    .update(edit_component_message)
+                };
+                let base = Path::new(&image_state.points_file_name).with_extension("");
+                image_state
+                    .drawing
+                    .borrow()
+                    .shapes
+                    .iter()
+                    .enumerate()
+                    .for_each(|(index, shape)| {
+                        shape
+                            .faces()
+                            .iter()
+                            .enumerate()
+                            .for_each(|(face, polygon)| {
+                                let Some(texture) = extract_plane_texture(
+                                    &photo,
+                                    compute_solution,
+                                    polygon,
+                                    pixels_per_unit,
+                                ) else {
+                                    return;
+                                };
+                                let file =
+                                    format!("{}_shape{index}_face{face}.png", base.display());
+                                trace!("export texture {file}");
+                                if let Err(error) = texture.save(&file) {
+                                    info!("could not write {file}: {error}");
+                                }
+                            })
+                    });
+            }
+            Message::ZoomChanged(zoom) => self.image_state.as_mut().unwrap().zoom = zoom,
+            Message::SpaceHeld(space_held) => self.space_held = space_held,
+            Message::ViewChanged(zoom, offset) => {
+                let image_state = self.image_state.as_mut().unwrap();
+                image_state.zoom = zoom;
+                image_state.offset = offset;
+            }
+            Message::LoupeZoomChanged(loupe_zoom) => self.loupe_zoom = loupe_zoom,
+            Message::DisplayAdjustmentsChanged(display_adjustments) => {
+                self.display_adjustments = display_adjustments;
+                // the full photo follows once the sliders rest
+                self.refresh_display_image(true);
+                self.display_changed_since = Some(Instant::now());
+                // remembered in the project, the points files do not keep them
+                if self.project_path.is_some() {
+                    self.unsaved_since = Some(Instant::now());
+                }
+            }
+            Message::RefreshDisplay => {
+                if self
+                    .display_changed_since
+                    .is_some_and(|changed_since| changed_since.elapsed() >= DISPLAY_DELAY)
                 {
-                    Action::Valid(point) => {
-                        self.image_state.as_ref().unwrap().twist_points.borrow_mut()[index] = point;
-                    }
-
-                    Action::Invalid => {}
-                },
-                _ => {}
-            },
-            Message::LoadImage => {
-                if let Some(path) = rfd::FileDialog::new()
-                    .add_filter("Image", &["png", "jpg", "jpeg"])
-                    .pick_file()
+                    self.display_changed_since = None;
+                    self.refresh_display_image(false);
+                }
+            }
+            Message::PixelErrorChanged(pixel_error) => self.pixel_error = pixel_error,
+            Message::ChangeEdit(edit) => {
+                self.update(Message::FinishShape);
+                self.pick_twist_point = None;
+                self.edit = edit;
+            }
+            Message::DrawPlaneChanged(draw_plane) => self.draw_plane = draw_plane,
+            Message::FinishShape => {
+                let mut drawing = self.image_state.as_ref().unwrap().drawing.borrow_mut();
+                // a single point is not a shape
+                if let Some(active) = drawing.active.take()
+                    && drawing.shapes[active].points.len() < 2
                 {
-                    if self.image_state.is_none() {
-                        self.image_state = Some(ImageState {
-                            //TODO: handle the intial load of the lines to draw
-                            draw_lines: Rc::new(RefCell::new(vec![Vector3::<f32>::zeros()])),
-                            zoom: 0.5,
-                            ..Default::default()
-                        })
-                    };
-                    self.images.push(path.to_str().unwrap().to_string());
-                    self.update(Message::SelectImage((self.images.len() - 1) as u8));
+                    drawing.shapes.remove(active);
                 }
             }
-            Message::NoImage => {}
-        }
-    }
-
-    fn refresh_reference_cub(&mut self) {
-        let first = *self
-            .image_state
-            .as_ref()
-            .unwrap()
-            .twist_points
-            .borrow()
-            .first()
-            .unwrap();
-        let min = self
-            .image_state
-            .as_ref()
-            .unwrap()
-            .twist_points
-            .borrow()
-            .iter()
-            .skip(1)
-            .fold(first, |mut acc, item| {
-                if acc.x > item.x {
-                    acc.x = item.x;
+            Message::ClearShapes => {
+                {
+                    let mut drawing = self.image_state.as_ref().unwrap().drawing.borrow_mut();
+                    drawing.shapes.clear();
+                    drawing.active = None;
                 }
-                if acc.y > item.y {
-                    acc.y = item.y;
+                self.checkpoint();
+            }
+            Message::RemoveMeasurement(index) => {
+                {
+                    let mut drawing = self.image_state.as_ref().unwrap().drawing.borrow_mut();
+                    if index < drawing.measurements.len() {
+                        drawing.measurements.remove(index);
+                    }
                 }
-                if acc.z > item.z {
-                    acc.z = item.z;
+                self.checkpoint();
+            }
+            Message::ShapesChanged => {}
+            Message::EstimateUncertainty => {
+                let image_state = self.image_state.as_mut().unwrap();
+                let (twist_points, twist_points_2d) = image_state.enabled_twist_points();
+                let uncertainty = match self.mode {
+                    UiMod::Twist => twist_uncertainty(
+                        &twist_points,
+                        &twist_points_2d,
+                        image_state.image_size,
+                        image_state.field_of_view.to_radians(),
+                        image_state.principal_point(),
+                        self.pixel_error,
+                        UNCERTAINTY_SAMPLES,
+                    ),
+                    UiMod::VanishingPoints => {
+                        image_state.axis_data.as_ref().and_then(|axis_data| {
+                            let axis_data = axis_data.borrow();
+                            let field_of_view = if axis_data.lock_field_of_view {
+                                Some(image_state.field_of_view.to_radians())
+                            } else {
+                                None
+                            };
+                            vanishing_points_uncertainty(
+                                &axis_data,
+                                image_state.image_size,
+                                &field_of_view,
+                                self.pixel_error,
+                                UNCERTAINTY_SAMPLES,
+                            )
+                        })
+                    }
+                };
+                if let Some(uncertainty) = uncertainty.as_ref() {
+                    let sidecar =
+                        Path::new(&image_state.points_file_name).with_extension("uncertainty.json");
+                    let written = serde_json::to_string_pretty(uncertainty)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|json| Ok(std::fs::write(&sidecar, json)?));
+                    if let Err(error) = written {
+                        info!("could not write {}: {error}", sidecar.display());
+                    }
+                } else {
+                    info!("no solution to estimate the uncertainty of");
                 }
-                acc
-            });
-        let first = *self
-            .image_state
-            .as_ref()
-            .unwrap()
-            .twist_points
-            .borrow()
-            .first()
-            .unwrap();
-        let max = self
-            .image_state
-            .as_ref()
-            .unwrap()
-            .twist_points
-            .borrow()
-            .iter()
-            .skip(1)
-            .fold(first, |mut acc, item| {
-                if acc.x < item.x {
-                    acc.x = item.x;
+                image_state.uncertainty = uncertainty;
+            }
+            Message::FieldOfViewChanged(field_of_view) => {
+                self.image_state.as_mut().unwrap().field_of_view = field_of_view;
+                match self.mode {
+                    UiMod::Twist => self.update(Message::PoseLambdaTwist),
+                    UiMod::VanishingPoints => {
+                        // moving the slider in vanishing point mode means the focal length is known
+                        if let Some(axis_data) = &self.image_state.as_ref().unwrap().axis_data {
+                            axis_data.borrow_mut().lock_field_of_view = true;
+                        }
+                        self.update(Message::PoseVanishingPoints)
+                    }
+                }
+            }
+            Message::ToggleHorizon => {
+                self.show_horizon = !self.show_horizon;
+            }
+            Message::ToggleLockFieldOfView => {
+                if let Some(axis_data) = &self.image_state.as_ref().unwrap().axis_data {
+                    let mut axis_data = axis_data.borrow_mut();
+                    axis_data.lock_field_of_view = !axis_data.lock_field_of_view;
+                }
+                self.update(Message::PoseVanishingPoints);
+            }
+            Message::ToggleSharedIntrinsics => {
+                if let Some(axis_data) = &self.image_state.as_ref().unwrap().axis_data {
+                    let mut axis_data = axis_data.borrow_mut();
+                    axis_data.shared_intrinsics = !axis_data.shared_intrinsics;
+                    if !axis_data.shared_intrinsics {
+                        axis_data.principal_point = None;
+                    }
+                }
+                self.apply_shared_intrinsics();
+                match self.mode {
+                    UiMod::Twist => self.update(Message::PoseLambdaTwist),
+                    UiMod::VanishingPoints => self.update(Message::PoseVanishingPoints),
+                }
+            }
+            Message::EstimateSharedIntrinsics => {
+                self.update(Message::Save);
+                let image_state = self.image_state.as_ref().unwrap();
+                // the selected image is taken from memory, the others from the project or their points files
+                let mut shared: Vec<(String, AxisData, Size<f32>)> = Vec::new();
+                for image_path in &self.images {
+                    let axis_data = if *image_path == image_state.image_path {
+                        image_state
+                            .axis_data
+                            .as_ref()
+                            .map(|axis_data| (axis_data.borrow().clone(), image_state.image_size))
+                    } else {
+                        self.read_image_data(image_path)
+                            .unwrap_or_else(|error| {
+                                info!("{error}");
+                                None
+                            })
+                            .zip(::image::image_dimensions(image_path).ok())
+                            .map(|((axis_data, _), (width, height))| {
+                                (axis_data, Size::new(width as f32, height as f32))
+                            })
+                    };
+                    if let Some((axis_data, image_size)) = axis_data
+                        && axis_data.shared_intrinsics
+                    {
+                        // the default lines would pull the estimate towards their own camera
+                        if axis_data.has_vanishing_lines() {
+                            shared.push((image_path.clone(), axis_data, image_size));
+                        } else {
+                            info!(
+                                "{image_path} has no vanishing lines, left out of the shared intrinsics"
+                            );
+                        }
+                    }
+                }
+                if let Some((_, _, first_size)) = shared.first() {
+                    let ratio = first_size.width / first_size.height;
+                    shared.retain(|(image_path, _, image_size)| {
+                        let matches =
+                            (image_size.width / image_size.height - ratio).abs() < 1e-3;
+                        if !matches {
+                            info!("{image_path} has another aspect ratio, left out of the shared intrinsics");
+                        }
+                        matches
+                    });
+                }
+                let images: Vec<(&AxisData, Size<f32>)> = shared
+                    .iter()
+                    .map(|(_, axis_data, image_size)| (axis_data, *image_size))
+                    .collect();
+                match estimate_shared_intrinsics(&images) {
+                    Ok(shared_intrinsics) => {
+                        trace!(
+                            "shared field of view {} over {} images",
+                            shared_intrinsics.field_of_view.to_degrees(),
+                            images.len()
+                        );
+                        self.shared_images = shared
+                            .into_iter()
+                            .map(|(image_path, ..)| image_path)
+                            .collect();
+                        self.shared_intrinsics = Some(shared_intrinsics);
+                    }
+                    Err(error) => {
+                        info!("could not estimate shared intrinsics: {error}");
+                        self.shared_intrinsics = None;
+                        self.shared_images.clear();
+                    }
+                }
+                self.apply_shared_intrinsics();
+                match self.mode {
+                    UiMod::Twist => self.update(Message::PoseLambdaTwist),
+                    UiMod::VanishingPoints => self.update(Message::PoseVanishingPoints),
                 }
-                if acc.y < item.y {
-                    acc.y = item.y;
+            }
+            Message::ToggleReferenceDistance => {
+                let image_state = self.image_state.as_mut().unwrap();
+                if let Some(axis_data) = &image_state.axis_data {
+                    let mut axis_data = axis_data.borrow_mut();
+                    if axis_data.reference_distance.take().is_some() {
+                        axis_data.custom_scale = None;
+                    } else {
+                        let origin = axis_data.control_point;
+                        axis_data.reference_distance = Some(ReferenceDistance {
+                            line: (origin, Point::new(origin.x + 0.1, origin.y)),
+                            axis: EditAxis::EditX,
+                            length: 1.0,
+                        });
+                        image_state.reference_distance_length = "1.0".to_string();
+                    }
                 }
-                if acc.z < item.z {
-                    acc.z = item.z;
+                self.update(Message::PoseVanishingPoints);
+            }
+            Message::ReferenceDistanceAxisChanged(axis) => {
+                if let Some(axis_data) = &self.image_state.as_ref().unwrap().axis_data
+                    && let Some(reference) = axis_data.borrow_mut().reference_distance.as_mut()
+                {
+                    reference.axis = axis;
                 }
-                acc
+                self.update(Message::PoseVanishingPoints);
+            }
+            Message::ReferenceDistanceLengthChanged(input) => {
+                let image_state = self.image_state.as_mut().unwrap();
+                if let Ok(length) = input.parse::<f32>()
+                    && let Some(axis_data) = &image_state.axis_data
+                    && let Some(reference) = axis_data.borrow_mut().reference_distance.as_mut()
+                {
+                    reference.length = length;
+                }
+                image_state.reference_distance_length = input;
+                self.update(Message::PoseVanishingPoints);
+            }
+            Message::ReferenceDistanceUnitChanged(unit) => {
+                if let Some(axis_data) = &self.image_state.as_ref().unwrap().axis_data {
+                    axis_data.borrow_mut().reference_distance_unit = unit;
+                }
+                self.checkpoint();
+            }
+            Message::PoseVanishingPoints => {
+                let image_state = self.image_state.as_mut().unwrap();
+                let Some(axis_data) = image_state.axis_data.as_ref().map(Rc::clone) else {
+                    return;
+                };
+                let field_of_view = if axis_data.borrow().lock_field_of_view {
+                    Some(image_state.field_of_view.to_radians())
+                } else {
+                    None
+                };
+                let pose = compute_vanishing_points_pose(
+                    &mut axis_data.borrow_mut(),
+                    image_state.image_size,
+                    &field_of_view,
+                );
+                image_state.uncertainty = None;
+                match pose {
+                    Ok((compute_solution, focal_length_deviation)) => {
+                        trace!(
+                            "vanishing points field of view {}",
+                            compute_solution.field_of_view().to_degrees()
+                        );
+                        image_state.compute_solution = Some(compute_solution);
+                        image_state.focal_length_deviation = focal_length_deviation;
+                    }
+                    Err(error) => {
+                        info!("could not compute pose from vanishing points: {error}");
+                        // a pose from the previous lines would not match what is drawn now
+                        image_state.compute_solution = None;
+                        image_state.focal_length_deviation = None;
+                    }
+                }
+                self.restore_adjusted_pose();
+                self.refresh_reference_cub();
+            }
+            Message::PoseLambdaTwist => {
+                let image_state = self.image_state.as_mut().unwrap();
+                let (twist_points, twist_points_2d) = image_state.enabled_twist_points();
+                let compute_solution = compute_camera_pose_twist(
+                    &twist_points,
+                    &twist_points_2d,
+                    image_state.image_size,
+                    image_state.field_of_view.to_radians(),
+                    image_state.principal_point(),
+                );
+                if compute_solution.is_none() {
+                    info!("could not compute pose from the twist points");
+                }
+                image_state.compute_solution = compute_solution;
+                image_state.uncertainty = None;
+                self.restore_adjusted_pose();
+                self.refresh_reference_cub();
+            }
+            Message::EditPoint(index, edit_component_message) => {
+                let image_state = self.image_state.as_mut().unwrap();
+                let Some(editor_component) = image_state.editor_components.get_mut(index) else {
+                    return;
+                };
+                match editor_component.update(edit_component_message) {
+                    Action::Valid(point) => {
+                        image_state.twist_points.borrow_mut()[index] = point;
+                        if image_state.coordinate_entered(index) {
+                            self.update(Message::PoseLambdaTwist);
+                        }
+                    }
+                    Action::Invalid => {}
+                    Action::Submitted => {
+                        self.checkpoint();
+                    }
+                }
+            }
+            Message::AddTwistPoint => self.adding_twist_point = !self.adding_twist_point,
+            Message::TwistPointAdded(point) => {
+                self.adding_twist_point = false;
+                let image_state = self.image_state.as_mut().unwrap();
+                // the 3D coordinate is typed in afterwards, the placeholder must not take part
+                // in the solve meanwhile
+                image_state.twist_points.borrow_mut().push(Point3::origin());
+                image_state.twist_points_2d.borrow_mut().push(point);
+                image_state.twist_labels.push(TwistLabel {
+                    disabled: true,
+                    awaiting_coordinate: true,
+                    ..TwistLabel::default()
+                });
+                image_state.refresh_editor_components();
+                self.update(Message::PoseLambdaTwist);
+                self.checkpoint();
+            }
+            Message::SnapTwistPoint(index) => {
+                let image_state = self.image_state.as_mut().unwrap();
+                if image_state.corner_image.is_none() {
+                    match ::image::open(&image_state.image_path) {
+                        Ok(photo) => image_state.corner_image = Some(photo.to_luma8()),
+                        Err(error) => {
+                            info!("could not open {}: {error}", image_state.image_path);
+                            return;
+                        }
+                    }
+                }
+                let corner_image = image_state.corner_image.as_ref().unwrap();
+                let (width, height) = corner_image.dimensions();
+                let (width, height) = (width as f32, height as f32);
+                let Some(point) = image_state.twist_points_2d.borrow().get(index).copied() else {
+                    return;
+                };
+                let Some(corner) =
+                    snap_to_corner(corner_image, Point2::new(point.x * width, point.y * height))
+                else {
+                    info!("no corner near twist point {}", index + 1);
+                    return;
+                };
+                let snapped = Point2::new(corner.x / width, corner.y / height);
+                info!(
+                    "snapped twist point {} by {:.2} px",
+                    index + 1,
+                    (corner - Point2::new(point.x * width, point.y * height)).norm()
+                );
+                image_state.twist_points_2d.borrow_mut()[index] = snapped;
+                self.update(Message::PoseLambdaTwist);
+                // the drop may already be recorded, the snap belongs to the same step
+                if std::mem::take(&mut self.drop_recorded) {
+                    if let Some(snapshot) = self.snapshot() {
+                        self.history.amend(snapshot);
+                        self.unsaved_since = Some(Instant::now());
+                    }
+                } else {
+                    self.checkpoint();
+                }
+            }
+            Message::RemoveTwistPoint(index) => {
+                let image_state = self.image_state.as_mut().unwrap();
+                if index >= image_state.twist_labels.len() {
+                    return;
+                }
+                image_state.twist_points.borrow_mut().remove(index);
+                image_state.twist_points_2d.borrow_mut().remove(index);
+                image_state.twist_labels.remove(index);
+                image_state.refresh_editor_components();
+                self.pick_twist_point = None;
+                self.update(Message::PoseLambdaTwist);
+                self.checkpoint();
+            }
+            Message::ToggleTwistPoint(index) => {
+                let image_state = self.image_state.as_mut().unwrap();
+                if let Some(twist_label) = image_state.twist_labels.get_mut(index) {
+                    twist_label.disabled = !twist_label.disabled;
+                    twist_label.awaiting_coordinate = false;
+                }
+                self.update(Message::PoseLambdaTwist);
+                self.checkpoint();
+            }
+            Message::TwistPointNameChanged(index, name) => {
+                let image_state = self.image_state.as_mut().unwrap();
+                if let Some(twist_label) = image_state.twist_labels.get_mut(index) {
+                    twist_label.name = name;
+                }
+                // every key typed into the same name is one step
+                if self.name_recorded == Some(index) {
+                    if let Some(snapshot) = self.snapshot() {
+                        self.history.amend(snapshot);
+                        self.unsaved_since = Some(Instant::now());
+                    }
+                } else if self.checkpoint() {
+                    self.name_recorded = Some(index);
+                }
+            }
+            Message::MoveTwistPointUp(index) => {
+                let image_state = self.image_state.as_mut().unwrap();
+                if index == 0 || index >= image_state.twist_labels.len() {
+                    return;
+                }
+                image_state.twist_points.borrow_mut().swap(index - 1, index);
+                image_state
+                    .twist_points_2d
+                    .borrow_mut()
+                    .swap(index - 1, index);
+                image_state.twist_labels.swap(index - 1, index);
+                image_state.refresh_editor_components();
+                // lambda twist solves from the first three enabled points
+                self.update(Message::PoseLambdaTwist);
+                self.checkpoint();
+            }
+            Message::LoadImage => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Image", &["png", "jpg", "jpeg"])
+                    .pick_file()
+                {
+                    if self.image_state.is_none() {
+                        self.image_state = Some(ImageState {
+                            zoom: 0.5,
+                            ..Default::default()
+                        })
+                    };
+                    let path = path.to_str().unwrap().to_string();
+                    self.images.push(path.clone());
+                    self.update(Message::SelectImage((self.images.len() - 1) as u8));
+                    if self
+                        .image_state
+                        .as_ref()
+                        .is_none_or(|image_state| image_state.image_path != path)
+                    {
+                        self.images.pop();
+                    }
+                }
+            }
+            Message::LoadMesh => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Wavefront OBJ", &["obj"])
+                    .pick_file()
+                {
+                    match Mesh::load(&path) {
+                        Ok(mesh) => {
+                            trace!(
+                                "mesh {} with {} vertices and {} edges",
+                                path.display(),
+                                mesh.vertices.len(),
+                                mesh.edges.len()
+                            );
+                            self.mesh = Some(Rc::new(mesh));
+                            self.refresh_reference_cub();
+                        }
+                        Err(error) => info!("could not load mesh {}: {error}", path.display()),
+                    }
+                }
+            }
+            Message::ClearMesh => {
+                self.mesh = None;
+                self.pick_twist_point = None;
+                self.refresh_reference_cub();
+            }
+            Message::PickTwistPoint(index) => {
+                self.pick_twist_point = if self.pick_twist_point == Some(index) {
+                    None
+                } else {
+                    Some(index)
+                };
+            }
+            Message::MeshVertexPicked(index, vertex) => {
+                self.pick_twist_point = None;
+                let Some(point) = self.mesh.as_ref().map(|mesh| mesh.vertices[vertex]) else {
+                    return;
+                };
+                self.set_twist_point(index, point);
+            }
+            Message::NewWorldPoint => {
+                let next = self
+                    .session_observations()
+                    .iter()
+                    .flat_map(|(_, observations)| observations.iter().map(|item| item.id + 1))
+                    .chain(self.world_points.keys().map(|id| id + 1))
+                    .max()
+                    .unwrap_or(1);
+                self.update(Message::ChangeEdit(Edit::Observe(next)));
+            }
+            Message::RemoveObservation(id) => {
+                self.image_state
+                    .as_ref()
+                    .unwrap()
+                    .drawing
+                    .borrow_mut()
+                    .observations
+                    .retain(|observation| observation.id != id);
+                self.checkpoint();
+            }
+            Message::Triangulate => {
+                self.update(Message::Save);
+                self.remember_camera();
+                let mut views: BTreeMap<usize, Vec<(String, Point)>> = BTreeMap::new();
+                self.session_observations()
+                    .into_iter()
+                    .filter(|(image_path, _)| self.solved_cameras.contains_key(image_path))
+                    .for_each(|(image_path, observations)| {
+                        observations.into_iter().for_each(|observation| {
+                            views
+                                .entry(observation.id)
+                                .or_default()
+                                .push((image_path.clone(), observation.point));
+                        });
+                    });
+                self.world_points.clear();
+                views.iter().for_each(|(id, sightings)| {
+                    let sightings: Vec<View> = sightings
+                        .iter()
+                        .map(|(image_path, point)| {
+                            let solved_camera = &self.solved_cameras[image_path];
+                            View {
+                                compute_solution: &solved_camera.compute_solution,
+                                image_size: solved_camera.image_size,
+                                point: *point,
+                            }
+                        })
+                        .collect();
+                    match triangulate(&sightings) {
+                        Ok(world_point) => {
+                            trace!("P{id} at {}", world_point.position);
+                            self.world_points.insert(*id, world_point);
+                        }
+                        Err(error) => info!("could not triangulate P{id}: {error}"),
+                    }
+                });
+            }
+            Message::BundleAdjust => {
+                if self.world_points.is_empty() {
+                    self.update(Message::Triangulate);
+                } else {
+                    self.update(Message::Save);
+                    self.remember_camera();
+                }
+                let observations: Vec<(String, Vec<Observation>)> = self
+                    .session_observations()
+                    .into_iter()
+                    .filter(|(image_path, _)| self.solved_cameras.contains_key(image_path))
+                    .collect();
+                let images: Vec<BundleImage> = observations
+                    .iter()
+                    .map(|(image_path, observations)| {
+                        let solved_camera = &self.solved_cameras[image_path];
+                        BundleImage {
+                            compute_solution: solved_camera.compute_solution.clone(),
+                            image_size: solved_camera.image_size,
+                            shared_intrinsics: solved_camera.shared_intrinsics,
+                            control_points: solved_camera.control_points.clone(),
+                            observations: observations
+                                .iter()
+                                .map(|observation| (observation.id, observation.point))
+                                .collect(),
+                        }
+                    })
+                    .collect();
+                let points = self
+                    .world_points
+                    .iter()
+                    .map(|(id, world_point)| (*id, world_point.position))
+                    .collect();
+                match bundle_adjust(&images, &points) {
+                    Ok(bundle_adjustment) => {
+                        trace!(
+                            "bundle adjustment {} px over {} images",
+                            bundle_adjustment.root_mean_square,
+                            images.len()
+                        );
+                        observations
+                            .iter()
+                            .zip(&bundle_adjustment.cameras)
+                            .for_each(|((image_path, _), compute_solution)| {
+                                if let Some(solved_camera) = self.solved_cameras.get_mut(image_path)
+                                {
+                                    solved_camera.compute_solution = compute_solution.clone();
+                                    solved_camera.adjusted = true;
+                                }
+                            });
+                        bundle_adjustment.points.iter().for_each(|(id, position)| {
+                            if let Some(world_point) = self.world_points.get_mut(id) {
+                                world_point.position = *position;
+                                world_point.residuals = bundle_adjustment
+                                    .point_residuals
+                                    .get(id)
+                                    .cloned()
+                                    .unwrap_or_default();
+                            }
+                        });
+                        let image_state = self.image_state.as_mut().unwrap();
+                        if let Some(solved_camera) =
+                            self.solved_cameras.get(&image_state.image_path)
+                        {
+                            image_state.compute_solution =
+                                Some(solved_camera.compute_solution.clone());
+                        }
+                        self.bundle_images = observations
+                            .into_iter()
+                            .map(|(image_path, _)| image_path)
+                            .collect();
+                        self.bundle_adjustment = Some(bundle_adjustment);
+                    }
+                    Err(error) => info!("could not bundle adjust: {error}"),
+                }
+            }
+            Message::ExportColmap => {
+                let Some(bundle_adjustment) = &self.bundle_adjustment else {
+                    return;
+                };
+                let Some(folder) = rfd::FileDialog::new().pick_folder() else {
+                    return;
+                };
+                let observations: Vec<(String, Vec<(usize, Point)>)> = self
+                    .session_observations()
+                    .into_iter()
+                    .filter(|(image_path, _)| self.bundle_images.contains(image_path))
+                    .map(|(image_path, observations)| {
+                        let observations = observations
+                            .iter()
+                            .map(|observation| (observation.id, observation.point))
+                            .collect();
+                        (image_path, observations)
+                    })
+                    .collect();
+                let names: Vec<String> = self
+                    .bundle_images
+                    .iter()
+                    .map(|image_path| {
+                        Path::new(image_path)
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default()
+                    })
+                    .collect();
+                let images: Vec<ColmapImage> = self
+                    .bundle_images
+                    .iter()
+                    .zip(&names)
+                    .zip(&bundle_adjustment.cameras)
+                    .map(|((image_path, name), compute_solution)| ColmapImage {
+                        name,
+                        compute_solution,
+                        image_size: self.solved_cameras[image_path].image_size,
+                        observations: observations
+                            .iter()
+                            .find(|(item, _)| item == image_path)
+                            .map(|(_, observations)| observations.as_slice())
+                            .unwrap_or_default(),
+                    })
+                    .collect();
+                let errors = bundle_adjustment
+                    .point_residuals
+                    .iter()
+                    .map(|(id, residuals)| {
+                        (
+                            *id,
+                            residuals.iter().sum::<f32>() / residuals.len().max(1) as f32,
+                        )
+                    })
+                    .collect();
+                let files = to_colmap(&images, &bundle_adjustment.points, &errors);
+                for (name, content) in ["cameras.txt", "images.txt", "points3D.txt"]
+                    .iter()
+                    .zip(files)
+                {
+                    let path = folder.join(name);
+                    trace!("export COLMAP model to {}", path.display());
+                    if let Err(error) = std::fs::write(&path, content) {
+                        info!("could not write {}: {error}", path.display());
+                    }
+                }
+            }
+            Message::ExportAllFSpy => {
+                self.remember_camera();
+                self.solved_cameras
+                    .iter()
+                    .for_each(|(image_path, solved_camera)| {
+                        let export_file_name = Path::new(image_path)
+                            .with_extension("fspy")
+                            .to_string_lossy()
+                            .to_string();
+                        trace!("export to file {export_file_name}");
+                        let data = block_on(store_scene_data_to_file(
+                            &solved_camera.compute_solution,
+                            solved_camera.image_size.width as u32,
+                            solved_camera.image_size.height as u32,
+                            image_path.clone(),
+                            export_file_name,
+                            solved_camera.reference_distance_unit,
+                        ));
+                        if let Err(error) = data {
+                            info!("could not export {image_path}: {error}");
+                        }
+                    });
+            }
+            Message::UseWorldPoint(id, index) => {
+                let Some(point) = self.world_points.get(&id).map(|item| item.position) else {
+                    return;
+                };
+                self.set_twist_point(index, point);
+            }
+            Message::RemoveImage => {
+                if self.images.len() < 2 {
+                    return;
+                }
+                let selected = self.image_state.as_ref().unwrap().selected_image as usize;
+                self.update(Message::Save);
+                self.remember_camera();
+                let removed = self.images.remove(selected);
+                trace!("remove image {removed}");
+                // a fresh state so the removed image is not saved again when switching
+                self.image_state = Some(ImageState {
+                    zoom: 0.5,
+                    ..Default::default()
+                });
+                self.update(Message::SelectImage(
+                    selected.min(self.images.len() - 1) as u8
+                ));
+            }
+            Message::OpenProject(project_path) => match read_project(&project_path) {
+                Ok(project) => self.open_project(project_path, project),
+                Err(error) => info!("could not open {}: {error}", project_path.display()),
+            },
+            Message::PickProject => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Perspective project", &[PROJECT_EXTENSION])
+                    .pick_file()
+                {
+                    self.update(Message::OpenProject(path));
+                }
+            }
+            Message::SaveProjectAs => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Perspective project", &[PROJECT_EXTENSION])
+                    .save_file()
+                {
+                    // the other images move their points files into the project
+                    for image_path in &self.images {
+                        if self.project_data.contains_key(image_path) {
+                            continue;
+                        }
+                        let points = Path::new(image_path).with_extension("points");
+                        if let Ok(data) =
+                            read_lines_from_file(&points.to_string_lossy().to_string())
+                        {
+                            self.project_data.insert(image_path.clone(), data);
+                        }
+                    }
+                    self.project_path = Some(path.with_extension(PROJECT_EXTENSION));
+                    self.update(Message::Save);
+                }
+            }
+            Message::NoImage => {}
+            Message::LoadFailed(error) => {
+                info!("{error}");
+                // nothing to show when the first image cannot be opened
+                if self
+                    .image_state
+                    .as_ref()
+                    .is_some_and(|image_state| image_state.axis_data.is_none())
+                {
+                    self.image_state = None;
+                }
+                self.error = Some(error);
+            }
+            Message::DismissError => self.error = None,
+        }
+    }
+
+    fn snapshot(&self) -> Option<Snapshot> {
+        let image_state = self.image_state.as_ref()?;
+        Some(Snapshot {
+            images: self.images.clone(),
+            selected_image: image_state.selected_image,
+            mode: self.mode.clone(),
+            data: image_state
+                .axis_data
+                .is_some()
+                .then(|| <Lines as From<&Perspective>>::from(self)),
+        })
+    }
+
+    /// Records the state as an undo step, true when it changed since the last one.
+    fn checkpoint(&mut self) -> bool {
+        self.drop_recorded = false;
+        self.name_recorded = None;
+        if self.restoring {
+            return false;
+        }
+        if let Some(snapshot) = self.snapshot()
+            && self.history.checkpoint(snapshot)
+        {
+            self.unsaved_since = Some(Instant::now());
+            return true;
+        }
+        false
+    }
+
+    /// Goes back to `snapshot`, switching image when it was taken on another one.
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.restoring = true;
+        self.images = snapshot.images;
+        self.mode = snapshot.mode;
+        let selected_image = snapshot.selected_image;
+        if self.images.get(selected_image as usize)
+            != Some(&self.image_state.as_ref().unwrap().image_path)
+        {
+            self.update(Message::SelectImage(selected_image));
+        }
+        // the image of the snapshot could not be opened, its data belongs to no shown image
+        let selected = self.images.get(selected_image as usize)
+            == Some(&self.image_state.as_ref().unwrap().image_path);
+        if let Some(data) = snapshot.data.filter(|_| selected) {
+            let (axis_data, drawing) = read_points(data);
+            let image_size = self.image_state.as_ref().unwrap().image_size;
+            self.update(Message::LoadApplicationState {
+                image_data: Some(Box::new(ImageData {
+                    axis_data,
+                    drawing: Some(drawing),
+                })),
+                image_size: Size::new(image_size.width as u32, image_size.height as u32),
             });
+        }
+        self.restoring = false;
+        if let Some(snapshot) = self.snapshot() {
+            self.history.restored(snapshot);
+        }
+        self.unsaved_since = Some(Instant::now());
+    }
+
+    /// Data of `image_path` from the project or its points file, `None` for an image without data.
+    fn read_image_data(&self, image_path: &str) -> Result<Option<(AxisData, Drawing)>> {
+        if let Some(data) = self.project_data.get(image_path) {
+            return Ok(Some(read_points(data.clone())));
+        }
+        let points = Path::new(image_path).with_extension("points");
+        if !points.exists() {
+            return Ok(None);
+        }
+        read_points_from_file(&points.to_string_lossy().to_string())
+            .map(Some)
+            .map_err(|error| anyhow::anyhow!("could not read {}: {error}", points.display()))
+    }
+
+    /// Snapshot of the session for the project file.
+    fn project(&self) -> Project {
+        let project_path = self.project_path.as_deref().unwrap_or(Path::new(""));
+        let images = self
+            .images
+            .iter()
+            .map(|image_path| ProjectImage {
+                path: relative_image_path(project_path, image_path),
+                data: self.project_data.get(image_path).cloned(),
+                solution: self.solved_cameras.get(image_path).map(|solved_camera| {
+                    StoreSolution::new(
+                        &solved_camera.compute_solution,
+                        solved_camera.image_size,
+                        &solved_camera.control_points,
+                    )
+                }),
+            })
+            .collect();
+        let world_points = self
+            .world_points
+            .iter()
+            .map(|(id, world_point)| StoreWorldPoint {
+                id: *id,
+                position: StorePoint3d {
+                    x: world_point.position.x,
+                    y: world_point.position.y,
+                    z: world_point.position.z,
+                },
+                residuals: world_point.residuals.clone(),
+            })
+            .collect();
+        Project {
+            images,
+            world_points,
+            export: ExportSettings {
+                texture_pixels_per_unit: self.texture_pixels_per_unit.parse().ok(),
+                axis_length: self.reference_geometry.axis_length,
+                hidden_lines: Some(self.hidden_lines),
+                reference: Some(StoreReference::new(&self.reference_geometry)),
+            },
+            selected_image: self
+                .image_state
+                .as_ref()
+                .map(|image_state| image_state.selected_image as usize),
+            display: Some(self.display_adjustments),
+        }
+    }
+
+    /// Replaces the session with the images of `project` and selects its last selected image.
+    fn open_project(&mut self, project_path: PathBuf, project: Project) {
+        if self.image_state.is_some() {
+            self.update(Message::Save);
+        }
+        trace!(
+            "open project {} with {} images",
+            project_path.display(),
+            project.images.len()
+        );
+        self.images = project
+            .images
+            .iter()
+            .map(|image| resolve_image_path(&project_path, &image.path))
+            .collect();
+        self.project_data = self
+            .images
+            .iter()
+            .zip(&project.images)
+            .filter_map(|(image_path, image)| {
+                image.data.clone().map(|data| (image_path.clone(), data))
+            })
+            .collect();
+        self.solved_cameras = self
+            .images
+            .iter()
+            .zip(&project.images)
+            .filter_map(|(image_path, image)| {
+                let solution = image.solution.as_ref()?;
+                let (shared_intrinsics, reference_distance_unit) = image
+                    .data
+                    .as_ref()
+                    .map(|data| {
+                        (
+                            data.shared_intrinsics.unwrap_or(false),
+                            data.reference_distance_unit.unwrap_or_default(),
+                        )
+                    })
+                    .unwrap_or_default();
+                Some((
+                    image_path.clone(),
+                    SolvedCamera {
+                        compute_solution: solution.compute_solution(),
+                        image_size: solution.image_size(),
+                        shared_intrinsics,
+                        control_points: solution.control_points(),
+                        reference_distance_unit,
+                        inputs: None,
+                        adjusted: false,
+                    },
+                ))
+            })
+            .collect();
+        self.world_points = project
+            .world_points
+            .iter()
+            .map(|world_point| {
+                (
+                    world_point.id,
+                    TriangulatedPoint {
+                        position: Point3::new(
+                            world_point.position.x,
+                            world_point.position.y,
+                            world_point.position.z,
+                        ),
+                        residuals: world_point.residuals.clone(),
+                    },
+                )
+            })
+            .collect();
+        if let Some(texture_pixels_per_unit) = project.export.texture_pixels_per_unit {
+            self.texture_pixels_per_unit = texture_pixels_per_unit.to_string();
+        }
+        if let Some(axis_length) = project.export.axis_length {
+            self.reference_inputs[ReferenceField::AxisLength as usize] = axis_length.to_string();
+        }
+        self.reference_geometry.axis_length = project.export.axis_length;
+        if let Some(reference) = &project.export.reference {
+            self.reference_geometry = reference.reference_geometry(project.export.axis_length);
+            [
+                (ReferenceField::GridSpacing, reference.grid_spacing),
+                (ReferenceField::GridExtent, reference.grid_extent),
+                (ReferenceField::BoxX, reference.box_size.x),
+                (ReferenceField::BoxY, reference.box_size.y),
+                (ReferenceField::BoxZ, reference.box_size.z),
+            ]
+            .into_iter()
+            .for_each(|(field, value)| self.reference_inputs[field as usize] = value.to_string());
+        }
+        self.hidden_lines = project.export.hidden_lines.unwrap_or_default();
+        self.display_adjustments = project.display.unwrap_or_default();
+        self.shared_intrinsics = None;
+        self.shared_images.clear();
+        self.bundle_adjustment = None;
+        self.bundle_images.clear();
+        self.project_path = Some(project_path);
+        self.history = History::default();
+
+        if self.images.is_empty() {
+            self.image_state = None;
+            return;
+        }
+        // a fresh state so the previous image is not saved into the project
+        self.image_state = Some(ImageState {
+            zoom: 0.5,
+            ..Default::default()
+        });
+        let selected = project
+            .selected_image
+            .unwrap_or_default()
+            .min(self.images.len() - 1);
+        self.update(Message::SelectImage(selected as u8));
+    }
+
+    fn set_twist_point(&mut self, index: usize, point: Point3<f32>) {
+        let image_state = self.image_state.as_mut().unwrap();
+        if let Some(twist_point) = image_state.twist_points.borrow_mut().get_mut(index) {
+            *twist_point = point;
+        }
+        if let Some(editor_component) = image_state.editor_components.get_mut(index) {
+            *editor_component = EditorComponent::new(format!("#{}", index + 1), &point);
+        }
+        image_state.coordinate_entered(index);
+        self.update(Message::PoseLambdaTwist);
+    }
+
+    /// Adjusts the photo shown in the canvas to `display_adjustments`, the original photo is
+    /// shown without adjustments. `preview` adjusts a smaller copy, fast enough for every
+    /// slider step.
+    fn refresh_display_image(&mut self, preview: bool) {
+        let display_adjustments = self.display_adjustments;
+        let Some(image_state) = self.image_state.as_mut() else {
+            return;
+        };
+        if display_adjustments.is_neutral() {
+            image_state.display_image = None;
+            return;
+        }
+        if image_state
+            .display_image
+            .as_ref()
+            .is_some_and(|(adjustments, shown_preview, _)| {
+                *adjustments == display_adjustments && (preview || !shown_preview)
+            })
+        {
+            return;
+        }
+        if image_state.display_photo.is_none() {
+            match ::image::open(&image_state.image_path) {
+                Ok(photo) => image_state.display_photo = Some(DisplayPhoto::new(photo.to_rgba8())),
+                Err(error) => {
+                    info!("could not open {}: {error}", image_state.image_path);
+                    return;
+                }
+            }
+        }
+        let adjusted = image_state
+            .display_photo
+            .as_mut()
+            .unwrap()
+            .adjust(&display_adjustments, preview);
+        let handle =
+            image::Handle::from_rgba(adjusted.width(), adjusted.height(), adjusted.into_raw());
+        image_state.display_image = Some((display_adjustments, preview, handle));
+    }
+
+    /// Keeps the solution of the selected image for triangulating from the other images.
+    fn remember_camera(&mut self) {
+        let Some(image_state) = self.image_state.as_ref() else {
+            return;
+        };
+        let Some(compute_solution) = &image_state.compute_solution else {
+            return;
+        };
+        let inputs = self.pose_inputs();
+        // the adjusted pose is better than the one solved from the image alone
+        if self
+            .solved_cameras
+            .get(&image_state.image_path)
+            .is_some_and(|solved_camera| solved_camera.adjusted && solved_camera.inputs == inputs)
+        {
+            return;
+        }
+        let (shared_intrinsics, reference_distance_unit) = image_state
+            .axis_data
+            .as_ref()
+            .map(|axis_data| {
+                let axis_data = axis_data.borrow();
+                (
+                    axis_data.shared_intrinsics,
+                    axis_data.reference_distance_unit,
+                )
+            })
+            .unwrap_or_default();
+        let control_points = match self.mode {
+            UiMod::Twist => {
+                let (twist_points, twist_points_2d) = image_state.enabled_twist_points();
+                twist_points.into_iter().zip(twist_points_2d).collect()
+            }
+            UiMod::VanishingPoints => Vec::new(),
+        };
+        self.solved_cameras.insert(
+            image_state.image_path.clone(),
+            SolvedCamera {
+                compute_solution: compute_solution.clone(),
+                image_size: image_state.image_size,
+                shared_intrinsics,
+                control_points,
+                reference_distance_unit,
+                inputs,
+                adjusted: false,
+            },
+        );
+    }
 
-        info!("min {}, max {}", min, max);
-        let mut size = max - min;
-        if size.x == 0.0 {
-            size.x = 1.0
-        }
-        if size.y == 0.0 {
-            size.y = 1.0
-        }
-        if size.z == 0.0 {
-            size.z = 1.0
-        }
-        let mut reference_cube = vec![
-            Point3::<f32>::new(0.0, 0.0, 0.0),
-            Point3::<f32>::new(size.x, 0.0, 0.0),
-            Point3::<f32>::new(size.x, 0.0, 0.0),
-            Point3::<f32>::new(size.x, size.y, 0.0),
-            Point3::<f32>::new(size.x, size.y, 0.0),
-            Point3::<f32>::new(0.0, size.y, 0.0),
-            Point3::<f32>::new(0.0, size.y, 0.0),
-            Point3::<f32>::new(0.0, 0.0, 0.0),
-            // z
-            Point3::<f32>::new(0.0, 0.0, size.z),
-            Point3::<f32>::new(size.x, 0.0, size.z),
-            Point3::<f32>::new(size.x, 0.0, size.z),
-            Point3::<f32>::new(size.x, size.y, size.z),
-            Point3::<f32>::new(size.x, size.y, size.z),
-            Point3::<f32>::new(0.0, size.y, size.z),
-            Point3::<f32>::new(0.0, size.y, size.z),
-            Point3::<f32>::new(0.0, 0.0, size.z),
-        ];
-
-        for i in 0..=size.y as usize {
-            reference_cube.push(Point3::<f32>::new(0.0, 0.0 + i as f32, 0.0));
-            reference_cube.push(Point3::<f32>::new(size.x, 0.0 + i as f32, 0.0));
+    /// Data of the selected image a pose is solved from, shapes and measurements left out.
+    fn pose_inputs(&self) -> Option<Lines> {
+        self.image_state.as_ref()?.axis_data.as_ref()?;
+        Some(Lines {
+            shapes: None,
+            measurements: None,
+            ..<Lines as From<&Perspective>>::from(self)
+        })
+    }
+
+    /// Shows the bundle adjusted pose of the selected image instead of the one just solved
+    /// while the image is unchanged since the adjustment.
+    fn restore_adjusted_pose(&mut self) {
+        let inputs = self.pose_inputs();
+        let Some(image_state) = self.image_state.as_mut() else {
+            return;
+        };
+        if let Some(solved_camera) = self.solved_cameras.get(&image_state.image_path)
+            && solved_camera.adjusted
+            && solved_camera.inputs == inputs
+        {
+            image_state.compute_solution = Some(solved_camera.compute_solution.clone());
         }
+    }
 
-        for i in 0..=size.x as usize {
-            reference_cube.push(Point3::<f32>::new(0.0 + i as f32, 0.0, 0.0));
-            reference_cube.push(Point3::<f32>::new(0.0 + i as f32, size.y, 0.0));
+    /// Observations of every image, the selected one from memory, the others from the project or their points files.
+    fn session_observations(&self) -> Vec<(String, Vec<Observation>)> {
+        let image_state = self.image_state.as_ref().unwrap();
+        self.images
+            .iter()
+            .map(|image_path| {
+                let observations = if *image_path == image_state.image_path {
+                    image_state.drawing.borrow().observations.clone()
+                } else {
+                    match self.read_image_data(image_path) {
+                        Ok(image_data) => image_data
+                            .map(|(_, drawing)| drawing.observations)
+                            .unwrap_or_default(),
+                        Err(error) => {
+                            info!("{error}");
+                            Vec::new()
+                        }
+                    }
+                };
+                (image_path.clone(), observations)
+            })
+            .collect()
+    }
+
+    /// Locks the selected image to the shared field of view and principal point when it takes part.
+    fn apply_shared_intrinsics(&mut self) {
+        let Some(shared_intrinsics) = &self.shared_intrinsics else {
+            return;
+        };
+        let Some(image_state) = self.image_state.as_mut() else {
+            return;
+        };
+        let Some(axis_data) = &image_state.axis_data else {
+            return;
+        };
+        let mut axis_data = axis_data.borrow_mut();
+        if !axis_data.shared_intrinsics {
+            return;
         }
+        axis_data.lock_field_of_view = true;
+        axis_data.principal_point = Some(shared_intrinsics.principal_point);
+        image_state.field_of_view = shared_intrinsics.field_of_view.to_degrees();
+    }
 
-        reference_cube
-            .iter_mut()
-            .for_each(|item| item.coords += min.coords);
-        self.image_state
-            .as_mut()
-            .unwrap()
+    fn refresh_reference_cub(&mut self) {
+        let Some(image_state) = self.image_state.as_ref() else {
+            return;
+        };
+        let twist_points = image_state.twist_points.borrow();
+        let mut occluders = self.reference_geometry.faces(&twist_points);
+        if let Some(mesh) = &self.mesh {
+            occluders.extend(mesh.polygons());
+        }
+        self.occluders = Rc::new(occluders);
+        image_state
             .reference_cube
-            .replace(reference_cube);
+            .replace(self.reference_geometry.segments(&twist_points));
     }
     fn view(&self) -> Element<'_, Message> {
         let Some(_image_state) = self.image_state.as_ref() else {
             return center(
-                row![
-                    button("Click").on_press(Message::LoadImage),
-                    text("to open an image").width(Fill),
+                column![
+                    row![
+                        button("Click").on_press(Message::LoadImage),
+                        text("to open an image or").width(Length::Shrink),
+                        button("Open project").on_press(Message::PickProject),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center)
+                    .width(Length::Shrink),
                 ]
+                .push(text(self.error.clone().unwrap_or_default()).style(text::danger))
                 .spacing(10)
-                .align_y(Alignment::Center)
-                .width(Length::Shrink),
+                .align_x(Alignment::Center),
             )
             .into();
         };
 
         let component: Element<Message> = match self.mode {
-            UiMod::Twist => ComputeCameraPoseTwist::new(
+            UiMod::Twist => {
+                let twist = ComputeCameraPoseTwist::new(
+                    Rc::clone(&self.image_state.as_ref().unwrap().reference_cube),
+                    &self.image_state.as_ref().unwrap().compute_solution,
+                    Rc::clone(&self.image_state.as_ref().unwrap().twist_points),
+                    Rc::clone(&self.image_state.as_ref().unwrap().twist_points_2d),
+                    || Message::PoseLambdaTwist,
+                )
+                .show_horizon(self.show_horizon)
+                .reference_labels(self.reference_geometry.labels())
+                .mesh(self.mesh.clone())
+                .hidden_lines(self.hidden_lines, Rc::clone(&self.occluders))
+                .twist_labels(self.image_state.as_ref().unwrap().twist_labels.clone())
+                .image_size(self.image_state.as_ref().unwrap().image_size)
+                .on_snap(Message::SnapTwistPoint)
+                .width(Length::Fill)
+                .height(Length::Fill);
+                match self.pick_twist_point {
+                    Some(index) => twist
+                        .on_vertex_pick(move |vertex| Message::MeshVertexPicked(index, vertex))
+                        .into(),
+                    None if self.adding_twist_point => {
+                        twist.on_add_point(Message::TwistPointAdded).into()
+                    }
+                    None => twist.into(),
+                }
+            }
+            UiMod::VanishingPoints => ComputeCameraPoseVanishingPoints::new(
                 Rc::clone(&self.image_state.as_ref().unwrap().reference_cube),
                 &self.image_state.as_ref().unwrap().compute_solution,
-                Rc::clone(&self.image_state.as_ref().unwrap().twist_points),
-                Rc::clone(&self.image_state.as_ref().unwrap().twist_points_2d),
-                || Message::PoseLambdaTwist,
+                Rc::clone(
+                    self.image_state
+                        .as_ref()
+                        .unwrap()
+                        .axis_data
+                        .as_ref()
+                        .unwrap(),
+                ),
+                || Message::PoseVanishingPoints,
             )
+            .show_horizon(self.show_horizon)
+            .reference_labels(self.reference_geometry.labels())
+            .mesh(self.mesh.clone())
+            .hidden_lines(self.hidden_lines, Rc::clone(&self.occluders))
             .image_size(self.image_state.as_ref().unwrap().image_size)
             .width(Length::Fill)
             .height(Length::Fill)
             .into(),
         };
-        let canvas = scrollable(stack!(
-            image(
-                self.images
-                    .get(self.image_state.as_ref().unwrap().selected_image as usize)
-                    .unwrap()
-            )
-            .width(
-                self.image_state.as_ref().unwrap().image_size.width
-                    * self.image_state.as_ref().unwrap().zoom
-            )
-            .height(
-                self.image_state.as_ref().unwrap().image_size.height
-                    * self.image_state.as_ref().unwrap().zoom
-            ),
-            component,
-        ))
-        .direction(Direction::Both {
-            vertical: Scrollbar::default(),
-            horizontal: Scrollbar::default(),
-        });
+        let draw_tool = DrawTool::new(
+            &self.image_state.as_ref().unwrap().compute_solution,
+            Rc::clone(&self.image_state.as_ref().unwrap().drawing),
+            self.edit.clone(),
+            || Message::ShapesChanged,
+        )
+        .draw_plane(self.draw_plane)
+        .unit(
+            self.image_state
+                .as_ref()
+                .unwrap()
+                .axis_data
+                .as_ref()
+                .map(|axis_data| axis_data.borrow().reference_distance_unit)
+                .unwrap_or_default(),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill);
+        let zoom = self.image_state.as_ref().unwrap().zoom;
+        let canvas = PanZoom::new(
+            Loupe::new(stack!(
+                image(match &self.image_state.as_ref().unwrap().display_image {
+                    Some((_, _, handle)) => handle.clone(),
+                    None => image::Handle::from_path(
+                        self.images
+                            .get(self.image_state.as_ref().unwrap().selected_image as usize)
+                            .unwrap()
+                    ),
+                })
+                // image pixels stay sharp squares when zoomed in
+                .filter_method(if zoom > 2.0 {
+                    FilterMethod::Nearest
+                } else {
+                    FilterMethod::Linear
+                })
+                .width(self.image_state.as_ref().unwrap().image_size.width * zoom)
+                .height(self.image_state.as_ref().unwrap().image_size.height * zoom),
+                component,
+                draw_tool,
+            ))
+            .zoom(self.loupe_zoom),
+            self.image_state.as_ref().unwrap().image_size,
+            zoom,
+            self.image_state.as_ref().unwrap().offset,
+        )
+        .space_held(self.space_held)
+        .on_change(Message::ViewChanged);
 
         let canvas_with_context_menu = ContextMenu::new(canvas, move || {
             let mut buttons = Vec::new();
+            if self.history.can_undo() {
+                buttons.push(
+                    mouse_area(container("Undo (Ctrl+Z)").width(Length::Fill))
+                        .on_press(Message::Undo)
+                        .into(),
+                );
+            }
+            if self.history.can_redo() {
+                buttons.push(
+                    mouse_area(container("Redo (Ctrl+Shift+Z)").width(Length::Fill))
+                        .on_press(Message::Redo)
+                        .into(),
+                );
+            }
             match self.mode {
                 UiMod::Twist => {
                     buttons.push(
@@ -722,17 +2460,104 @@ impl Perspective {
                             .on_press(Message::ExportToFSpy)
                             .into(),
                     );
+                    buttons.push(
+                        mouse_area(container("Export Geometry To OBJ").width(Length::Fill))
+                            .on_press(Message::ExportToObj { textured: false })
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Export Textured OBJ").width(Length::Fill))
+                            .on_press(Message::ExportToObj { textured: true })
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Extract Face Textures").width(Length::Fill))
+                            .on_press(Message::ExtractTextures)
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Render Overlay To PNG").width(Length::Fill))
+                            .on_press(Message::RenderOverlay)
+                            .into(),
+                    );
                     buttons.push(
                         mouse_area(container("Save lines").width(Length::Fill))
                             .on_press(Message::Save)
                             .into(),
                     );
+                    buttons.push(
+                        mouse_area(container("Open Project").width(Length::Fill))
+                            .on_press(Message::PickProject)
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Save Project As").width(Length::Fill))
+                            .on_press(Message::SaveProjectAs)
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Remove Image").width(Length::Fill))
+                            .on_press(Message::RemoveImage)
+                            .into(),
+                    );
                     buttons.push(
                         mouse_area(container("Pose Lambda Twist").width(Length::Fill))
                             .on_press(Message::PoseLambdaTwist)
                             .into(),
                     );
                 }
+                UiMod::VanishingPoints => {
+                    buttons.push(
+                        mouse_area(container("Export Pose To FSpy").width(Length::Fill))
+                            .on_press(Message::ExportToFSpy)
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Export Geometry To OBJ").width(Length::Fill))
+                            .on_press(Message::ExportToObj { textured: false })
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Export Textured OBJ").width(Length::Fill))
+                            .on_press(Message::ExportToObj { textured: true })
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Extract Face Textures").width(Length::Fill))
+                            .on_press(Message::ExtractTextures)
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Render Overlay To PNG").width(Length::Fill))
+                            .on_press(Message::RenderOverlay)
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Save lines").width(Length::Fill))
+                            .on_press(Message::Save)
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Open Project").width(Length::Fill))
+                            .on_press(Message::PickProject)
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Save Project As").width(Length::Fill))
+                            .on_press(Message::SaveProjectAs)
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Remove Image").width(Length::Fill))
+                            .on_press(Message::RemoveImage)
+                            .into(),
+                    );
+                    buttons.push(
+                        mouse_area(container("Pose Vanishing Points").width(Length::Fill))
+                            .on_press(Message::PoseVanishingPoints)
+                            .into(),
+                    );
+                }
             }
             column(buttons).width(300).padding(5).spacing(7).into()
         });
@@ -749,10 +2574,790 @@ impl Perspective {
                 )
                 .step(0.1)
             ]),
+            UiMod::VanishingPoints => {
+                let image_state = self.image_state.as_ref().unwrap();
+                let locked = image_state
+                    .axis_data
+                    .as_ref()
+                    .is_some_and(|axis_data| axis_data.borrow().lock_field_of_view);
+                let solved = image_state
+                    .compute_solution
+                    .as_ref()
+                    .map(|compute_solution| compute_solution.field_of_view().to_degrees());
+                let deviation = image_state
+                    .focal_length_deviation
+                    .map(|deviation| {
+                        format!(
+                        "Free solution {:.1} degrees ({:+.1})\nPrincipal point offset {:.1}, {:.1} px\nAxes off square by {:.2} degrees",
+                        deviation.free_field_of_view.to_degrees(),
+                        deviation.free_field_of_view.to_degrees() - image_state.field_of_view,
+                        deviation.principal_point_offset.x * image_state.image_size.width / 2.0,
+                        -deviation.principal_point_offset.y * image_state.image_size.width / 2.0,
+                        deviation.orthogonality_error.to_degrees(),
+                    )
+                    })
+                    .unwrap_or_default();
+                let reference_distance = image_state.axis_data.as_ref().and_then(|axis_data| {
+                    let axis_data = axis_data.borrow();
+                    axis_data
+                        .reference_distance
+                        .as_ref()
+                        .map(|reference| (reference.axis, axis_data.custom_scale))
+                });
+                let reference_distance_element: Element<Message> =
+                    if let Some((axis, scale)) = reference_distance {
+                        column![
+                            row![
+                                pick_list(
+                                    EditAxis::ALL,
+                                    Some(axis),
+                                    Message::ReferenceDistanceAxisChanged
+                                ),
+                                text_input("length", &image_state.reference_distance_length)
+                                    .on_input(Message::ReferenceDistanceLengthChanged)
+                                    .on_submit(Message::Checkpoint),
+                            ]
+                            .align_y(Vertical::Center)
+                            .spacing(5),
+                            text(match scale {
+                                Some(scale) => format!("Scale 1:{scale:.4}"),
+                                None => "Reference distance not usable".to_string(),
+                            }),
+                        ]
+                        .spacing(5)
+                        .into()
+                    } else {
+                        column![].into()
+                    };
+                container(
+                    column![
+                        text(match (locked, solved) {
+                            (true, _) => format!(
+                                "Field of view {:.1} degrees (locked)",
+                                image_state.field_of_view
+                            ),
+                            (false, Some(solved)) =>
+                                format!("Field of view {solved:.1} degrees (solved)"),
+                            (false, None) => "Field of view not solved".to_string(),
+                        }),
+                        slider(
+                            1.0f32..=130.0f32,
+                            image_state.field_of_view,
+                            Message::FieldOfViewChanged
+                        )
+                        .step(0.1),
+                        button(
+                            text(if locked {
+                                "Unlock field of view"
+                            } else {
+                                "Lock field of view"
+                            })
+                            .width(Length::Fill)
+                            .align_x(Horizontal::Center)
+                        )
+                        .on_press(Message::ToggleLockFieldOfView)
+                        .width(Length::Fill),
+                        text(deviation),
+                        button(
+                            text(if reference_distance.is_some() {
+                                "Remove reference distance"
+                            } else {
+                                "Add reference distance"
+                            })
+                            .width(Length::Fill)
+                            .align_x(Horizontal::Center)
+                        )
+                        .on_press(Message::ToggleReferenceDistance)
+                        .width(Length::Fill),
+                        reference_distance_element,
+                    ]
+                    .spacing(5),
+                )
+            }
+        };
+
+        let shared_intrinsics_element = {
+            let image_state = self.image_state.as_ref().unwrap();
+            let sharing = image_state
+                .axis_data
+                .as_ref()
+                .is_some_and(|axis_data| axis_data.borrow().shared_intrinsics);
+            let summary = self
+                .shared_intrinsics
+                .as_ref()
+                .map(|shared_intrinsics| {
+                    let mut summary = format!(
+                        "Shared field of view {:.1} degrees from the vanishing lines of {} images\nPrincipal point offset {:.1}, {:.1} px",
+                        shared_intrinsics.field_of_view.to_degrees(),
+                        self.shared_images.len(),
+                        shared_intrinsics.principal_point.x * image_state.image_size.width / 2.0,
+                        -shared_intrinsics.principal_point.y * image_state.image_size.width / 2.0,
+                    );
+                    self.shared_images
+                        .iter()
+                        .zip(&shared_intrinsics.residuals)
+                        .for_each(|(image_path, residual)| {
+                            let name = Path::new(image_path)
+                                .file_name()
+                                .map(|name| name.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            summary.push_str(&match residual {
+                                Some(residual) => format!(
+                                    "\n{name}: axes off square by {:.2} degrees",
+                                    residual.to_degrees()
+                                ),
+                                None => format!("\n{name}: no vanishing points"),
+                            });
+                        });
+                    summary
+                })
+                .unwrap_or_default();
+            column![
+                row![
+                    button(
+                        text(if sharing {
+                            "Stop sharing intrinsics"
+                        } else {
+                            "Share intrinsics"
+                        })
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center)
+                    )
+                    .on_press(Message::ToggleSharedIntrinsics)
+                    .width(Length::Fill),
+                    button(
+                        text("Estimate shared")
+                            .width(Length::Fill)
+                            .align_x(Horizontal::Center)
+                    )
+                    .on_press(Message::EstimateSharedIntrinsics)
+                    .width(Length::Fill),
+                ]
+                .spacing(5),
+                text(summary),
+            ]
+            .spacing(5)
+        };
+
+        let uncertainty_element = {
+            let image_state = self.image_state.as_ref().unwrap();
+            let summary = image_state
+                .uncertainty
+                .as_ref()
+                .map(|uncertainty| {
+                    let interval = |interval: &Interval| {
+                        format!("{:.2} [{:.2}, {:.2}]", interval.value, interval.low, interval.high)
+                    };
+                    let [x, y, z] = &uncertainty.camera_position;
+                    let [rx, ry, rz] = &uncertainty.camera_rotation;
+                    format!(
+                        "{:.0}% interval, {}/{} samples solved\nField of view {}\nPosition x {}\nPosition y {}\nPosition z {}\nRotation x {}\nRotation y {}\nRotation z {}",
+                        uncertainty.confidence * 100.0,
+                        uncertainty.solved_samples,
+                        uncertainty.samples,
+                        interval(&uncertainty.field_of_view),
+                        interval(x),
+                        interval(y),
+                        interval(z),
+                        interval(rx),
+                        interval(ry),
+                        interval(rz),
+                    )
+                })
+                .unwrap_or_default();
+            column![
+                text(format!("Handle error ±{:.1} px", self.pixel_error)),
+                slider(
+                    0.1f32..=5.0f32,
+                    self.pixel_error,
+                    Message::PixelErrorChanged
+                )
+                .step(0.1),
+                button(
+                    text("Estimate uncertainty")
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center)
+                )
+                .on_press(Message::EstimateUncertainty)
+                .width(Length::Fill),
+                text(summary),
+            ]
+            .spacing(5)
+        };
+
+        let measurements_element: Element<Message> = {
+            let image_state = self.image_state.as_ref().unwrap();
+            let unit = image_state
+                .axis_data
+                .as_ref()
+                .map(|axis_data| axis_data.borrow().reference_distance_unit)
+                .unwrap_or_default();
+            let ratio = image_state.image_size.width / image_state.image_size.height;
+            column(
+                image_state
+                    .drawing
+                    .borrow()
+                    .measurements
+                    .iter()
+                    .enumerate()
+                    .map(|(index, measurement)| {
+                        let length =
+                            image_state
+                                .compute_solution
+                                .as_ref()
+                                .and_then(|compute_solution| {
+                                    measure_points(
+                                        compute_solution,
+                                        ratio,
+                                        &Vector2::new(measurement.line.0.x, measurement.line.0.y),
+                                        &Vector2::new(measurement.line.1.x, measurement.line.1.y),
+                                        &measurement.axis,
+                                    )
+                                    .map(|points| measurement_length(&points, &measurement.axis))
+                                });
+                        let kind = match measurement.axis {
+                            EditAxis::None => "distance".to_string(),
+                            axis => format!("height {axis}"),
+                        };
+                        row![
+                            text(match length {
+                                Some(length) => format!("#{} {kind} {length:.3} {unit}", index + 1),
+                                None => format!("#{} {kind} not measurable", index + 1),
+                            })
+                            .width(Length::Fill),
+                            button("x").on_press(Message::RemoveMeasurement(index)),
+                        ]
+                        .align_y(Vertical::Center)
+                        .spacing(5)
+                        .into()
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .spacing(2)
+            .into()
+        };
+        let extrude_axis = match self.edit {
+            Edit::Extrude(axis) => Some(axis),
+            _ => None,
+        };
+        let tools_element = column![
+            text(match self.edit {
+                Edit::Draw =>
+                    "Click to place points, click the first point to close, Enter to finish",
+                Edit::Extrude(_) => "Drag a point of a shape to extrude it",
+                Edit::Measure(EditAxis::None) => "Drag between two points on the ground",
+                Edit::Measure(_) => "Drag from a point on the ground up to the top",
+                _ => "Shapes",
+            }),
+            row![
+                button(text("Draw").width(Length::Fill).align_x(Horizontal::Center))
+                    .on_press(Message::ChangeEdit(Edit::Draw))
+                    .width(Length::Fill),
+                button(
+                    text("Select")
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center)
+                )
+                .on_press(Message::ChangeEdit(Edit::None))
+                .width(Length::Fill),
+                button(
+                    text("Clear")
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center)
+                )
+                .on_press(Message::ClearShapes)
+                .width(Length::Fill),
+            ]
+            .spacing(5),
+            row![
+                text("Plane normal"),
+                pick_list(
+                    EditAxis::ALL,
+                    Some(self.draw_plane),
+                    Message::DrawPlaneChanged
+                )
+                .width(Length::Fill),
+            ]
+            .align_y(Vertical::Center)
+            .spacing(5),
+            row![
+                button(
+                    text("Measure")
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center)
+                )
+                .on_press(Message::ChangeEdit(Edit::Measure(EditAxis::None)))
+                .width(Length::Fill),
+                button(
+                    text("Height")
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center)
+                )
+                .on_press(Message::ChangeEdit(Edit::Measure(EditAxis::EditZ)))
+                .width(Length::Fill),
+            ]
+            .spacing(5),
+            measurements_element,
+            row![
+                text("Texture px/unit"),
+                text_input("100", &self.texture_pixels_per_unit)
+                    .on_input(Message::TexturePixelsPerUnitChanged),
+            ]
+            .align_y(Vertical::Center)
+            .spacing(5),
+            row![
+                text("Extrude along"),
+                pick_list(EditAxis::ALL, extrude_axis, |axis| Message::ChangeEdit(
+                    Edit::Extrude(axis)
+                ))
+                .placeholder("axis")
+                .width(Length::Fill),
+            ]
+            .align_y(Vertical::Center)
+            .spacing(5),
+        ]
+        .spacing(5);
+
+        let reference_input = |field: ReferenceField, placeholder: &str| {
+            text_input(placeholder, &self.reference_inputs[field as usize])
+                .on_input(move |value| Message::ReferenceFieldChanged(field, value))
+        };
+        let box_size_element: Element<Message> =
+            if self.reference_geometry.box_kind == ReferenceBox::Dimensions {
+                row![
+                    reference_input(ReferenceField::BoxX, "x"),
+                    reference_input(ReferenceField::BoxY, "y"),
+                    reference_input(ReferenceField::BoxZ, "z"),
+                ]
+                .spacing(5)
+                .into()
+            } else {
+                column![].into()
+            };
+        let reference_element = column![
+            text("Reference"),
+            row![
+                pick_list(
+                    ReferencePlane::ALL,
+                    Some(self.reference_geometry.grid_plane),
+                    Message::ReferencePlaneChanged
+                )
+                .width(Length::Fill),
+                reference_input(ReferenceField::GridSpacing, "spacing"),
+                reference_input(ReferenceField::GridExtent, "extent"),
+            ]
+            .align_y(Vertical::Center)
+            .spacing(5),
+            pick_list(
+                ReferenceBox::ALL,
+                Some(self.reference_geometry.box_kind),
+                Message::ReferenceBoxChanged
+            )
+            .width(Length::Fill),
+            box_size_element,
+            row![
+                button(
+                    text(if self.reference_geometry.axis_length.is_some() {
+                        "Hide axes"
+                    } else {
+                        "Show axes"
+                    })
+                    .width(Length::Fill)
+                    .align_x(Horizontal::Center)
+                )
+                .on_press(Message::ToggleReferenceAxes)
+                .width(Length::Fill),
+                reference_input(ReferenceField::AxisLength, "length"),
+            ]
+            .align_y(Vertical::Center)
+            .spacing(5),
+            row![
+                pick_list(
+                    HiddenLineMode::ALL,
+                    Some(self.hidden_lines.mode),
+                    Message::HiddenLineModeChanged
+                )
+                .width(Length::Fill),
+                button(
+                    text(if self.hidden_lines.dash_hidden {
+                        "Omit hidden"
+                    } else {
+                        "Dash hidden"
+                    })
+                    .width(Length::Fill)
+                    .align_x(Horizontal::Center)
+                )
+                .on_press(Message::ToggleDashHidden)
+                .width(Length::Fill),
+            ]
+            .align_y(Vertical::Center)
+            .spacing(5),
+        ]
+        .spacing(5);
+
+        let pick_buttons: Element<Message> = if self.mesh.is_some() {
+            row(
+                (0..self.image_state.as_ref().unwrap().twist_labels.len()).map(|index| {
+                    button(
+                        text(if self.pick_twist_point == Some(index) {
+                            format!("Picking #{}", index + 1)
+                        } else {
+                            format!("Pick #{}", index + 1)
+                        })
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center),
+                    )
+                    .on_press(Message::PickTwistPoint(index))
+                    .width(Length::Fill)
+                    .into()
+                }),
+            )
+            .spacing(5)
+            .into()
+        } else {
+            column![].into()
+        };
+        let mesh_element = column![
+            text(match &self.mesh {
+                Some(mesh) => format!(
+                    "Mesh {} vertices, {} edges",
+                    mesh.vertices.len(),
+                    mesh.edges.len()
+                ),
+                None => "No reference mesh".to_string(),
+            }),
+            row![
+                button(
+                    text("Load mesh")
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center)
+                )
+                .on_press(Message::LoadMesh)
+                .width(Length::Fill),
+                button(
+                    text("Clear mesh")
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center)
+                )
+                .on_press(Message::ClearMesh)
+                .width(Length::Fill),
+            ]
+            .spacing(5),
+            pick_buttons,
+        ]
+        .spacing(5);
+
+        let display_element = {
+            let adjustments = self.display_adjustments;
+            column![
+                text(format!("Exposure {:+.1} EV", adjustments.exposure)),
+                slider(-4.0f32..=4.0f32, adjustments.exposure, move |exposure| {
+                    Message::DisplayAdjustmentsChanged(DisplayAdjustments {
+                        exposure,
+                        ..adjustments
+                    })
+                })
+                .step(0.1),
+                text(format!("Gamma {:.2}", adjustments.gamma)),
+                slider(0.2f32..=3.0f32, adjustments.gamma, move |gamma| {
+                    Message::DisplayAdjustmentsChanged(DisplayAdjustments {
+                        gamma,
+                        ..adjustments
+                    })
+                })
+                .step(0.05),
+                text(format!("Contrast {:.2}", adjustments.contrast)),
+                slider(0.2f32..=3.0f32, adjustments.contrast, move |contrast| {
+                    Message::DisplayAdjustmentsChanged(DisplayAdjustments {
+                        contrast,
+                        ..adjustments
+                    })
+                })
+                .step(0.05),
+                row![
+                    button(
+                        text(if adjustments.grayscale {
+                            "Color"
+                        } else {
+                            "Grayscale"
+                        })
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center)
+                    )
+                    .on_press(Message::DisplayAdjustmentsChanged(DisplayAdjustments {
+                        grayscale: !adjustments.grayscale,
+                        ..adjustments
+                    }))
+                    .width(Length::Fill),
+                    button(
+                        text(if adjustments.edges {
+                            "Hide edges"
+                        } else {
+                            "Show edges"
+                        })
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center)
+                    )
+                    .on_press(Message::DisplayAdjustmentsChanged(DisplayAdjustments {
+                        edges: !adjustments.edges,
+                        ..adjustments
+                    }))
+                    .width(Length::Fill),
+                    button(
+                        text("Reset")
+                            .width(Length::Fill)
+                            .align_x(Horizontal::Center)
+                    )
+                    .on_press_maybe((!adjustments.is_neutral()).then(|| {
+                        Message::DisplayAdjustmentsChanged(DisplayAdjustments::default())
+                    }))
+                    .width(Length::Fill),
+                ]
+                .spacing(5),
+            ]
+            .spacing(5)
+        };
+
+        let twist_points_element = {
+            let image_state = self.image_state.as_ref().unwrap();
+            let count = image_state.twist_labels.len();
+            let enabled = image_state
+                .twist_labels
+                .iter()
+                .filter(|twist_label| !twist_label.disabled)
+                .count();
+            column![
+                row![
+                    text(format!("Correspondences, {enabled} of {count} enabled"))
+                        .width(Length::Fill),
+                    button(text(if self.adding_twist_point {
+                        "Click the image"
+                    } else {
+                        "Add point"
+                    }))
+                    .on_press(Message::AddTwistPoint),
+                ]
+                .align_y(Vertical::Center)
+                .spacing(5),
+                column(
+                    image_state
+                        .twist_labels
+                        .iter()
+                        .zip(&image_state.editor_components)
+                        .enumerate()
+                        .map(|(index, (twist_label, editor_component))| {
+                            column![
+                                row![
+                                    text_input(&format!("Point #{}", index + 1), &twist_label.name)
+                                        .on_input(move |name| {
+                                            Message::TwistPointNameChanged(index, name)
+                                        })
+                                        .on_submit(Message::Checkpoint),
+                                    button(text(if twist_label.disabled {
+                                        "Enable"
+                                    } else {
+                                        "Disable"
+                                    }))
+                                    .on_press(Message::ToggleTwistPoint(index)),
+                                    button("↑").on_press_maybe(
+                                        (index > 0).then_some(Message::MoveTwistPointUp(index))
+                                    ),
+                                    button("↓").on_press_maybe(
+                                        (index + 1 < count)
+                                            .then_some(Message::MoveTwistPointUp(index + 1))
+                                    ),
+                                    button("x").on_press(Message::RemoveTwistPoint(index)),
+                                ]
+                                .align_y(Vertical::Center)
+                                .spacing(5),
+                                editor_component
+                                    .view(move |action| Message::EditPoint(index, action)),
+                            ]
+                            .push(twist_label.awaiting_coordinate.then(|| {
+                                text("Enter the 3D coordinate to use this point").size(12)
+                            }))
+                            .into()
+                        })
+                )
+                .spacing(5),
+            ]
+            .spacing(5)
+        };
+        let error_element: Element<Message> = match &self.error {
+            Some(error) => row![
+                text(error.clone()).style(text::danger).width(Length::Fill),
+                button("Dismiss").on_press(Message::DismissError),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center)
+            .into(),
+            None => column![].into(),
+        };
+        let save_element: Element<Message> = match &self.save_error {
+            Some(save_error) if self.image_state.as_ref().unwrap().save_blocked => column![
+                text(save_error.clone()).style(text::danger),
+                button(
+                    text("Save anyway")
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center)
+                )
+                .on_press(Message::SaveAnyway)
+                .width(Length::Fill),
+            ]
+            .spacing(5)
+            .into(),
+            Some(save_error) => text(save_error.clone()).style(text::danger).into(),
+            None if self.unsaved_since.is_some() => text("Unsaved changes").into(),
+            None if self.project_path.is_some() => text("Saved to project").into(),
+            None => text("Saved").into(),
+        };
+        let world_points_element = {
+            let image_state = self.image_state.as_ref().unwrap();
+            let inputs = self.pose_inputs();
+            let bundle_summary =
+                self.bundle_adjustment
+                    .as_ref()
+                    .map(|bundle_adjustment| {
+                        let mut summary = format!(
+                            "Bundle adjusted, {:.2} px RMS",
+                            bundle_adjustment.root_mean_square
+                        );
+                        self.bundle_images
+                            .iter()
+                            .zip(&bundle_adjustment.image_residuals)
+                            .for_each(|(image_path, residual)| {
+                                let name = Path::new(image_path)
+                                    .file_name()
+                                    .map(|name| name.to_string_lossy().to_string())
+                                    .unwrap_or_default();
+                                summary.push_str(&match residual {
+                                    Some(residual) => format!("\n{name}: {residual:.2} px"),
+                                    None => format!("\n{name}: nothing observed"),
+                                });
+                                // the selected image is compared as edited, the others as remembered
+                                let adjusted = self.solved_cameras.get(image_path).is_some_and(
+                                    |solved_camera| {
+                                        solved_camera.adjusted
+                                            && (*image_path != image_state.image_path
+                                                || solved_camera.inputs == inputs)
+                                    },
+                                );
+                                if !adjusted {
+                                    summary.push_str(", changed since, adjust again");
+                                }
+                            });
+                        summary
+                    })
+                    .unwrap_or_default();
+            let observed: Vec<usize> = image_state
+                .drawing
+                .borrow()
+                .observations
+                .iter()
+                .map(|observation| observation.id)
+                .collect();
+            let mut ids: Vec<usize> = self
+                .world_points
+                .keys()
+                .copied()
+                .chain(observed.iter().copied())
+                .collect();
+            if let Edit::Observe(id) = self.edit {
+                ids.push(id);
+            }
+            ids.sort_unstable();
+            ids.dedup();
+            column![
+                text(match self.edit {
+                    Edit::Observe(id) => format!("Click P{id} in every solved image it is seen in"),
+                    _ => "World points".to_string(),
+                }),
+                row![
+                    button(
+                        text("New point")
+                            .width(Length::Fill)
+                            .align_x(Horizontal::Center)
+                    )
+                    .on_press(Message::NewWorldPoint)
+                    .width(Length::Fill),
+                    button(
+                        text("Triangulate")
+                            .width(Length::Fill)
+                            .align_x(Horizontal::Center)
+                    )
+                    .on_press(Message::Triangulate)
+                    .width(Length::Fill),
+                ]
+                .spacing(5),
+                row![
+                    button(
+                        text("Bundle adjust")
+                            .width(Length::Fill)
+                            .align_x(Horizontal::Center)
+                    )
+                    .on_press(Message::BundleAdjust)
+                    .width(Length::Fill),
+                    button(
+                        text("COLMAP")
+                            .width(Length::Fill)
+                            .align_x(Horizontal::Center)
+                    )
+                    .on_press_maybe(
+                        self.bundle_adjustment
+                            .as_ref()
+                            .map(|_| Message::ExportColmap)
+                    )
+                    .width(Length::Fill),
+                    button(
+                        text("All .fspy")
+                            .width(Length::Fill)
+                            .align_x(Horizontal::Center)
+                    )
+                    .on_press(Message::ExportAllFSpy)
+                    .width(Length::Fill),
+                ]
+                .spacing(5),
+                text(bundle_summary),
+                column(ids.into_iter().map(|id| {
+                    let world_point = self.world_points.get(&id);
+                    let label = match world_point {
+                        Some(world_point) => format!(
+                            "P{id} {:.2}, {:.2}, {:.2}\n{} views, max {:.1} px",
+                            world_point.position.x,
+                            world_point.position.y,
+                            world_point.position.z,
+                            world_point.residuals.len(),
+                            world_point.residuals.iter().copied().fold(0.0, f32::max),
+                        ),
+                        None => format!("P{id} not triangulated"),
+                    };
+                    let mut item = row![
+                        text(label).width(Length::Fill),
+                        button("Observe").on_press(Message::ChangeEdit(Edit::Observe(id))),
+                    ]
+                    .align_y(Vertical::Center)
+                    .spacing(5);
+                    if world_point.is_some() {
+                        item = (0..image_state.twist_labels.len()).fold(item, |item, index| {
+                            item.push(
+                                button(text(format!("#{}", index + 1)))
+                                    .on_press(Message::UseWorldPoint(id, index)),
+                            )
+                        });
+                    }
+                    if observed.contains(&id) {
+                        item = item.push(button("x").on_press(Message::RemoveObservation(id)));
+                    }
+                    item.into()
+                }))
+                .spacing(2),
+            ]
+            .spacing(5)
         };
 
         let mode = match self.mode {
             UiMod::Twist => text("Twist Mode"),
+            UiMod::VanishingPoints => text("Vanishing Points Mode"),
         };
         column!(
             row!(
@@ -762,46 +3367,97 @@ impl Perspective {
                     .align_x(Horizontal::Center)
                     .align_y(Vertical::Center),
                 column!(
-                    container(
-                        column!(
-                            mode,
-                            button(
-                                text("Add image")
+                    // the sections do not fit a normal window height, the twist points come last
+                    scrollable(
+                        container(
+                            column!(
+                                mode,
+                                error_element,
+                                save_element,
+                                row![
+                                    button(
+                                        text("Twist")
+                                            .width(Length::Fill)
+                                            .align_x(Horizontal::Center)
+                                    )
+                                    .on_press(Message::ChangeMode(UiMod::Twist))
+                                    .width(Length::Fill),
+                                    button(
+                                        text("Vanishing points")
+                                            .width(Length::Fill)
+                                            .align_x(Horizontal::Center)
+                                    )
+                                    .on_press(Message::ChangeMode(UiMod::VanishingPoints))
+                                    .width(Length::Fill),
+                                ]
+                                .spacing(5),
+                                row![
+                                    text("Unit"),
+                                    pick_list(
+                                        ReferenceDistanceUnit::ALL,
+                                        self.image_state.as_ref().unwrap().axis_data.as_ref().map(
+                                            |axis_data| axis_data.borrow().reference_distance_unit
+                                        ),
+                                        Message::ReferenceDistanceUnitChanged
+                                    )
+                                    .width(Length::Fill),
+                                ]
+                                .align_y(Vertical::Center)
+                                .spacing(5),
+                                button(
+                                    text(if self.show_horizon {
+                                        "Hide horizon"
+                                    } else {
+                                        "Show horizon"
+                                    })
                                     .width(Length::Fill)
                                     .align_x(Horizontal::Center)
+                                )
+                                .on_press(Message::ToggleHorizon)
+                                .width(Length::Fill),
+                                button(
+                                    text("Add image")
+                                        .width(Length::Fill)
+                                        .align_x(Horizontal::Center)
+                                )
+                                .on_press(Message::LoadImage)
+                                .width(Length::Fill),
+                                text(format!(
+                                    "Scale {:.1}x",
+                                    self.image_state.as_ref().unwrap().zoom
+                                )),
+                                slider(
+                                    MIN_ZOOM..=MAX_ZOOM,
+                                    self.image_state.as_ref().unwrap().zoom,
+                                    Message::ZoomChanged
+                                )
+                                .step(0.05),
+                                text(if self.loupe_zoom > 1.0 {
+                                    format!("Loupe {:.0}x", self.loupe_zoom)
+                                } else {
+                                    "Loupe off".to_string()
+                                }),
+                                slider(
+                                    1.0f32..=16.0f32,
+                                    self.loupe_zoom,
+                                    Message::LoupeZoomChanged
+                                )
+                                .step(1.0),
+                                display_element,
+                                field_of_view_element,
+                                shared_intrinsics_element,
+                                uncertainty_element,
+                                tools_element,
+                                reference_element,
+                                mesh_element,
+                                world_points_element,
+                                twist_points_element,
                             )
-                            .on_press(Message::LoadImage)
-                            .width(Length::Fill),
-                            text(format!(
-                                "Scale {:.1}x",
-                                self.image_state.as_ref().unwrap().zoom
-                            )),
-                            slider(
-                                0.25f32..=1.0f32,
-                                self.image_state.as_ref().unwrap().zoom,
-                                Message::ZoomChanged
-                            )
-                            .step(0.05),
-                            field_of_view_element,
-                            self.image_state
-                                .as_ref()
-                                .unwrap()
-                                .editor_component_1
-                                .view(&move |action| Message::EditPoint(0, action)),
-                            self.image_state
-                                .as_ref()
-                                .unwrap()
-                                .editor_component_2
-                                .view(&move |action| Message::EditPoint(1, action)),
-                            self.image_state
-                                .as_ref()
-                                .unwrap()
-                                .editor_component_3
-                                .view(&move |action| Message::EditPoint(2, action)),
+                            .spacing(5)
                         )
-                        .spacing(5)
+                        .padding(10)
                     )
-                    .padding(10),
+                    .height(Length::FillPortion(2)),
                     scrollable(
                         column(self.images.iter().enumerate().map(|(index, item)| {
                             let opacity = if index as u8
@@ -824,6 +3480,7 @@ impl Perspective {
                         .spacing(20)
                         .padding(20)
                     )
+                    .height(Length::FillPortion(1))
                 )
                 .width(300)
             )
@@ -899,20 +3556,69 @@ impl From<&Perspective> for Lines {
             },
             twist_points: Some(twist_points),
             twist_points_2d: Some(twist_points_2d),
+            twist_labels: Some(value.image_state.as_ref().unwrap().twist_labels.clone()),
             field_of_view: Some(value.image_state.as_ref().unwrap().field_of_view),
-            points: Some(
+            lock_field_of_view: Some(axis_data.borrow().lock_field_of_view),
+            shared_intrinsics: Some(axis_data.borrow().shared_intrinsics),
+            principal_point: axis_data
+                .borrow()
+                .principal_point
+                .map(|principal_point| StorePoint {
+                    x: principal_point.x,
+                    y: principal_point.y,
+                }),
+            reference_distance: axis_data
+                .borrow()
+                .reference_distance
+                .as_ref()
+                .map(|reference| StoreReferenceDistance {
+                    a: StorePoint {
+                        x: reference.line.0.x,
+                        y: reference.line.0.y,
+                    },
+                    b: StorePoint {
+                        x: reference.line.1.x,
+                        y: reference.line.1.y,
+                    },
+                    axis: Some(reference.axis),
+                    length: Some(reference.length),
+                }),
+            reference_distance_unit: Some(axis_data.borrow().reference_distance_unit),
+            points: None,
+            shapes: Some(
                 value
                     .image_state
                     .as_ref()
                     .unwrap()
-                    .draw_lines
+                    .drawing
                     .borrow()
+                    .shapes
                     .iter()
-                    .map(|item| StorePoint3d {
-                        x: item.x,
-                        y: item.y,
-                        z: item.z,
-                    })
+                    .map(Into::into)
+                    .collect(),
+            ),
+            observations: Some(
+                value
+                    .image_state
+                    .as_ref()
+                    .unwrap()
+                    .drawing
+                    .borrow()
+                    .observations
+                    .iter()
+                    .map(Into::into)
+                    .collect(),
+            ),
+            measurements: Some(
+                value
+                    .image_state
+                    .as_ref()
+                    .unwrap()
+                    .drawing
+                    .borrow()
+                    .measurements
+                    .iter()
+                    .map(Into::into)
                     .collect(),
             ),
             flip: Some([
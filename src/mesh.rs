@@ -0,0 +1,187 @@
+use std::{collections::HashSet, path::Path};
+
+use anyhow::Result;
+use iced::{
+    Color, Point, Size,
+    advanced::graphics::geometry::{self, Frame},
+    widget::canvas::{self, Fill},
+};
+use nalgebra::{Point3, Vector3};
+
+use crate::{
+    compute::data::ComputeSolution,
+    hidden_lines::{HiddenLines, draw_wireframe},
+    utils::to_canvas,
+};
+
+pub const MESH_COLOR: Color = Color::from_rgba(0.4, 0.8, 0.9, 0.8);
+
+/// Reference mesh of the photographed object, loaded from a Wavefront OBJ file.
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub vertices: Vec<Point3<f32>>,
+    /// Polygons as indices into `vertices`.
+    pub faces: Vec<Vec<usize>>,
+    /// Unique edges of the faces and of the `l` polylines.
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl Mesh {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_obj(&std::fs::read_to_string(path)?)
+    }
+
+    /// Reads vertices, faces and lines, everything else (normals, texture coordinates,
+    /// groups, materials) is ignored.
+    pub fn from_obj(source: &str) -> Result<Self> {
+        let mut mesh = Mesh::default();
+        let mut lines: Vec<Vec<usize>> = Vec::new();
+        for (number, line) in source.lines().enumerate() {
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("v") => {
+                    let coordinates = tokens
+                        .take(3)
+                        .map(str::parse::<f32>)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let [x, y, z] = coordinates[..] else {
+                        return Err(anyhow::anyhow!(
+                            "line {}: vertex needs 3 coordinates",
+                            number + 1
+                        ));
+                    };
+                    mesh.vertices.push(Point3::new(x, y, z));
+                }
+                Some(kind @ ("f" | "l")) => {
+                    let indices = tokens
+                        .map(|token| mesh.index(token))
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("line {}: invalid vertex index", number + 1)
+                        })?;
+                    if kind == "f" {
+                        mesh.faces.push(indices);
+                    } else {
+                        lines.push(indices);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut edges = HashSet::new();
+        mesh.faces.iter().for_each(|face| {
+            face.iter()
+                .zip(face.iter().cycle().skip(1))
+                .for_each(|(a, b)| {
+                    edges.insert((*a.min(b), *a.max(b)));
+                });
+        });
+        lines.iter().for_each(|line| {
+            line.windows(2).for_each(|pair| {
+                edges.insert((pair[0].min(pair[1]), pair[0].max(pair[1])));
+            });
+        });
+        mesh.edges = edges.into_iter().filter(|(a, b)| a != b).collect();
+        mesh.edges.sort_unstable();
+        Ok(mesh)
+    }
+
+    /// Zero based vertex index of a `v`, `v/vt`, `v//vn` or `v/vt/vn` reference,
+    /// negative indices count back from the last vertex read so far.
+    fn index(&self, token: &str) -> Option<usize> {
+        let index: i64 = token.split('/').next()?.parse().ok()?;
+        let index = match index {
+            0 => return None,
+            index if index < 0 => self.vertices.len() as i64 + index,
+            index => index - 1,
+        };
+        (0..self.vertices.len() as i64)
+            .contains(&index)
+            .then_some(index as usize)
+    }
+
+    /// Edges as consecutive point pairs, ready for `calculate_location_position_to_2d_frustum`.
+    pub fn segments(&self) -> Vec<Point3<f32>> {
+        self.edges
+            .iter()
+            .flat_map(|(a, b)| [self.vertices[*a], self.vertices[*b]])
+            .collect()
+    }
+
+    /// Faces as polygons, occluders for hidden line removal.
+    pub fn polygons(&self) -> Vec<Vec<Vector3<f32>>> {
+        self.faces
+            .iter()
+            .map(|face| {
+                face.iter()
+                    .map(|index| self.vertices[*index].coords)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Index of the vertex in front of the camera projected closest to `cursor`, within `max_distance` pixels.
+    pub fn closest_vertex(
+        &self,
+        compute_solution: &ComputeSolution<f32>,
+        size: Size,
+        cursor: Point,
+        max_distance: f32,
+    ) -> Option<usize> {
+        self.vertices
+            .iter()
+            .enumerate()
+            .filter(|(_, vertex)| compute_solution.is_in_front(&vertex.coords))
+            .filter_map(|(index, vertex)| {
+                let point = compute_solution.calculate_location_position_to_2d(&vertex.coords)?;
+                let point = to_canvas(size, &point);
+                let distance = cursor.distance(Point::new(point.x, point.y));
+                (distance < max_distance).then_some((index, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index)
+    }
+}
+
+/// Draws the wireframe clipped to the frustum without the edges hidden by `faces`,
+/// and the vertices when they can be picked.
+pub fn draw_mesh<Renderer: geometry::Renderer>(
+    frame: &mut Frame<Renderer>,
+    compute_solution: &ComputeSolution<f32>,
+    mesh: &Mesh,
+    faces: &[Vec<Vector3<f32>>],
+    hidden_lines: HiddenLines,
+    size: Size,
+    show_vertices: bool,
+) {
+    draw_wireframe(
+        frame,
+        compute_solution,
+        &mesh.segments(),
+        faces,
+        hidden_lines,
+        size,
+        MESH_COLOR,
+    );
+
+    if !show_vertices {
+        return;
+    }
+    let mut builder = canvas::path::Builder::new();
+    mesh.vertices
+        .iter()
+        .filter(|vertex| compute_solution.is_in_front(&vertex.coords))
+        .filter_map(|vertex| compute_solution.calculate_location_position_to_2d(&vertex.coords))
+        .for_each(|point| {
+            let point = to_canvas(size, &point);
+            builder.circle(Point::new(point.x, point.y), 3.0);
+        });
+    frame.fill(
+        &builder.build(),
+        Fill {
+            style: canvas::Style::Solid(MESH_COLOR),
+            ..Fill::default()
+        },
+    );
+}
@@ -0,0 +1,92 @@
+use iced::{
+    Color, Pixels, Point, Size, Vector,
+    advanced::graphics::geometry::{self, Frame},
+    keyboard::{self, key::Named},
+    widget::canvas::{self, Fill, Stroke, Text},
+};
+
+use crate::utils::scale_point_to_canvas;
+
+/// Image pixels moved by an arrow key.
+const STEP: f32 = 1.0;
+/// Image pixels moved by an arrow key with Shift held.
+const FINE_STEP: f32 = 0.1;
+
+/// Offset of an arrow key press relative to the image size, `None` for other keys.
+pub fn nudge_offset(
+    key: &keyboard::Key,
+    modifiers: keyboard::Modifiers,
+    image_size: Size,
+) -> Option<Vector> {
+    if image_size.width < 1.0 || image_size.height < 1.0 {
+        return None;
+    }
+    let step = if modifiers.shift() { FINE_STEP } else { STEP };
+    let (x, y) = match key.as_ref() {
+        keyboard::Key::Named(Named::ArrowLeft) => (-step, 0.0),
+        keyboard::Key::Named(Named::ArrowRight) => (step, 0.0),
+        keyboard::Key::Named(Named::ArrowUp) => (0.0, -step),
+        keyboard::Key::Named(Named::ArrowDown) => (0.0, step),
+        _ => return None,
+    };
+    Some(Vector::new(x / image_size.width, y / image_size.height))
+}
+
+/// Index following `current` among `count` handles for a Tab press, the previous one with Shift.
+pub fn cycle(current: Option<usize>, count: usize, backwards: bool) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    Some(match current {
+        Some(index) if backwards => (index.min(count) + count - 1) % count,
+        Some(index) => (index + 1) % count,
+        None if backwards => count - 1,
+        None => 0,
+    })
+}
+
+/// Marks the handle moved by the arrow keys at the relative `position` and writes its
+/// subpixel coordinate in the image.
+pub fn draw_handle_position<Renderer: geometry::Renderer>(
+    frame: &mut Frame<Renderer>,
+    position: Point,
+    size: Size,
+    image_size: Size,
+) {
+    let color = Color::from_rgba(0.95, 0.95, 0.95, 0.9);
+    let center = scale_point_to_canvas(&position, size);
+    let mut builder = canvas::path::Builder::new();
+    builder.circle(center, 9.0);
+    builder.move_to(Point::new(center.x - 3.0, center.y));
+    builder.line_to(Point::new(center.x + 3.0, center.y));
+    builder.move_to(Point::new(center.x, center.y - 3.0));
+    builder.line_to(Point::new(center.x, center.y + 3.0));
+    frame.stroke(
+        &builder.build(),
+        Stroke {
+            style: canvas::Style::Solid(color),
+            width: 1.0,
+            ..Stroke::default()
+        },
+    );
+    let content = format!(
+        "{:.1}, {:.1} px",
+        position.x * image_size.width,
+        position.y * image_size.height
+    );
+    frame.fill_rectangle(
+        Point::new(center.x + 2.0, center.y - 19.0),
+        Size::new(content.chars().count() as f32 * 6.0 + 4.0, 15.0),
+        Fill {
+            style: canvas::Style::Solid(Color::from_rgba(0.3, 0.3, 0.3, 0.9)),
+            ..Fill::default()
+        },
+    );
+    frame.fill_text(Text {
+        content,
+        position: Point::new(center.x + 4.0, center.y - 17.0),
+        color,
+        size: Pixels(10.0),
+        ..Default::default()
+    });
+}
@@ -0,0 +1,139 @@
+use std::fmt::Write;
+
+use iced::Size;
+use nalgebra::{Point3, Vector3};
+
+use crate::{Shape, compute::data::ComputeSolution, utils::to_canvas};
+
+/// Camera and image size the texture coordinates are projected from,
+/// and the material library referencing the photo.
+pub struct ObjTexture<'a> {
+    pub compute_solution: &'a ComputeSolution<f32>,
+    pub image_size: Size<f32>,
+    pub material_file_name: &'a str,
+}
+
+/// Wavefront OBJ writer keeping the global 1-based vertex index,
+/// every vertex gets a texture coordinate when textured.
+struct ObjWriter<'a> {
+    out: String,
+    vertices: usize,
+    texture: Option<&'a ObjTexture<'a>>,
+}
+
+impl ObjWriter<'_> {
+    fn object(&mut self, name: &str) {
+        let _ = writeln!(self.out, "o {name}");
+    }
+
+    fn vertex(&mut self, point: &Vector3<f32>) -> usize {
+        let _ = writeln!(self.out, "v {} {} {}", point.x, point.y, point.z);
+        if let Some(texture) = self.texture {
+            let (u, v) = texture
+                .compute_solution
+                .calculate_location_position_to_2d(point)
+                .map(|point| {
+                    let point = to_canvas(texture.image_size, &point);
+                    (
+                        point.x / texture.image_size.width,
+                        1.0 - point.y / texture.image_size.height,
+                    )
+                })
+                .unwrap_or_default();
+            let _ = writeln!(self.out, "vt {u} {v}");
+        }
+        self.vertices += 1;
+        self.vertices
+    }
+
+    fn element(&mut self, kind: &str, indices: &[usize]) {
+        let _ = write!(self.out, "{kind}");
+        indices.iter().for_each(|index| {
+            let _ = match (kind, self.texture) {
+                ("f", Some(_)) => write!(self.out, " {index}/{index}"),
+                _ => write!(self.out, " {index}"),
+            };
+        });
+        let _ = writeln!(self.out);
+    }
+}
+
+/// Exports the drawn shapes, the twist points and the reference cube edges.
+/// Closed shapes become faces, extruded shapes get a quad per wall, open ones stay polylines.
+pub fn to_obj(
+    shapes: &[Shape],
+    twist_points: &[Point3<f32>],
+    reference_cube: &[Point3<f32>],
+    texture: Option<&ObjTexture>,
+) -> String {
+    let mut writer = ObjWriter {
+        out: String::from("# exported by perspective\n"),
+        vertices: 0,
+        texture,
+    };
+    if let Some(texture) = texture {
+        let _ = writeln!(writer.out, "mtllib {}", texture.material_file_name);
+        let _ = writeln!(writer.out, "usemtl photo");
+    }
+
+    shapes.iter().enumerate().for_each(|(index, shape)| {
+        if shape.points.len() < 2 {
+            return;
+        }
+        writer.object(&format!("shape_{index}"));
+        let base: Vec<usize> = shape
+            .points
+            .iter()
+            .map(|point| writer.vertex(point))
+            .collect();
+        let closed = shape.closed && base.len() > 2;
+
+        match shape.extrusion {
+            Some(extrusion) => {
+                let top: Vec<usize> = shape
+                    .points
+                    .iter()
+                    .map(|point| writer.vertex(&(point + extrusion)))
+                    .collect();
+                let segments = if closed { base.len() } else { base.len() - 1 };
+                (0..segments).for_each(|a| {
+                    let b = (a + 1) % base.len();
+                    writer.element("f", &[base[a], base[b], top[b], top[a]]);
+                });
+                if closed {
+                    writer.element("f", &base);
+                    writer.element("f", &top);
+                }
+            }
+            None if closed => writer.element("f", &base),
+            None => writer.element("l", &base),
+        }
+    });
+
+    if !twist_points.is_empty() {
+        writer.object("twist_points");
+        let points: Vec<usize> = twist_points
+            .iter()
+            .map(|point| writer.vertex(&point.coords))
+            .collect();
+        writer.element("p", &points);
+    }
+
+    if reference_cube.len() > 1 {
+        writer.object("reference_cube");
+        reference_cube.chunks(2).for_each(|edge| {
+            if let [a, b] = edge {
+                let a = writer.vertex(&a.coords);
+                let b = writer.vertex(&b.coords);
+                writer.element("l", &[a, b]);
+            }
+        });
+    }
+
+    writer.out
+}
+
+/// Material library using the photo as diffuse texture.
+pub fn to_mtl(image_file_name: &str) -> String {
+    format!("newmtl photo\nKd 1 1 1\nillum 0\nmap_Kd {image_file_name}\n")
+}
@@ -0,0 +1,227 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::Result;
+use iced::Size;
+use nalgebra::{Matrix4, Point2, Point3, Vector2, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    adjust::DisplayAdjustments,
+    compute::{Lines, StorePoint, StorePoint3d, data::ComputeSolution},
+    hidden_lines::HiddenLines,
+    reference::{ReferenceBox, ReferenceGeometry, ReferencePlane},
+    safe_write::write_atomic,
+};
+
+/// Extension of project files.
+pub const PROJECT_EXTENSION: &str = "perspective";
+
+/// Every image of a session with its correspondences and solution, kept in one `.perspective` file.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Project {
+    pub images: Vec<ProjectImage>,
+    /// Triangulated world points shared by the images.
+    pub world_points: Vec<StoreWorldPoint>,
+    pub export: ExportSettings,
+    /// Index in `images` of the image shown when the project is opened.
+    pub selected_image: Option<usize>,
+    /// Adjustments of the photos shown in the canvas.
+    pub display: Option<DisplayAdjustments>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProjectImage {
+    /// Path relative to the folder of the project file.
+    pub path: String,
+    /// Same content as the `.points` file of the image.
+    pub data: Option<Lines>,
+    pub solution: Option<StoreSolution>,
+}
+
+/// Solved camera of an image.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StoreSolution {
+    /// Column major view transform.
+    pub view_transform: [f32; 16],
+    pub ortho_center: StorePoint,
+    /// Horizontal field of view in radians.
+    pub field_of_view: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Twist correspondences of the solution, empty when solved from vanishing points.
+    pub control_points: Vec<(StorePoint3d, StorePoint)>,
+}
+
+impl StoreSolution {
+    pub fn new(
+        compute_solution: &ComputeSolution<f32>,
+        image_size: Size<f32>,
+        control_points: &[(Point3<f32>, Point2<f32>)],
+    ) -> Self {
+        let ortho_center = compute_solution.ortho_center();
+        Self {
+            view_transform: compute_solution
+                .view_transform()
+                .as_slice()
+                .try_into()
+                .unwrap(),
+            ortho_center: StorePoint {
+                x: ortho_center.x,
+                y: ortho_center.y,
+            },
+            field_of_view: compute_solution.field_of_view(),
+            width: image_size.width,
+            height: image_size.height,
+            control_points: control_points
+                .iter()
+                .map(|(world, image)| {
+                    (
+                        StorePoint3d {
+                            x: world.x,
+                            y: world.y,
+                            z: world.z,
+                        },
+                        StorePoint {
+                            x: image.x,
+                            y: image.y,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn compute_solution(&self) -> ComputeSolution<f32> {
+        ComputeSolution::new(
+            Matrix4::from_column_slice(&self.view_transform),
+            Vector2::new(self.ortho_center.x, self.ortho_center.y),
+            self.field_of_view,
+        )
+    }
+
+    pub fn image_size(&self) -> Size<f32> {
+        Size::new(self.width, self.height)
+    }
+
+    pub fn control_points(&self) -> Vec<(Point3<f32>, Point2<f32>)> {
+        self.control_points
+            .iter()
+            .map(|(world, image)| {
+                (
+                    Point3::new(world.x, world.y, world.z),
+                    Point2::new(image.x, image.y),
+                )
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StoreWorldPoint {
+    pub id: usize,
+    pub position: StorePoint3d,
+    /// Reprojection error in pixels of each image observing the point.
+    pub residuals: Vec<f32>,
+}
+
+/// Settings of the exports that are not stored per image.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ExportSettings {
+    /// Resolution of the extracted face textures.
+    pub texture_pixels_per_unit: Option<f32>,
+    /// Length of the axes drawn in the overlay, hidden when `None`.
+    pub axis_length: Option<f32>,
+    pub hidden_lines: Option<HiddenLines>,
+    /// Grid and box drawn over the images and the overlay.
+    pub reference: Option<StoreReference>,
+}
+
+/// Grid and box of a [`ReferenceGeometry`], the axes are kept in [`ExportSettings`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StoreReference {
+    pub grid_plane: ReferencePlane,
+    pub grid_spacing: f32,
+    pub grid_extent: f32,
+    pub box_kind: ReferenceBox,
+    pub box_size: StorePoint3d,
+}
+
+impl StoreReference {
+    pub fn new(reference: &ReferenceGeometry) -> Self {
+        Self {
+            grid_plane: reference.grid_plane,
+            grid_spacing: reference.grid_spacing,
+            grid_extent: reference.grid_extent,
+            box_kind: reference.box_kind,
+            box_size: StorePoint3d {
+                x: reference.box_size.x,
+                y: reference.box_size.y,
+                z: reference.box_size.z,
+            },
+        }
+    }
+
+    pub fn reference_geometry(&self, axis_length: Option<f32>) -> ReferenceGeometry {
+        ReferenceGeometry {
+            grid_plane: self.grid_plane,
+            grid_spacing: self.grid_spacing,
+            grid_extent: self.grid_extent,
+            box_kind: self.box_kind,
+            box_size: Vector3::new(self.box_size.x, self.box_size.y, self.box_size.z),
+            axis_length,
+        }
+    }
+}
+
+pub fn read_project(path: &Path) -> Result<Project> {
+    let mut file = File::open(path)?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn write_project(path: &Path, project: &Project) -> Result<()> {
+    write_atomic(path, &serde_json::to_vec_pretty(project)?)
+}
+
+/// `image` relative to the folder of the `project` file, absolute when they share no common folder.
+pub fn relative_image_path(project: &Path, image: &str) -> String {
+    let (Ok(project), Ok(image)) = (std::path::absolute(project), std::path::absolute(image))
+    else {
+        return image.to_string();
+    };
+    let folder: Vec<Component> = project
+        .parent()
+        .map(|parent| parent.components().collect())
+        .unwrap_or_default();
+    let components: Vec<Component> = image.components().collect();
+    let common = folder
+        .iter()
+        .zip(&components)
+        .take_while(|(a, b)| a == b)
+        .count();
+    // only the root in common, e.g. another drive
+    if common <= 1 {
+        return image.to_string_lossy().to_string();
+    }
+    let mut relative = PathBuf::new();
+    (common..folder.len()).for_each(|_| relative.push(".."));
+    components[common..]
+        .iter()
+        .for_each(|component| relative.push(component));
+    relative.to_string_lossy().to_string()
+}
+
+/// `path` of a project image resolved against the folder of the `project` file.
+pub fn resolve_image_path(project: &Path, path: &str) -> String {
+    project
+        .parent()
+        .map(|parent| parent.join(path))
+        .unwrap_or_else(|| PathBuf::from(path))
+        .to_string_lossy()
+        .to_string()
+}
@@ -0,0 +1,332 @@
+use std::fmt::Display;
+
+use iced::{
+    Color, Pixels, Point, Size,
+    advanced::graphics::geometry::{self, Frame},
+    widget::canvas::Text,
+};
+use nalgebra::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::{EditAxis, compute::data::ComputeSolution, horizon::AXIS_COLORS, utils::to_canvas};
+
+pub const REFERENCE_COLOR: Color = Color::from_rgba(0.9, 0.7, 0.7, 1.0);
+/// Upper bound of grid lines per direction, keeps a tiny spacing from flooding the canvas.
+const MAX_GRID_LINES: usize = 500;
+/// Length of an arrow head relative to the arrow.
+const ARROW_HEAD: f32 = 0.1;
+
+/// Plane the reference grid is laid on.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReferencePlane {
+    #[default]
+    Hidden,
+    XY,
+    XZ,
+    YZ,
+}
+
+impl ReferencePlane {
+    pub const ALL: [ReferencePlane; 4] = [
+        ReferencePlane::Hidden,
+        ReferencePlane::XY,
+        ReferencePlane::XZ,
+        ReferencePlane::YZ,
+    ];
+
+    /// The two in plane directions, `None` when hidden.
+    fn axes(&self) -> Option<(Vector3<f32>, Vector3<f32>)> {
+        match self {
+            ReferencePlane::Hidden => None,
+            ReferencePlane::XY => Some((Vector3::x(), Vector3::y())),
+            ReferencePlane::XZ => Some((Vector3::x(), Vector3::z())),
+            ReferencePlane::YZ => Some((Vector3::y(), Vector3::z())),
+        }
+    }
+}
+
+impl Display for ReferencePlane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferencePlane::Hidden => write!(f, "No grid"),
+            ReferencePlane::XY => write!(f, "XY"),
+            ReferencePlane::XZ => write!(f, "XZ"),
+            ReferencePlane::YZ => write!(f, "YZ"),
+        }
+    }
+}
+
+/// How the reference box is sized.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReferenceBox {
+    Hidden,
+    /// Bounding box of the twist points with unit lines on its base.
+    #[default]
+    TwistPoints,
+    /// Box of `ReferenceGeometry::box_size` with a corner in the origin.
+    Dimensions,
+}
+
+impl ReferenceBox {
+    pub const ALL: [ReferenceBox; 3] = [
+        ReferenceBox::Hidden,
+        ReferenceBox::TwistPoints,
+        ReferenceBox::Dimensions,
+    ];
+}
+
+impl Display for ReferenceBox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceBox::Hidden => write!(f, "No box"),
+            ReferenceBox::TwistPoints => write!(f, "Fit twist points"),
+            ReferenceBox::Dimensions => write!(f, "Dimensions"),
+        }
+    }
+}
+
+/// Label placed at the tip of an axis arrow.
+#[derive(Debug, Clone, Copy)]
+pub struct ReferenceLabel {
+    pub position: Point3<f32>,
+    pub axis: EditAxis,
+}
+
+/// World space overlay drawn over the image to check the calibration.
+#[derive(Debug, Clone)]
+pub struct ReferenceGeometry {
+    pub grid_plane: ReferencePlane,
+    /// Distance between grid lines.
+    pub grid_spacing: f32,
+    /// Half size of the grid around the origin.
+    pub grid_extent: f32,
+    pub box_kind: ReferenceBox,
+    pub box_size: Vector3<f32>,
+    /// Length of the axis arrows, hidden when `None`.
+    pub axis_length: Option<f32>,
+}
+
+impl Default for ReferenceGeometry {
+    fn default() -> Self {
+        Self {
+            grid_plane: ReferencePlane::Hidden,
+            grid_spacing: 1.0,
+            grid_extent: 5.0,
+            box_kind: ReferenceBox::TwistPoints,
+            box_size: Vector3::repeat(1.0),
+            axis_length: None,
+        }
+    }
+}
+
+impl ReferenceGeometry {
+    /// Segments as consecutive point pairs, ready for `calculate_location_position_to_2d_frustum`.
+    pub fn segments(&self, twist_points: &[Point3<f32>]) -> Vec<Point3<f32>> {
+        let mut segments = Vec::new();
+        if let Some((u, v)) = self.grid_plane.axes()
+            && self.grid_spacing > 0.0
+            && self.grid_extent > 0.0
+        {
+            let lines =
+                ((self.grid_extent / self.grid_spacing).floor() as usize).min(MAX_GRID_LINES);
+            for i in 0..=2 * lines {
+                let offset = (i as f32 - lines as f32) * self.grid_spacing;
+                segments.push(Point3::from(u * offset - v * self.grid_extent));
+                segments.push(Point3::from(u * offset + v * self.grid_extent));
+                segments.push(Point3::from(v * offset - u * self.grid_extent));
+                segments.push(Point3::from(v * offset + u * self.grid_extent));
+            }
+        }
+
+        match self.box_kind {
+            ReferenceBox::Hidden => {}
+            ReferenceBox::TwistPoints => {
+                if let Some((min, size)) = twist_points_bounds(twist_points) {
+                    segments.extend(box_edges(&min, &size));
+                    for i in 0..=size.y as usize {
+                        segments.push(min + Vector3::new(0.0, i as f32, 0.0));
+                        segments.push(min + Vector3::new(size.x, i as f32, 0.0));
+                    }
+                    for i in 0..=size.x as usize {
+                        segments.push(min + Vector3::new(i as f32, 0.0, 0.0));
+                        segments.push(min + Vector3::new(i as f32, size.y, 0.0));
+                    }
+                }
+            }
+            ReferenceBox::Dimensions => {
+                segments.extend(box_edges(&Point3::origin(), &self.box_size));
+            }
+        }
+
+        if let Some(length) = self.axis_length {
+            EditAxis::ALL.iter().for_each(|axis| {
+                let direction: Vector3<f32> = axis.direction().unwrap();
+                let tip = Point3::from(direction * length);
+                segments.push(Point3::origin());
+                segments.push(tip);
+                // a head in both planes containing the axis so it reads from any side
+                let back = tip - direction * length * ARROW_HEAD;
+                EditAxis::ALL
+                    .iter()
+                    .filter(|side| *side != axis)
+                    .for_each(|side| {
+                        let side: Vector3<f32> = side.direction().unwrap();
+                        let side = side * length * ARROW_HEAD * 0.5;
+                        segments.extend([tip, back + side, tip, back - side]);
+                    });
+            });
+        }
+        segments
+    }
+
+    /// Faces of the box with outward winding, they hide the edges behind the box.
+    pub fn faces(&self, twist_points: &[Point3<f32>]) -> Vec<Vec<Vector3<f32>>> {
+        match self.box_kind {
+            ReferenceBox::Hidden => Vec::new(),
+            ReferenceBox::TwistPoints => twist_points_bounds(twist_points)
+                .map(|(min, size)| box_faces(&min, &size))
+                .unwrap_or_default(),
+            ReferenceBox::Dimensions => box_faces(&Point3::origin(), &self.box_size),
+        }
+    }
+
+    /// Labels at the tips of the axis arrows.
+    pub fn labels(&self) -> Vec<ReferenceLabel> {
+        let Some(length) = self.axis_length else {
+            return Vec::new();
+        };
+        EditAxis::ALL
+            .iter()
+            .map(|axis| ReferenceLabel {
+                position: Point3::from(axis.direction::<f32>().unwrap() * length * 1.05),
+                axis: *axis,
+            })
+            .collect()
+    }
+}
+
+fn box_point(corner: &Point3<f32>, size: &Vector3<f32>, x: f32, y: f32, z: f32) -> Point3<f32> {
+    corner + Vector3::new(x * size.x, y * size.y, z * size.z)
+}
+
+/// The six faces of the box spanned from `corner` by `size`, counter-clockwise seen from outside.
+fn box_faces(corner: &Point3<f32>, size: &Vector3<f32>) -> Vec<Vec<Vector3<f32>>> {
+    [
+        [
+            (0.0, 0.0, 0.0),
+            (0.0, 1.0, 0.0),
+            (1.0, 1.0, 0.0),
+            (1.0, 0.0, 0.0),
+        ],
+        [
+            (0.0, 0.0, 1.0),
+            (1.0, 0.0, 1.0),
+            (1.0, 1.0, 1.0),
+            (0.0, 1.0, 1.0),
+        ],
+        [
+            (0.0, 0.0, 0.0),
+            (1.0, 0.0, 0.0),
+            (1.0, 0.0, 1.0),
+            (0.0, 0.0, 1.0),
+        ],
+        [
+            (0.0, 1.0, 0.0),
+            (0.0, 1.0, 1.0),
+            (1.0, 1.0, 1.0),
+            (1.0, 1.0, 0.0),
+        ],
+        [
+            (0.0, 0.0, 0.0),
+            (0.0, 0.0, 1.0),
+            (0.0, 1.0, 1.0),
+            (0.0, 1.0, 0.0),
+        ],
+        [
+            (1.0, 0.0, 0.0),
+            (1.0, 1.0, 0.0),
+            (1.0, 1.0, 1.0),
+            (1.0, 0.0, 1.0),
+        ],
+    ]
+    .iter()
+    .map(|face| {
+        face.iter()
+            .map(|&(x, y, z)| box_point(corner, size, x, y, z).coords)
+            .collect()
+    })
+    .collect()
+}
+
+/// The twelve edges of the box spanned from `corner` by `size`.
+fn box_edges(corner: &Point3<f32>, size: &Vector3<f32>) -> Vec<Point3<f32>> {
+    let point = |x: f32, y: f32, z: f32| box_point(corner, size, x, y, z);
+    let mut edges = Vec::with_capacity(24);
+    for z in [0.0, 1.0] {
+        edges.extend([
+            point(0.0, 0.0, z),
+            point(1.0, 0.0, z),
+            point(1.0, 0.0, z),
+            point(1.0, 1.0, z),
+            point(1.0, 1.0, z),
+            point(0.0, 1.0, z),
+            point(0.0, 1.0, z),
+            point(0.0, 0.0, z),
+        ]);
+    }
+    for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+        edges.extend([point(x, y, 0.0), point(x, y, 1.0)]);
+    }
+    edges
+}
+
+/// Corner and size of the twist points bounding box, flat sides grow to a unit.
+fn twist_points_bounds(twist_points: &[Point3<f32>]) -> Option<(Point3<f32>, Vector3<f32>)> {
+    let first = twist_points.first()?;
+    let (min, max) = twist_points
+        .iter()
+        .fold((first.coords, first.coords), |(min, max), item| {
+            (min.inf(&item.coords), max.sup(&item.coords))
+        });
+    let size = (max - min).map(|size| if size == 0.0 { 1.0 } else { size });
+    Some((Point3::from(min), size))
+}
+
+/// Draws the axis labels that are in front of the camera and on the canvas.
+pub fn draw_reference_labels<Renderer: geometry::Renderer>(
+    frame: &mut Frame<Renderer>,
+    compute_solution: &ComputeSolution<f32>,
+    labels: &[ReferenceLabel],
+    size: Size,
+) {
+    labels.iter().for_each(|label| {
+        if !compute_solution.is_in_front(&label.position.coords) {
+            return;
+        }
+        let Some(position) =
+            compute_solution.calculate_location_position_to_2d(&label.position.coords)
+        else {
+            return;
+        };
+        let position = to_canvas(size, &position);
+        if position.x < 0.0
+            || position.y < 0.0
+            || position.x > size.width
+            || position.y > size.height
+        {
+            return;
+        }
+        let color = match label.axis {
+            EditAxis::EditX => AXIS_COLORS[0],
+            EditAxis::EditY => AXIS_COLORS[1],
+            _ => AXIS_COLORS[2],
+        };
+        frame.fill_text(Text {
+            content: label.axis.to_string(),
+            position: Point::new(position.x - 4.0, position.y - 7.0),
+            color,
+            size: Pixels(13.0),
+            ..Default::default()
+        });
+    });
+}
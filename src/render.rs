@@ -0,0 +1,277 @@
+use iced::{Color, Point, Size};
+use image::{Rgba, RgbaImage};
+use nalgebra::{Point2, Point3, Vector2, Vector3};
+
+use crate::{
+    EditAxis,
+    compute::data::ComputeSolution,
+    hidden_lines::{HiddenLines, split_hidden_edges},
+    horizon::AXIS_COLORS,
+    mesh::{MESH_COLOR, Mesh},
+    reference::REFERENCE_COLOR,
+    utils::to_canvas,
+};
+
+const MARKER_COLOR: Color = Color::from_rgba(0.8, 0.8, 0.2, 1.0);
+const RESIDUAL_COLOR: Color = Color::from_rgba(1.0, 0.3, 0.3, 1.0);
+/// Stroke widths and marker sizes are given for a 1000 pixel image and scaled with it.
+const REFERENCE_SIZE: f32 = 1000.0;
+const DASH_LENGTH: f32 = 6.0;
+
+/// Everything burned into the photo, the 3D parts are projected through `compute_solution`
+/// and clipped to its frustum, the 2D parts are relative to the image size.
+pub struct Overlay<'a> {
+    pub compute_solution: &'a ComputeSolution<f32>,
+    /// Reference geometry as consecutive point pairs.
+    pub reference: &'a [Point3<f32>],
+    pub mesh: Option<&'a Mesh>,
+    /// Faces hiding the reference and mesh edges.
+    pub faces: &'a [Vec<Vector3<f32>>],
+    pub hidden_lines: HiddenLines,
+    /// Length of the X, Y and Z axes drawn from the origin, hidden when `None`.
+    pub axis_length: Option<f32>,
+    /// Twist correspondences, drawn as a marker on the image point and a residual line
+    /// to the reprojected world point.
+    pub twist_points: &'a [Point3<f32>],
+    pub twist_points_2d: &'a [Point2<f32>],
+    /// Vanishing lines, two per axis.
+    pub axis_lines: &'a [(Point, Point)],
+}
+
+/// Draws the overlay onto `image` with a CPU rasterizer.
+pub fn render_overlay(image: &mut RgbaImage, overlay: &Overlay) {
+    let size = Size::new(image.width() as f32, image.height() as f32);
+    let mut canvas = Canvas {
+        image,
+        scale: (size.width.max(size.height) / REFERENCE_SIZE).max(1.0),
+    };
+    let compute_solution = overlay.compute_solution;
+
+    canvas.wireframe(
+        compute_solution,
+        overlay.reference,
+        overlay.faces,
+        overlay.hidden_lines,
+        REFERENCE_COLOR,
+    );
+    if let Some(mesh) = overlay.mesh {
+        canvas.wireframe(
+            compute_solution,
+            &mesh.segments(),
+            overlay.faces,
+            overlay.hidden_lines,
+            MESH_COLOR,
+        );
+    }
+
+    if let Some(length) = overlay.axis_length {
+        EditAxis::ALL
+            .iter()
+            .zip(AXIS_COLORS)
+            .for_each(|(axis, color)| {
+                let tip = Point3::from(axis.direction::<f32>().unwrap() * length);
+                canvas.segments(
+                    compute_solution,
+                    &[Point3::origin(), tip],
+                    color,
+                    2.0,
+                    false,
+                );
+            });
+    }
+
+    overlay
+        .axis_lines
+        .iter()
+        .enumerate()
+        .for_each(|(index, (a, b))| {
+            let color = AXIS_COLORS[(index / 2).min(2)];
+            canvas.line(
+                Vector2::new(a.x * size.width, a.y * size.height),
+                Vector2::new(b.x * size.width, b.y * size.height),
+                color,
+                1.5,
+            );
+        });
+
+    overlay
+        .twist_points
+        .iter()
+        .zip(overlay.twist_points_2d)
+        .for_each(|(point, point_2d)| {
+            let marker = Vector2::new(point_2d.x * size.width, point_2d.y * size.height);
+            if compute_solution.is_in_front(&point.coords)
+                && let Some(projected) =
+                    compute_solution.calculate_location_position_to_2d(&point.coords)
+            {
+                let projected = to_canvas(size, &projected);
+                canvas.line(marker, projected, RESIDUAL_COLOR, 1.5);
+                canvas.cross(projected, 4.0, RESIDUAL_COLOR);
+            }
+            canvas.ring(marker, 6.0, MARKER_COLOR);
+        });
+}
+
+struct Canvas<'a> {
+    image: &'a mut RgbaImage,
+    /// Pixels per reference pixel.
+    scale: f32,
+}
+
+impl Canvas<'_> {
+    fn wireframe(
+        &mut self,
+        compute_solution: &ComputeSolution<f32>,
+        segments: &[Point3<f32>],
+        faces: &[Vec<Vector3<f32>>],
+        hidden_lines: HiddenLines,
+        color: Color,
+    ) {
+        let (visible, hidden) =
+            split_hidden_edges(compute_solution, segments, faces, hidden_lines.mode);
+        self.segments(compute_solution, &visible, color, 1.0, false);
+        if hidden_lines.dash_hidden {
+            let color = Color {
+                a: color.a * 0.5,
+                ..color
+            };
+            self.segments(compute_solution, &hidden, color, 1.0, true);
+        }
+    }
+
+    /// Strokes world space point pairs clipped to the frustum.
+    fn segments(
+        &mut self,
+        compute_solution: &ComputeSolution<f32>,
+        segments: &[Point3<f32>],
+        color: Color,
+        width: f32,
+        dashed: bool,
+    ) {
+        let size = Size::new(self.image.width() as f32, self.image.height() as f32);
+        segments.chunks(2).for_each(|points| {
+            compute_solution
+                .calculate_location_position_to_2d_frustum(points)
+                .iter()
+                .for_each(|(start, end)| {
+                    let start = to_canvas(size, &start.coords.xy());
+                    let end = to_canvas(size, &end.coords.xy());
+                    if dashed {
+                        self.dashed_line(start, end, color, width);
+                    } else {
+                        self.line(start, end, color, width);
+                    }
+                });
+        });
+    }
+
+    fn dashed_line(&mut self, a: Vector2<f32>, b: Vector2<f32>, color: Color, width: f32) {
+        let length = (b - a).norm();
+        let dash = DASH_LENGTH * self.scale;
+        let mut start = 0.0;
+        while start < length {
+            let end = (start + dash).min(length);
+            self.line(
+                a + (b - a) * (start / length),
+                a + (b - a) * (end / length),
+                color,
+                width,
+            );
+            start += 2.0 * dash;
+        }
+    }
+
+    /// Anti-aliased line, coverage from the distance of each pixel center to the segment.
+    fn line(&mut self, a: Vector2<f32>, b: Vector2<f32>, color: Color, width: f32) {
+        let half_width = width * self.scale / 2.0;
+        let (width_px, height_px) = (self.image.width() as f32, self.image.height() as f32);
+        let delta = b - a;
+        // walk along the major axis and cover the span across the line at each step
+        let steep = delta.y.abs() > delta.x.abs();
+        let (major, minor) = if steep { (1, 0) } else { (0, 1) };
+        let (start, end) = if a[major] <= b[major] { (a, b) } else { (b, a) };
+        let limit = if steep { height_px } else { width_px };
+        let minor_limit = if steep { width_px } else { height_px };
+        let from = (start[major] - half_width).floor().max(0.0) as i64;
+        let to = (end[major] + half_width).ceil().min(limit - 1.0) as i64;
+        let slope = if delta[major].abs() > f32::EPSILON {
+            delta[minor] / delta[major]
+        } else {
+            0.0
+        };
+        // the span across the line widens with the slope
+        let reach = half_width * (1.0 + slope * slope).sqrt() + 1.0;
+        for step in from..=to {
+            let along = (step as f32 + 0.5 - start[major]).clamp(0.0, end[major] - start[major]);
+            let center = start[minor] + along * slope;
+            let low = (center - reach).floor().max(0.0) as i64;
+            let high = (center + reach).ceil().min(minor_limit - 1.0) as i64;
+            for across in low..=high {
+                let mut pixel = Vector2::zeros();
+                pixel[major] = step as f32 + 0.5;
+                pixel[minor] = across as f32 + 0.5;
+                let coverage = half_width + 0.5 - distance_to_segment(&pixel, &a, &b);
+                self.blend(pixel.x as u32, pixel.y as u32, color, coverage);
+            }
+        }
+    }
+
+    fn ring(&mut self, center: Vector2<f32>, radius: f32, color: Color) {
+        let radius = radius * self.scale;
+        let half_width = self.scale;
+        let reach = radius + half_width + 1.0;
+        for y in (center.y - reach).floor() as i64..=(center.y + reach).ceil() as i64 {
+            for x in (center.x - reach).floor() as i64..=(center.x + reach).ceil() as i64 {
+                if x < 0 || y < 0 {
+                    continue;
+                }
+                let distance =
+                    (Vector2::new(x as f32 + 0.5, y as f32 + 0.5) - center).norm() - radius;
+                self.blend(x as u32, y as u32, color, half_width + 0.5 - distance.abs());
+            }
+        }
+    }
+
+    fn cross(&mut self, center: Vector2<f32>, radius: f32, color: Color) {
+        let radius = radius * self.scale;
+        self.line(
+            center - Vector2::new(radius, radius),
+            center + Vector2::new(radius, radius),
+            color,
+            1.5,
+        );
+        self.line(
+            center - Vector2::new(radius, -radius),
+            center + Vector2::new(radius, -radius),
+            color,
+            1.5,
+        );
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: Color, coverage: f32) {
+        if coverage <= 0.0 || x >= self.image.width() || y >= self.image.height() {
+            return;
+        }
+        let alpha = color.a * coverage.min(1.0);
+        let [r, g, b, _] = Color { a: 1.0, ..color }.into_rgba8();
+        let pixel = self.image.get_pixel_mut(x, y);
+        let Rgba([pr, pg, pb, pa]) = *pixel;
+        let mix = |over: u8, under: u8| (over as f32 * alpha + under as f32 * (1.0 - alpha)) as u8;
+        *pixel = Rgba([
+            mix(r, pr),
+            mix(g, pg),
+            mix(b, pb),
+            pa.max((alpha * 255.0) as u8),
+        ]);
+    }
+}
+
+fn distance_to_segment(point: &Vector2<f32>, a: &Vector2<f32>, b: &Vector2<f32>) -> f32 {
+    let ab = b - a;
+    let length = ab.norm_squared();
+    if length <= f32::EPSILON {
+        return (point - a).norm();
+    }
+    let t = ((point - a).dot(&ab) / length).clamp(0.0, 1.0);
+    (point - (a + ab * t)).norm()
+}
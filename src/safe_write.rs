@@ -0,0 +1,159 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use tracing::warn;
+
+/// Rolling backups kept next to a file, `<file>.bak1` is the file before the last write.
+const BACKUPS: usize = 3;
+/// Minimum age of `<file>.bak2` before the older backups are rotated, autosaves in between
+/// only replace `<file>.bak1`.
+const BACKUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Writes `content` to a temporary file next to `path` and renames it over `path`,
+/// a crash while writing leaves the previous file intact.
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("{} is not a file", path.display()))?
+        .to_string_lossy();
+    let temporary = path.with_file_name(format!(".{file_name}.tmp"));
+    let result = (|| -> Result<()> {
+        let mut file = File::create(&temporary)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        if path.exists() {
+            if let Err(error) = rotate_backups(path) {
+                warn!(
+                    "could not rotate the backups of {}: {error}",
+                    path.display()
+                );
+            }
+            // a copy could keep the modification time of the file
+            fs::write(backup_path(path, 1), fs::read(path)?)?;
+        }
+        fs::rename(&temporary, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result
+}
+
+fn backup_path(path: &Path, index: usize) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".bak{index}"));
+    PathBuf::from(backup)
+}
+
+/// Shifts `<file>.bak1` and the older backups one generation back, unless `<file>.bak2` is
+/// recent.
+fn rotate_backups(path: &Path) -> Result<()> {
+    let newest = backup_path(path, 1);
+    if !newest.exists() {
+        return Ok(());
+    }
+    let recent = fs::metadata(backup_path(path, 2))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < BACKUP_INTERVAL);
+    if recent {
+        return Ok(());
+    }
+    for index in (1..BACKUPS).rev() {
+        let older = backup_path(path, index);
+        if older.exists() {
+            fs::rename(&older, backup_path(path, index + 1))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Empty directory of its own for each test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "perspective-safe-write-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Pretends `path` was written longer ago than the backup interval.
+    fn age(path: &Path) {
+        let modified = SystemTime::now() - BACKUP_INTERVAL * 2;
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn write_replaces_the_file_and_keeps_the_previous_one() {
+        let dir = test_dir("write");
+        let path = dir.join("points.json");
+        write_atomic(&path, b"first").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+        assert!(!backup_path(&path, 1).exists());
+
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        assert_eq!(fs::read(backup_path(&path, 1)).unwrap(), b"first");
+        assert!(!dir.join(".points.json.tmp").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recent_backups_are_not_rotated() {
+        let dir = test_dir("recent");
+        let path = dir.join("points.json");
+        for content in ["1", "2", "3"] {
+            write_atomic(&path, content.as_bytes()).unwrap();
+        }
+        // the first autosave starts the second generation, the next ones only replace the newest
+        assert_eq!(fs::read(backup_path(&path, 1)).unwrap(), b"2");
+        assert_eq!(fs::read(backup_path(&path, 2)).unwrap(), b"1");
+        write_atomic(&path, b"4").unwrap();
+        assert_eq!(fs::read(backup_path(&path, 1)).unwrap(), b"3");
+        assert_eq!(fs::read(backup_path(&path, 2)).unwrap(), b"1");
+        assert!(!backup_path(&path, 3).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn old_backups_are_rotated() {
+        let dir = test_dir("rotate");
+        let path = dir.join("points.json");
+        for content in ["1", "2", "3"] {
+            write_atomic(&path, content.as_bytes()).unwrap();
+        }
+        age(&backup_path(&path, 2));
+        write_atomic(&path, b"4").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"4");
+        assert_eq!(fs::read(backup_path(&path, 1)).unwrap(), b"3");
+        assert_eq!(fs::read(backup_path(&path, 2)).unwrap(), b"2");
+        assert_eq!(fs::read(backup_path(&path, 3)).unwrap(), b"1");
+
+        // the oldest generation is dropped
+        age(&backup_path(&path, 2));
+        write_atomic(&path, b"5").unwrap();
+        assert_eq!(fs::read(backup_path(&path, 1)).unwrap(), b"4");
+        assert_eq!(fs::read(backup_path(&path, 2)).unwrap(), b"3");
+        assert_eq!(fs::read(backup_path(&path, 3)).unwrap(), b"2");
+        assert!(!backup_path(&path, 4).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,106 @@
+use iced::Size;
+use image::{ImageBuffer, Rgba, RgbaImage};
+use nalgebra::{Vector2, Vector3};
+
+use crate::{
+    compute::data::ComputeSolution,
+    utils::{polygon_contains, polygon_normal, to_canvas},
+};
+
+/// Largest side of an extracted texture.
+const MAX_TEXTURE_SIZE: f32 = 8192.0;
+
+pub fn sample_bilinear(img: &ImageBuffer<Rgba<u8>, Vec<u8>>, fx: f32, fy: f32) -> Rgba<u8> {
+    let (w, h) = img.dimensions();
+    if fx < 0.0 || fy < 0.0 || fx >= w as f32 - 1.0 || fy >= h as f32 - 1.0 {
+        // outside, return black or nearest
+        let sx = fx.clamp(0.0, (w - 1) as f32) as u32;
+        let sy = fy.clamp(0.0, (h - 1) as f32) as u32;
+        return *img.get_pixel(sx, sy);
+    }
+
+    let x0 = fx.floor() as u32;
+    let y0 = fy.floor() as u32;
+    let x1 = x0 + 1;
+    let y1 = y0 + 1;
+
+    let dx = fx - x0 as f32;
+    let dy = fy - y0 as f32;
+
+    let p00 = img.get_pixel(x0, y0).0;
+    let p10 = img.get_pixel(x1, y0).0;
+    let p01 = img.get_pixel(x0, y1).0;
+    let p11 = img.get_pixel(x1, y1).0;
+
+    let mut out = [0u8; 4];
+    for i in 0..4 {
+        let v = (p00[i] as f32) * (1.0 - dx) * (1.0 - dy)
+            + (p10[i] as f32) * dx * (1.0 - dy)
+            + (p01[i] as f32) * (1.0 - dx) * dy
+            + (p11[i] as f32) * dx * dy;
+        out[i] = v.clamp(0.0, 255.0) as u8;
+    }
+    Rgba(out)
+}
+
+/// Ortho-rectified texture of the planar `polygon`, looked up in `image` through the camera.
+/// The first edge runs along the texture width, pixels outside of the polygon or the photo
+/// are transparent. `None` for a degenerate polygon.
+pub fn extract_plane_texture(
+    image: &RgbaImage,
+    compute_solution: &ComputeSolution<f32>,
+    polygon: &[Vector3<f32>],
+    pixels_per_unit: f32,
+) -> Option<RgbaImage> {
+    let origin = *polygon.first()?;
+    let normal = polygon_normal(polygon)?;
+    let u = (polygon.get(1)? - origin).try_normalize(f32::EPSILON)?;
+    let v = normal.cross(&u);
+
+    let outline: Vec<Vector2<f32>> = polygon
+        .iter()
+        .map(|point| Vector2::new((point - origin).dot(&u), (point - origin).dot(&v)))
+        .collect();
+    let (min, max) = outline.iter().fold(
+        (
+            Vector2::repeat(f32::INFINITY),
+            Vector2::repeat(f32::NEG_INFINITY),
+        ),
+        |(min, max), point| (min.inf(point), max.sup(point)),
+    );
+    let extent = (max - min) * pixels_per_unit;
+    if !(extent.x >= 1.0 && extent.y >= 1.0) {
+        return None;
+    }
+    let pixels_per_unit = pixels_per_unit * (MAX_TEXTURE_SIZE / extent.max()).min(1.0);
+    let width = ((max.x - min.x) * pixels_per_unit).ceil() as u32;
+    let height = ((max.y - min.y) * pixels_per_unit).ceil() as u32;
+
+    let image_size = Size::new(image.width() as f32, image.height() as f32);
+    Some(ImageBuffer::from_fn(width, height, |x, y| {
+        // the top row is the far side of the second axis, so walls stay upright
+        let point = Vector2::new(
+            min.x + (x as f32 + 0.5) / pixels_per_unit,
+            max.y - (y as f32 + 0.5) / pixels_per_unit,
+        );
+        if !polygon_contains(&outline, &point) {
+            return Rgba([0, 0, 0, 0]);
+        }
+        let location = origin + u * point.x + v * point.y;
+        if !compute_solution.is_in_front(&location) {
+            return Rgba([0, 0, 0, 0]);
+        }
+        let Some(projected) = compute_solution.calculate_location_position_to_2d(&location) else {
+            return Rgba([0, 0, 0, 0]);
+        };
+        let pixel = to_canvas(image_size, &projected);
+        if pixel.x < 0.0
+            || pixel.y < 0.0
+            || pixel.x > image_size.width
+            || pixel.y > image_size.height
+        {
+            return Rgba([0, 0, 0, 0]);
+        }
+        sample_bilinear(image, pixel.x - 0.5, pixel.y - 0.5)
+    }))
+}
@@ -0,0 +1,226 @@
+use anyhow::Result;
+use iced::{Point, Size};
+use nalgebra::{Matrix3, Matrix4, Point3, RowVector4, Vector2, Vector3};
+
+use crate::{compute::data::ComputeSolution, utils::relative_to_image_plane};
+
+/// Gauss-Newton steps refining the linear estimate.
+const REFINE_ITERATIONS: usize = 20;
+
+/// World point seen in several solved images.
+#[derive(Debug, Clone)]
+pub struct TriangulatedPoint {
+    pub position: Point3<f32>,
+    /// Reprojection error in pixels, one per view in the order given.
+    pub residuals: Vec<f32>,
+}
+
+/// One sighting of the point: the solved camera, its image size and the
+/// observed point relative to the image size.
+pub struct View<'a> {
+    pub compute_solution: &'a ComputeSolution<f32>,
+    pub image_size: Size<f32>,
+    pub point: Point,
+}
+
+impl View<'_> {
+    /// Rows x, y and w of the projection to image plane coordinates.
+    fn projection(&self) -> [RowVector4<f64>; 3] {
+        let transform = self.compute_solution.transform().cast::<f64>();
+        [transform.row(0), transform.row(1), transform.row(3)].map(|row| row.into_owned())
+    }
+
+    fn image_point(&self) -> Vector2<f64> {
+        relative_to_image_plane(
+            self.image_size.width / self.image_size.height,
+            &Vector2::new(self.point.x, self.point.y),
+        )
+        .cast()
+    }
+
+    /// Image plane coordinates are pixels divided by half the image width.
+    fn pixels(&self) -> f64 {
+        self.image_size.width as f64 / 2.0
+    }
+}
+
+/// Linear (DLT) triangulation of the point seen in `views`, refined by minimizing
+/// the reprojection error in pixels over all views.
+pub fn triangulate(views: &[View]) -> Result<TriangulatedPoint> {
+    if views.len() < 2 {
+        return Err(anyhow::anyhow!("a point needs to be seen in two images"));
+    }
+
+    // x (P3·X) - (P1·X) = 0 and y (P3·X) - (P2·X) = 0 for every view, the smallest
+    // singular vector of the stacked rows is the homogeneous point
+    let mut normal = Matrix4::<f64>::zeros();
+    views.iter().for_each(|view| {
+        let [p1, p2, p3] = view.projection();
+        let image_point = view.image_point();
+        [p3 * image_point.x - p1, p3 * image_point.y - p2]
+            .iter()
+            .for_each(|row| {
+                // unit rows keep a distant camera from dominating the estimate
+                let row = row.normalize();
+                normal += row.transpose() * row;
+            });
+    });
+    let eigen = normal.symmetric_eigen();
+    let homogeneous = eigen.eigenvectors.column(eigen.eigenvalues.imin());
+    if homogeneous.w.abs() < f64::EPSILON {
+        return Err(anyhow::anyhow!(
+            "rays do not meet, the point is at infinity"
+        ));
+    }
+    let mut position: Vector3<f64> = homogeneous.xyz() / homogeneous.w;
+
+    for _ in 0..REFINE_ITERATIONS {
+        let mut jtj = Matrix3::<f64>::zeros();
+        let mut jtr = Vector3::<f64>::zeros();
+        views.iter().for_each(|view| {
+            let [p1, p2, p3] = view.projection();
+            let point = position.push(1.0);
+            let w = p3.dot(&point.transpose());
+            if w.abs() < f64::EPSILON {
+                return;
+            }
+            let projected =
+                Vector2::new(p1.dot(&point.transpose()), p2.dot(&point.transpose())) / w;
+            let residual = (projected - view.image_point()) * view.pixels();
+            let jacobian = |row: &RowVector4<f64>, value: f64| {
+                (row.fixed_columns::<3>(0) - p3.fixed_columns::<3>(0) * value) / w * view.pixels()
+            };
+            [
+                (jacobian(&p1, projected.x), residual.x),
+                (jacobian(&p2, projected.y), residual.y),
+            ]
+            .iter()
+            .for_each(|(jacobian, residual)| {
+                jtj += jacobian.transpose() * jacobian;
+                jtr += jacobian.transpose() * *residual;
+            });
+        });
+        let Some(step) = jtj.try_inverse().map(|inverse| inverse * jtr) else {
+            break;
+        };
+        position -= step;
+        if step.norm() < 1e-9 * (1.0 + position.norm()) {
+            break;
+        }
+    }
+
+    let position = Point3::from(position.cast::<f32>());
+    if views
+        .iter()
+        .any(|view| !view.compute_solution.is_in_front(&position.coords))
+    {
+        return Err(anyhow::anyhow!("point is behind one of the cameras"));
+    }
+    let residuals = views
+        .iter()
+        .map(|view| {
+            view.compute_solution
+                .calculate_location_position_to_2d(&position.coords)
+                .map(|projected| {
+                    (projected - view.image_point().cast::<f32>()).norm() * view.pixels() as f32
+                })
+                .unwrap_or(f32::INFINITY)
+        })
+        .collect();
+    Ok(TriangulatedPoint {
+        position,
+        residuals,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Isometry3;
+
+    use super::*;
+
+    const IMAGE_SIZE: Size<f32> = Size::new(1200.0, 800.0);
+
+    fn camera(eye: Point3<f32>) -> ComputeSolution<f32> {
+        let view = Isometry3::look_at_rh(&eye, &Point3::origin(), &Vector3::z());
+        ComputeSolution::new(view.to_homogeneous(), Vector2::zeros(), 50f32.to_radians())
+    }
+
+    /// Where `camera` sees `position`, relative to the image size.
+    fn observe(camera: &ComputeSolution<f32>, position: &Point3<f32>) -> Point {
+        let image_plane = camera
+            .calculate_location_position_to_2d(&position.coords)
+            .unwrap();
+        let ratio = IMAGE_SIZE.width / IMAGE_SIZE.height;
+        Point::new(
+            (image_plane.x + 1.0) / 2.0,
+            (1.0 - image_plane.y * ratio) / 2.0,
+        )
+    }
+
+    #[test]
+    fn recovers_a_point_seen_from_three_cameras() {
+        let cameras = [
+            camera(Point3::new(5.0, -4.0, 2.0)),
+            camera(Point3::new(-3.0, -5.0, 1.5)),
+            camera(Point3::new(1.0, 6.0, 3.0)),
+        ];
+        let position = Point3::new(0.4, -0.3, 0.8);
+        let views: Vec<View> = cameras
+            .iter()
+            .map(|compute_solution| View {
+                compute_solution,
+                image_size: IMAGE_SIZE,
+                point: observe(compute_solution, &position),
+            })
+            .collect();
+
+        let triangulated = triangulate(&views).unwrap();
+        assert!((triangulated.position - position).norm() < 1e-3);
+        assert!(
+            triangulated
+                .residuals
+                .iter()
+                .all(|residual| *residual < 0.01)
+        );
+    }
+
+    #[test]
+    fn refinement_spreads_the_error_of_a_noisy_observation() {
+        let cameras = [
+            camera(Point3::new(5.0, -4.0, 2.0)),
+            camera(Point3::new(-3.0, -5.0, 1.5)),
+        ];
+        let position = Point3::new(0.4, -0.3, 0.8);
+        let mut views: Vec<View> = cameras
+            .iter()
+            .map(|compute_solution| View {
+                compute_solution,
+                image_size: IMAGE_SIZE,
+                point: observe(compute_solution, &position),
+            })
+            .collect();
+        // two pixels off in the first image
+        views[0].point.x += 2.0 / IMAGE_SIZE.width;
+
+        let triangulated = triangulate(&views).unwrap();
+        assert!((triangulated.position - position).norm() < 0.05);
+        assert!(
+            triangulated
+                .residuals
+                .iter()
+                .all(|residual| *residual < 2.0)
+        );
+    }
+
+    #[test]
+    fn needs_two_views() {
+        let camera = camera(Point3::new(5.0, -4.0, 2.0));
+        let view = View {
+            compute_solution: &camera,
+            image_size: IMAGE_SIZE,
+            point: Point::new(0.5, 0.5),
+        };
+        assert!(triangulate(&[view]).is_err());
+    }
+}
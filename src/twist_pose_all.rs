@@ -15,14 +15,20 @@ use iced::{
         },
     },
     event::Status,
+    keyboard::{self, key::Named},
     widget::canvas::{self, Event, Fill, Stroke, Text},
 };
-use nalgebra::{Point2, Point3, Vector2};
+use nalgebra::{Point2, Point3, Vector2, Vector3};
 
 use crate::{
-    Component,
+    Component, TwistLabel,
     compute::data::ComputeSolution,
-    utils::{scale_point, scale_point_to_canvas, to_canvas},
+    hidden_lines::{HiddenLines, draw_wireframe},
+    horizon::{draw_horizon, vanishing_points_to_canvas},
+    mesh::{Mesh, draw_mesh},
+    nudge::{cycle, draw_handle_position, nudge_offset},
+    reference::{REFERENCE_COLOR, ReferenceLabel, draw_reference_labels},
+    utils::{scale_point, scale_point_to_canvas},
 };
 
 pub struct ComputeCameraPoseTwist<'a, Message, Theme = iced::Theme, Renderer = iced::Renderer>
@@ -34,6 +40,17 @@ where
     message_: PhantomData<Message>,
     referece_cub_cache: geometry::Cache<Renderer>,
     twist_points_cache: geometry::Cache<Renderer>,
+    horizon_cache: geometry::Cache<Renderer>,
+    mesh_cache: geometry::Cache<Renderer>,
+    show_horizon: bool,
+    reference_labels: Vec<ReferenceLabel>,
+    mesh: Option<Rc<Mesh>>,
+    occluders: Rc<Vec<Vec<Vector3<f32>>>>,
+    hidden_lines: HiddenLines,
+    on_vertex_pick: Option<Box<dyn Fn(usize) -> Message + 'a>>,
+    twist_labels: Vec<TwistLabel>,
+    on_add_point: Option<Box<dyn Fn(Point2<f32>) -> Message + 'a>>,
+    on_snap: Option<Box<dyn Fn(usize) -> Message + 'a>>,
 
     compute_solution: RefCell<Option<ComputeSolution<f32>>>,
     renderer_: PhantomData<Renderer>,
@@ -65,6 +82,17 @@ where
             theme_: PhantomData,
             referece_cub_cache: geometry::Cache::default(),
             twist_points_cache: geometry::Cache::default(),
+            horizon_cache: geometry::Cache::default(),
+            mesh_cache: geometry::Cache::default(),
+            show_horizon: false,
+            reference_labels: Vec::new(),
+            mesh: None,
+            occluders: Rc::default(),
+            hidden_lines: HiddenLines::default(),
+            on_vertex_pick: None,
+            twist_labels: Vec::new(),
+            on_add_point: None,
+            on_snap: None,
             reference_cub,
             image_size: Size::default(),
             twist_points,
@@ -83,6 +111,59 @@ where
         self
     }
 
+    /// Draws the horizon line, vanishing points and principal point of the solution.
+    pub fn show_horizon(mut self, show_horizon: bool) -> Self {
+        self.show_horizon = show_horizon;
+        self
+    }
+
+    /// Labels drawn with the reference geometry, at the tips of the axis arrows.
+    pub fn reference_labels(mut self, reference_labels: Vec<ReferenceLabel>) -> Self {
+        self.reference_labels = reference_labels;
+        self
+    }
+
+    /// Faces hiding the edges of the reference geometry and of the mesh.
+    pub fn hidden_lines(
+        mut self,
+        hidden_lines: HiddenLines,
+        occluders: Rc<Vec<Vec<Vector3<f32>>>>,
+    ) -> Self {
+        self.hidden_lines = hidden_lines;
+        self.occluders = occluders;
+        self
+    }
+
+    /// Reference mesh drawn as a wireframe.
+    pub fn mesh(mut self, mesh: Option<Rc<Mesh>>) -> Self {
+        self.mesh = mesh;
+        self
+    }
+
+    /// Shows the mesh vertices, a click on one of them publishes its index.
+    pub fn on_vertex_pick(mut self, on_vertex_pick: impl Fn(usize) -> M + 'a) -> Self {
+        self.on_vertex_pick = Some(Box::new(on_vertex_pick));
+        self
+    }
+
+    /// Names shown next to the twist points, disabled points are drawn dimmed.
+    pub fn twist_labels(mut self, twist_labels: Vec<TwistLabel>) -> Self {
+        self.twist_labels = twist_labels;
+        self
+    }
+
+    /// A click away from the twist points publishes the clicked position relative to the image.
+    pub fn on_add_point(mut self, on_add_point: impl Fn(Point2<f32>) -> M + 'a) -> Self {
+        self.on_add_point = Some(Box::new(on_add_point));
+        self
+    }
+
+    /// Message for a point dropped with Ctrl (Cmd on macOS) held, to snap it to the nearest image corner.
+    pub fn on_snap(mut self, on_snap: impl Fn(usize) -> M + 'a) -> Self {
+        self.on_snap = Some(Box::new(on_snap));
+        self
+    }
+
     pub fn image_size(mut self, image_size: Size) -> Self {
         self.image_size = image_size;
         self
@@ -102,6 +183,20 @@ where
         let scale_cursor = scale_point(adjusted_cursor, bounds.size());
         match event {
             Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if self.on_vertex_pick.is_some()
+                    && let (Some(compute_solution), Some(mesh)) =
+                        (self.compute_solution.borrow().as_ref(), &self.mesh)
+                {
+                    state.picked_vertex = mesh.closest_vertex(
+                        compute_solution,
+                        bounds.size(),
+                        Point::new(adjusted_cursor.x, adjusted_cursor.y),
+                        10.0,
+                    );
+                    if state.picked_vertex.is_some() {
+                        return Status::Captured;
+                    }
+                }
                 let clicked_position = scale_cursor;
                 state.captured = Some(Vector::new(clicked_position.x, clicked_position.y));
                 let cursor = Point::new(adjusted_cursor.x, adjusted_cursor.y);
@@ -116,10 +211,19 @@ where
                             state.selected_twist_point = Some(index);
                         }
                     });
+                if state.selected_twist_point.is_some() {
+                    state.focused_twist_point = state.selected_twist_point;
+                }
+                if state.selected_twist_point.is_none() && self.on_add_point.is_some() {
+                    state.added_point = Some(Point2::new(scale_cursor.x, scale_cursor.y));
+                }
                 Status::Captured
             }
 
             Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if state.modifiers.command() {
+                    state.snapped_twist_point = state.selected_twist_point;
+                }
                 state.selected_twist_point = None;
                 self.twist_points_cache.clear();
                 Status::Captured
@@ -138,6 +242,33 @@ where
                     Status::Ignored
                 }
             }
+
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
+                if let keyboard::Key::Named(Named::Tab) = key.as_ref() {
+                    state.focused_twist_point = cycle(
+                        state.focused_twist_point,
+                        self.twist_points_2d.borrow().len(),
+                        modifiers.shift(),
+                    );
+                    self.twist_points_cache.clear();
+                    return Status::Captured;
+                }
+                let (Some(focused), Some(offset)) = (
+                    state.focused_twist_point,
+                    nudge_offset(key, *modifiers, self.image_size),
+                ) else {
+                    return Status::Ignored;
+                };
+                let mut twist_points_2d = self.twist_points_2d.borrow_mut();
+                let Some(point) = twist_points_2d.get_mut(focused) else {
+                    return Status::Ignored;
+                };
+                point.x += offset.x;
+                point.y += offset.y;
+                self.twist_points_cache.clear();
+                state.publish = true;
+                Status::Captured
+            }
             _ => Status::Ignored,
         }
     }
@@ -157,34 +288,20 @@ where
                     return;
                 }
 
-                let mut builder = canvas::path::Builder::new();
-
-                self.reference_cub
-                    .as_ref()
-                    .borrow()
-                    .chunks(2)
-                    .for_each(|points| {
-                        self.compute_solution
-                            .borrow()
-                            .as_ref()
-                            .unwrap()
-                            .calculate_location_position_to_2d_frustum(points)
-                            .iter()
-                            .for_each(|&(start, end)| {
-                                let start = to_canvas(bounds.size(), &start.coords.xy());
-                                let end = to_canvas(bounds.size(), &end.coords.xy());
-                                builder.move_to(Point::new(start.x, start.y));
-                                builder.line_to(Point::new(end.x, end.y));
-                            });
-                    });
-                let path = builder.build();
-                frame.stroke(
-                    &path,
-                    Stroke {
-                        style: canvas::Style::Solid(Color::from_rgba(0.9, 0.7, 0.7, 1.0)),
-                        width: 1.0,
-                        ..Stroke::default()
-                    },
+                draw_wireframe(
+                    frame,
+                    self.compute_solution.borrow().as_ref().unwrap(),
+                    &self.reference_cub.borrow(),
+                    &self.occluders,
+                    self.hidden_lines,
+                    bounds.size(),
+                    REFERENCE_COLOR,
+                );
+                draw_reference_labels(
+                    frame,
+                    self.compute_solution.borrow().as_ref().unwrap(),
+                    &self.reference_labels,
+                    bounds.size(),
                 );
             });
 
@@ -192,90 +309,109 @@ where
             .twist_points_cache
             .draw(renderer, bounds.size(), |frame| {
                 let selected_color = Color::from_rgba(0.8, 0.8, 0.2, 0.8);
+                let disabled_color = Color::from_rgba(0.5, 0.5, 0.5, 0.6);
+                let draw_point = |frame: &mut geometry::Frame<Renderer>, index: usize| {
+                    let (Some(item), Some(twist_point)) = (
+                        self.twist_points_2d.borrow().get(index).copied(),
+                        self.twist_points.borrow().get(index).copied(),
+                    ) else {
+                        return;
+                    };
+                    let label = self.twist_labels.get(index);
+                    let item = scale_point_to_canvas(&Point::new(item.x, item.y), bounds.size());
+                    let mut builder = canvas::path::Builder::new();
+                    builder.circle(item, 5.0);
+                    let path = builder.build();
+                    let name = match label {
+                        Some(label) if !label.name.is_empty() => label.name.clone(),
+                        _ => format!("#{}", index + 1),
+                    };
+                    let content = format!(
+                        "{name} {:>7.2},{:>7.2},{:>7.2}",
+                        twist_point.x, twist_point.y, twist_point.z
+                    );
+                    frame.fill_rectangle(
+                        Point::new(item.x + 2.0, item.y + 2.0),
+                        Size::new(content.chars().count() as f32 * 6.0 + 4.0, 15.0),
+                        Fill {
+                            style: canvas::Style::Solid(Color::from_rgba(0.3, 0.3, 0.3, 0.9)),
+                            ..Fill::default()
+                        },
+                    );
+                    frame.fill_text(Text {
+                        content,
+                        position: Point::new(item.x + 4.0, item.y + 4.0),
+                        color: Color::from_rgba(0.8, 0.8, 0.8, 0.8),
+                        size: Pixels(10.0),
+                        ..Default::default()
+                    });
+                    let color = if label.is_some_and(|label| label.disabled) {
+                        disabled_color
+                    } else {
+                        selected_color
+                    };
+                    frame.stroke(
+                        &path,
+                        Stroke {
+                            style: canvas::Style::Solid(color),
+                            width: 2.0,
+                            ..Stroke::default()
+                        },
+                    );
+                };
                 if let Some(selected) = state.selected_twist_point {
-                    if let Some(item) = self.twist_points_2d.borrow().get(selected) {
-                        let item =
-                            scale_point_to_canvas(&Point::new(item.x, item.y), bounds.size());
-                        let mut builder = canvas::path::Builder::new();
-                        builder.circle(item, 5.0);
-                        let path = builder.build();
-                        frame.fill_rectangle(
-                            Point::new(item.x + 2.0, item.y + 2.0),
-                            Size::new(100.0, 15.0),
-                            Fill {
-                                style: canvas::Style::Solid(Color::from_rgba(0.3, 0.3, 0.3, 0.9)),
-                                ..Fill::default()
-                            },
-                        );
-
-                        if let Some(twist_point) = self.twist_points.borrow().get(selected) {
-                            frame.fill_text(Text {
-                                content: format!(
-                                    "{:>7.2},{:>7.2},{:>7.2}",
-                                    twist_point.x, twist_point.y, twist_point.z
-                                ),
-                                position: Point::new(item.x + 4.0, item.y + 4.0),
-                                color: Color::from_rgba(0.8, 0.8, 0.8, 0.8),
-                                size: Pixels(10.0),
-                                ..Default::default()
-                            });
-                            frame.stroke(
-                                &path,
-                                Stroke {
-                                    style: canvas::Style::Solid(selected_color),
-                                    width: 2.0,
-                                    ..Stroke::default()
-                                },
-                            );
-                        }
-                    }
+                    draw_point(frame, selected);
                 } else {
-                    self.twist_points_2d
-                        .borrow()
-                        .iter()
-                        .enumerate()
-                        .for_each(|(selected, item)| {
-                            let item =
-                                scale_point_to_canvas(&Point::new(item.x, item.y), bounds.size());
-                            let mut builder = canvas::path::Builder::new();
-                            builder.circle(item, 5.0);
-                            let path = builder.build();
-                            frame.fill_rectangle(
-                                Point::new(item.x + 2.0, item.y + 2.0),
-                                Size::new(100.0, 15.0),
-                                Fill {
-                                    style: canvas::Style::Solid(Color::from_rgba(
-                                        0.3, 0.3, 0.3, 0.9,
-                                    )),
-                                    ..Fill::default()
-                                },
-                            );
-
-                            if let Some(twist_point) = self.twist_points.borrow().get(selected) {
-                                frame.fill_text(Text {
-                                    content: format!(
-                                        "{:>7.2},{:>7.2},{:>7.2}",
-                                        twist_point.x, twist_point.y, twist_point.z
-                                    ),
-                                    position: Point::new(item.x + 4.0, item.y + 4.0),
-                                    color: Color::from_rgba(0.8, 0.8, 0.8, 0.8),
-                                    size: Pixels(10.0),
-                                    ..Default::default()
-                                });
-                                frame.stroke(
-                                    &path,
-                                    Stroke {
-                                        style: canvas::Style::Solid(selected_color),
-                                        width: 2.0,
-                                        ..Stroke::default()
-                                    },
-                                );
-                            }
-                        })
+                    (0..self.twist_points_2d.borrow().len()).for_each(|index| {
+                        draw_point(frame, index);
+                    });
                 };
+                if let Some(focused) = state.focused_twist_point
+                    && let Some(item) = self.twist_points_2d.borrow().get(focused)
+                {
+                    draw_handle_position(
+                        frame,
+                        Point::new(item.x, item.y),
+                        bounds.size(),
+                        self.image_size,
+                    );
+                }
             });
 
-        vec![twist_point, referece_cub_cache]
+        let horizon = self.horizon_cache.draw(renderer, bounds.size(), |frame| {
+            let compute_solution = self.compute_solution.borrow();
+            let Some(compute_solution) = compute_solution.as_ref() else {
+                return;
+            };
+            if !self.show_horizon {
+                return;
+            }
+            *state.vanishing_points.borrow_mut() =
+                vanishing_points_to_canvas(compute_solution, bounds.size());
+            draw_horizon(
+                frame,
+                compute_solution,
+                &state.vanishing_points.borrow(),
+                bounds.size(),
+            );
+        });
+
+        let mesh = self.mesh_cache.draw(renderer, bounds.size(), |frame| {
+            let compute_solution = self.compute_solution.borrow();
+            if let (Some(compute_solution), Some(mesh)) = (compute_solution.as_ref(), &self.mesh) {
+                draw_mesh(
+                    frame,
+                    compute_solution,
+                    mesh,
+                    &self.occluders,
+                    self.hidden_lines,
+                    bounds.size(),
+                    self.on_vertex_pick.is_some(),
+                );
+            }
+        });
+
+        vec![mesh, horizon, twist_point, referece_cub_cache]
     }
 }
 
@@ -323,11 +459,28 @@ where
         let bounds = layout.bounds();
 
         let state = tree.state.downcast_mut::<State>();
+        if let Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) = event {
+            state.modifiers = *modifiers;
+        }
 
         let event_status = self.update_inner(state, event, bounds, cursor);
         if let Status::Captured = event_status {
             shell.capture_event();
-            if let Event::Mouse(mouse::Event::CursorMoved { position: _ }) = event {
+            if let Some(vertex) = state.picked_vertex.take()
+                && let Some(on_vertex_pick) = &self.on_vertex_pick
+            {
+                shell.publish(on_vertex_pick(vertex));
+            } else if let Some(point) = state.added_point.take()
+                && let Some(on_add_point) = &self.on_add_point
+            {
+                shell.publish(on_add_point(point));
+            } else if let Some(index) = state.snapped_twist_point.take()
+                && let Some(on_snap) = &self.on_snap
+            {
+                shell.publish(on_snap(index));
+            } else if std::mem::take(&mut state.publish)
+                || matches!(event, Event::Mouse(mouse::Event::CursorMoved { .. }))
+            {
                 shell.publish((self.on_points_move)());
             } else {
                 shell.request_redraw();
@@ -382,8 +535,16 @@ pub struct State {
     pub edit: Option<Component>,
     pub image_path: String,
     pub captured: Option<Vector>,
-    pub vanishing_points: RefCell<(Vector2<f32>, Vector2<f32>, Vector2<f32>)>,
+    pub vanishing_points: RefCell<[Option<Vector2<f32>>; 3]>,
     pub selected_twist_point: Option<usize>,
+    /// Point moved by the arrow keys, the last one clicked or reached with Tab.
+    pub focused_twist_point: Option<usize>,
+    pub picked_vertex: Option<usize>,
+    pub added_point: Option<Point2<f32>>,
+    /// Point dropped with Ctrl (Cmd on macOS) held.
+    pub snapped_twist_point: Option<usize>,
+    pub modifiers: keyboard::Modifiers,
+    pub publish: bool,
 }
 
 impl<'a, Message, Theme, Renderer> From<ComputeCameraPoseTwist<'a, Message, Theme, Renderer>>
@@ -0,0 +1,193 @@
+use std::f32::consts::PI;
+
+use iced::{Point, Size};
+use nalgebra::{Matrix3, Point2, Point3, Rotation3, Vector2};
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AxisData,
+    compute::{compute_camera_pose_twist, compute_vanishing_points_pose, data::ComputeSolution},
+};
+
+/// Fixed seed, the same handles give the same estimate.
+const SEED: u64 = 0x5eed;
+/// Two sided confidence level of the reported intervals.
+const CONFIDENCE: f32 = 0.95;
+
+/// Value of the unperturbed solution and the confidence interval around it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Interval {
+    pub value: f32,
+    pub low: f32,
+    pub high: f32,
+}
+
+impl Interval {
+    fn from_samples(value: f32, mut samples: Vec<f32>) -> Self {
+        samples.sort_by(f32::total_cmp);
+        let tail = (1.0 - CONFIDENCE) / 2.0;
+        let at = |quantile: f32| samples[((samples.len() - 1) as f32 * quantile).round() as usize];
+        Self {
+            value,
+            low: at(tail),
+            high: at(1.0 - tail),
+        }
+    }
+}
+
+/// Spread of the camera when every 2D handle is moved by up to `pixel_error` pixels.
+/// Field of view and rotation (XYZ euler angles of the camera) are in degrees.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalibrationUncertainty {
+    pub pixel_error: f32,
+    pub confidence: f32,
+    pub samples: usize,
+    pub solved_samples: usize,
+    pub field_of_view: Interval,
+    pub camera_position: [Interval; 3],
+    pub camera_rotation: [Interval; 3],
+}
+
+/// Monte Carlo estimate for the vanishing point solver, line ends, origin and
+/// reference segment are jittered.
+pub fn vanishing_points_uncertainty(
+    axis_data: &AxisData,
+    image_size: Size<f32>,
+    field_of_view: &Option<f32>,
+    pixel_error: f32,
+    samples: usize,
+) -> Option<CalibrationUncertainty> {
+    let (reference, _) =
+        compute_vanishing_points_pose(&mut axis_data.clone(), image_size, field_of_view).ok()?;
+    monte_carlo(&reference, pixel_error, samples, |rng| {
+        let mut axis_data = axis_data.clone();
+        axis_data.axis_lines.iter_mut().for_each(|(a, b)| {
+            *a = jitter(a, image_size, pixel_error, rng);
+            *b = jitter(b, image_size, pixel_error, rng);
+        });
+        axis_data.control_point = jitter(&axis_data.control_point, image_size, pixel_error, rng);
+        if let Some(reference) = axis_data.reference_distance.as_mut() {
+            reference.line.0 = jitter(&reference.line.0, image_size, pixel_error, rng);
+            reference.line.1 = jitter(&reference.line.1, image_size, pixel_error, rng);
+        }
+        compute_vanishing_points_pose(&mut axis_data, image_size, field_of_view)
+            .ok()
+            .map(|(compute_solution, _)| compute_solution)
+    })
+}
+
+/// Monte Carlo estimate for the lambda twist solver, the 2D twist handles are jittered.
+pub fn twist_uncertainty(
+    twist_points: &[Point3<f32>],
+    twist_points_2d: &[Point2<f32>],
+    image_size: Size<f32>,
+    field_of_view: f32,
+    principal_point: Vector2<f32>,
+    pixel_error: f32,
+    samples: usize,
+) -> Option<CalibrationUncertainty> {
+    let reference = compute_camera_pose_twist(
+        twist_points,
+        twist_points_2d,
+        image_size,
+        field_of_view,
+        principal_point,
+    )?;
+    monte_carlo(&reference, pixel_error, samples, |rng| {
+        let twist_points_2d: Vec<Point2<f32>> = twist_points_2d
+            .iter()
+            .map(|item| {
+                let item = jitter(&Point::new(item.x, item.y), image_size, pixel_error, rng);
+                Point2::new(item.x, item.y)
+            })
+            .collect();
+        compute_camera_pose_twist(
+            twist_points,
+            &twist_points_2d,
+            image_size,
+            field_of_view,
+            principal_point,
+        )
+    })
+}
+
+/// Moves a point given relative to the image size by up to `pixel_error` pixels on each axis.
+fn jitter(point: &Point, image_size: Size<f32>, pixel_error: f32, rng: &mut SmallRng) -> Point {
+    if pixel_error <= 0.0 {
+        return *point;
+    }
+    Point::new(
+        point.x + rng.gen_range(-pixel_error..=pixel_error) / image_size.width,
+        point.y + rng.gen_range(-pixel_error..=pixel_error) / image_size.height,
+    )
+}
+
+fn monte_carlo(
+    reference: &ComputeSolution<f32>,
+    pixel_error: f32,
+    samples: usize,
+    mut solve: impl FnMut(&mut SmallRng) -> Option<ComputeSolution<f32>>,
+) -> Option<CalibrationUncertainty> {
+    let mut rng = SmallRng::seed_from_u64(SEED);
+    let (reference_position, reference_rotation) = camera(reference)?;
+
+    let mut field_of_view = Vec::with_capacity(samples);
+    let mut position: [Vec<f32>; 3] = Default::default();
+    let mut rotation: [Vec<f32>; 3] = Default::default();
+    for _ in 0..samples {
+        let Some(compute_solution) = solve(&mut rng) else {
+            continue;
+        };
+        let Some((camera_position, camera_rotation)) = camera(&compute_solution) else {
+            continue;
+        };
+        field_of_view.push(compute_solution.field_of_view().to_degrees());
+        for axis in 0..3 {
+            position[axis].push(camera_position[axis]);
+            // keep the angles next to the reference so the interval does not wrap around
+            let delta = wrap_angle(camera_rotation[axis] - reference_rotation[axis]);
+            rotation[axis].push((reference_rotation[axis] + delta).to_degrees());
+        }
+    }
+    if field_of_view.is_empty() {
+        return None;
+    }
+
+    Some(CalibrationUncertainty {
+        pixel_error,
+        confidence: CONFIDENCE,
+        samples,
+        solved_samples: field_of_view.len(),
+        field_of_view: Interval::from_samples(
+            reference.field_of_view().to_degrees(),
+            field_of_view,
+        ),
+        camera_position: std::array::from_fn(|axis| {
+            Interval::from_samples(
+                reference_position[axis],
+                std::mem::take(&mut position[axis]),
+            )
+        }),
+        camera_rotation: std::array::from_fn(|axis| {
+            Interval::from_samples(
+                reference_rotation[axis].to_degrees(),
+                std::mem::take(&mut rotation[axis]),
+            )
+        }),
+    })
+}
+
+/// Camera position in world space and camera to world rotation as XYZ euler angles in radians.
+fn camera(compute_solution: &ComputeSolution<f32>) -> Option<([f32; 3], [f32; 3])> {
+    let (camera_center, _) = compute_solution.camera_ray(&Vector2::zeros())?;
+    let view = compute_solution.view_transform();
+    let rotation: Matrix3<f32> = view.fixed_view::<3, 3>(0, 0).transpose();
+    let (roll, pitch, yaw) = Rotation3::from_matrix(&rotation).euler_angles();
+    Some((camera_center.into(), [roll, pitch, yaw]))
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
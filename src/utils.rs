@@ -1,7 +1,7 @@
 use std::ops::{AddAssign, DivAssign, MulAssign, SubAssign};
 
 use iced::{Point, Size, Vector};
-use nalgebra::{Matrix3, Perspective3, Point2, Point3, RealField, Scalar, Vector2, Vector3};
+use nalgebra::{Matrix3, Point2, RealField, Scalar, Vector2, Vector3};
 use num_traits::Float;
 
 use crate::{EditAxis, compute::data::ComputeSolution};
@@ -124,8 +124,10 @@ pub fn to_canvas<T: Float + AddAssign + MulAssign + DivAssign + Scalar + 'static
     Point2::from_homogeneous(transform * point).unwrap().coords
 }
 
+/// Intersects the camera ray through the cursor with the plane through `last_point`
+/// normal to `edit_state` (the XY plane for `EditAxis::None`), `None` behind the camera.
 pub fn calculate_cursor_position_to_3d<
-    T: Float + AddAssign + MulAssign + DivAssign + RealField + Scalar,
+    T: Float + AddAssign + MulAssign + DivAssign + SubAssign + RealField + Scalar,
 >(
     edit_state: &EditAxis,
     compute_solution: &ComputeSolution<T>,
@@ -134,46 +136,28 @@ pub fn calculate_cursor_position_to_3d<
     last_point: Vector3<T>,
 ) -> Option<Vector3<T>> {
     let click_location = relative_to_image_plane(ratio, cursor_canvas);
+    let (camera_center, direction) = compute_solution.camera_ray(&click_location)?;
 
-    let perspective = Perspective3::new(
+    let normal = edit_state.direction().unwrap_or(Vector3::new(
+        T::from(0.0).unwrap(),
+        T::from(0.0).unwrap(),
         T::from(1.0).unwrap(),
-        compute_solution.field_of_view(),
-        T::from(0.01).unwrap(),
-        T::from(10.0).unwrap(),
-    );
-
-    let mut matrix = perspective.into_inner();
-    *matrix.index_mut((0, 2)) = -compute_solution.ortho_center().x;
-    *matrix.index_mut((1, 2)) = -compute_solution.ortho_center().y;
-
-    let model_view_projection = matrix * compute_solution.view_transform();
-    let model_view_projection = model_view_projection.try_inverse()?;
-    let last_point_axis = Vector3::zeros();
-    let point = model_view_projection * Point3::from(last_point_axis).to_homogeneous();
-    let point3d1 = Point3::from_homogeneous(point)?;
-
-    let point =
-        Point3::new(click_location.x, click_location.y, T::from(1.0).unwrap()).to_homogeneous();
-    let point = model_view_projection * point;
-
-    let point3d2 = Point3::from_homogeneous(point)?;
-
-    let axis = match edit_state {
-        EditAxis::EditZ => Vector3::new(
-            T::from(1.0).unwrap(),
-            T::from(0.0).unwrap(),
-            T::from(0.0).unwrap(),
-        ),
-        _ => Vector3::new(
-            T::from(0.0).unwrap(),
-            T::from(0.0).unwrap(),
-            T::from(1.0).unwrap(),
-        ),
-    };
+    ));
+    if Float::abs(normal.dot(&direction)) < T::from(1e-6).unwrap() {
+        return None;
+    }
 
-    let intersection1_3d =
-        line_insert_with_plane(&last_point, &axis, &point3d1.coords, &point3d2.coords);
-    Some(intersection1_3d)
+    let intersection = line_insert_with_plane(
+        &last_point,
+        &normal,
+        &camera_center,
+        &(camera_center + direction),
+    );
+    if (intersection - camera_center).dot(&direction) > T::from(0.0).unwrap() {
+        Some(intersection)
+    } else {
+        None
+    }
 }
 
 pub fn line_insert_with_yz_plane(
@@ -214,3 +198,54 @@ pub fn line_insert_with_plane<T: Float + AddAssign + MulAssign + SubAssign + Sca
     let t = normal_to_plane.dot(&(a - plane_point)) / -normal_to_plane.dot(&(b - a));
     a + (b - a) * t
 }
+
+/// Closest point on the line `point + s * direction` to the ray `origin + t * ray`.
+pub fn closest_point_on_line_to_ray<T: Float + AddAssign + MulAssign + SubAssign + Scalar>(
+    point: &Vector3<T>,
+    direction: &Vector3<T>,
+    origin: &Vector3<T>,
+    ray: &Vector3<T>,
+) -> Option<Vector3<T>> {
+    let w0 = point - origin;
+    let a = direction.dot(direction);
+    let b = direction.dot(ray);
+    let c = ray.dot(ray);
+    let d = direction.dot(&w0);
+    let e = ray.dot(&w0);
+    let denominator = a * c - b * b;
+    if denominator.abs() < T::from(1e-9).unwrap() {
+        return None;
+    }
+    let s = (b * e - c * d) / denominator;
+    Some(point + direction * s)
+}
+
+/// Unit normal of a planar polygon by Newell's method, robust for any simple polygon.
+pub fn polygon_normal(polygon: &[Vector3<f32>]) -> Option<Vector3<f32>> {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .fold(Vector3::zeros(), |normal, (a, b)| {
+            normal
+                + Vector3::new(
+                    (a.y - b.y) * (a.z + b.z),
+                    (a.z - b.z) * (a.x + b.x),
+                    (a.x - b.x) * (a.y + b.y),
+                )
+        })
+        .try_normalize(f32::EPSILON)
+}
+
+/// Even-odd rule point in polygon test.
+pub fn polygon_contains(polygon: &[Vector2<f32>], point: &Vector2<f32>) -> bool {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .filter(|(a, b)| {
+            (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y)
+        })
+        .count()
+        % 2
+        == 1
+}
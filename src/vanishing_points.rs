@@ -0,0 +1,748 @@
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+
+use iced::{
+    Color, Element,
+    Length::{self},
+    Pixels, Point, Rectangle, Size, Vector,
+    advanced::{
+        Clipboard, Layout, Shell, Widget,
+        graphics::geometry::{self},
+        layout, mouse,
+        renderer::Style,
+        widget::{
+            Tree,
+            tree::{self},
+        },
+    },
+    event::Status,
+    keyboard::{self, key::Named},
+    widget::canvas::{self, Event, Fill, Stroke, Text},
+};
+use nalgebra::{Point3, Vector2, Vector3};
+
+use crate::{
+    AxisData, EditAxis,
+    compute::data::ComputeSolution,
+    hidden_lines::{HiddenLines, draw_wireframe},
+    horizon::{AXIS_COLORS, draw_horizon, vanishing_points_to_canvas},
+    mesh::{Mesh, draw_mesh},
+    nudge::{cycle, draw_handle_position, nudge_offset},
+    reference::{REFERENCE_COLOR, ReferenceLabel, draw_reference_labels},
+    utils::{get_extension_for_line_within_bounds, scale_point, scale_point_to_canvas, to_canvas},
+};
+
+pub struct ComputeCameraPoseVanishingPoints<
+    'a,
+    Message,
+    Theme = iced::Theme,
+    Renderer = iced::Renderer,
+> where
+    Renderer: geometry::Renderer,
+{
+    width: Length,
+    height: Length,
+    message_: PhantomData<Message>,
+    referece_cub_cache: geometry::Cache<Renderer>,
+    axis_lines_cache: geometry::Cache<Renderer>,
+    horizon_cache: geometry::Cache<Renderer>,
+    mesh_cache: geometry::Cache<Renderer>,
+    show_horizon: bool,
+    reference_labels: Vec<ReferenceLabel>,
+    mesh: Option<Rc<Mesh>>,
+    occluders: Rc<Vec<Vec<Vector3<f32>>>>,
+    hidden_lines: HiddenLines,
+    image_size: Size<f32>,
+
+    compute_solution: RefCell<Option<ComputeSolution<f32>>>,
+    renderer_: PhantomData<Renderer>,
+    theme_: PhantomData<Theme>,
+    reference_cub: Rc<RefCell<Vec<Point3<f32>>>>,
+    axis_data: Rc<RefCell<AxisData>>,
+    on_points_move: Box<dyn Fn() -> Message + 'a>,
+}
+
+impl<'a, M, Theme, Renderer> ComputeCameraPoseVanishingPoints<'a, M, Theme, Renderer>
+where
+    Renderer: geometry::Renderer,
+{
+    const DEFAULT_SIZE: f32 = 100.0;
+    const GIZMO_LENGTH: f32 = 60.0;
+    pub fn new(
+        reference_cub: Rc<RefCell<Vec<Point3<f32>>>>,
+        compute_solution: &'a Option<ComputeSolution<f32>>,
+        axis_data: Rc<RefCell<AxisData>>,
+        on_points_move: impl Fn() -> M + 'a,
+    ) -> Self {
+        ComputeCameraPoseVanishingPoints {
+            width: Length::Fixed(Self::DEFAULT_SIZE),
+            height: Length::Fixed(Self::DEFAULT_SIZE),
+            compute_solution: RefCell::new(compute_solution.clone()),
+            message_: PhantomData,
+            renderer_: PhantomData,
+            theme_: PhantomData,
+            referece_cub_cache: geometry::Cache::default(),
+            axis_lines_cache: geometry::Cache::default(),
+            horizon_cache: geometry::Cache::default(),
+            mesh_cache: geometry::Cache::default(),
+            show_horizon: false,
+            reference_labels: Vec::new(),
+            mesh: None,
+            occluders: Rc::default(),
+            hidden_lines: HiddenLines::default(),
+            image_size: Size::default(),
+            reference_cub,
+            axis_data,
+            on_points_move: Box::new(on_points_move),
+        }
+    }
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    /// Sets the height of the [`Canvas`].
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = height.into();
+        self
+    }
+
+    /// Draws the horizon line, vanishing points and principal point of the solution.
+    pub fn show_horizon(mut self, show_horizon: bool) -> Self {
+        self.show_horizon = show_horizon;
+        self
+    }
+
+    /// Labels drawn with the reference geometry, at the tips of the axis arrows.
+    pub fn reference_labels(mut self, reference_labels: Vec<ReferenceLabel>) -> Self {
+        self.reference_labels = reference_labels;
+        self
+    }
+
+    /// Faces hiding the edges of the reference geometry and of the mesh.
+    pub fn hidden_lines(
+        mut self,
+        hidden_lines: HiddenLines,
+        occluders: Rc<Vec<Vec<Vector3<f32>>>>,
+    ) -> Self {
+        self.hidden_lines = hidden_lines;
+        self.occluders = occluders;
+        self
+    }
+
+    /// Reference mesh drawn as a wireframe.
+    pub fn mesh(mut self, mesh: Option<Rc<Mesh>>) -> Self {
+        self.mesh = mesh;
+        self
+    }
+
+    /// Size of the image in pixels, for the arrow key steps and the handle coordinates.
+    pub fn image_size(mut self, image_size: Size) -> Self {
+        self.image_size = image_size;
+        self
+    }
+
+    /// Projected world origin and the tips of the fixed length screen space axis arrows.
+    fn origin_gizmo(&self, size: Size) -> Option<(Point, [Point; 3])> {
+        let compute_solution = self.compute_solution.borrow();
+        let compute_solution = compute_solution.as_ref()?;
+        let project = |location: &Vector3<f32>| {
+            compute_solution
+                .calculate_location_position_to_2d(location)
+                .map(|point| {
+                    let point = to_canvas(size, &point);
+                    Point::new(point.x, point.y)
+                })
+        };
+        if !compute_solution.is_in_front(&Vector3::zeros()) {
+            return None;
+        }
+        let origin = project(&Vector3::zeros())?;
+        let (camera_center, _) = compute_solution.camera_ray(&Vector2::zeros())?;
+        let length = camera_center.norm() * 0.05;
+        let mut tips = [origin; 3];
+        for (tip, axis) in tips.iter_mut().zip(EditAxis::ALL) {
+            let direction = project(&(axis.direction::<f32>()? * length))? - origin;
+            let norm = (direction.x * direction.x + direction.y * direction.y).sqrt();
+            if norm > 1e-3 {
+                *tip = origin + direction * (Self::GIZMO_LENGTH / norm);
+            }
+        }
+        Some((origin, tips))
+    }
+
+    /// Canvas positions of the reference cube corners in front of the camera, used to snap the origin.
+    fn reference_corners(&self, size: Size) -> Vec<Point> {
+        let compute_solution = self.compute_solution.borrow();
+        let Some(compute_solution) = compute_solution.as_ref() else {
+            return Vec::new();
+        };
+        let mut corners: Vec<Point> = Vec::new();
+        self.reference_cub
+            .borrow()
+            .iter()
+            .filter(|corner| compute_solution.is_in_front(&corner.coords))
+            .filter_map(|corner| compute_solution.calculate_location_position_to_2d(&corner.coords))
+            .for_each(|corner| {
+                let corner = to_canvas(size, &corner);
+                let corner = Point::new(corner.x, corner.y);
+                if !corners.iter().any(|item| item.distance(corner) < 1.0) {
+                    corners.push(corner);
+                }
+            });
+        corners
+    }
+
+    /// Handles in the order Tab walks through them.
+    fn handles(&self) -> Vec<Handle> {
+        let axis_data = self.axis_data.borrow();
+        let mut handles: Vec<Handle> = (0..axis_data.axis_lines.len())
+            .flat_map(|index| [Handle::LineEnd(index, false), Handle::LineEnd(index, true)])
+            .collect();
+        handles.push(Handle::ControlPoint);
+        if axis_data.reference_distance.is_some() {
+            handles.extend([Handle::ReferenceEnd(false), Handle::ReferenceEnd(true)]);
+        }
+        handles
+    }
+
+    /// Position of `handle` relative to the image, the control point sits at the projected origin.
+    fn handle_position(&self, handle: Handle, size: Size) -> Option<Point> {
+        if let Handle::ControlPoint = handle
+            && let Some((origin, _)) = self.origin_gizmo(size)
+        {
+            return Some(scale_point(Vector::new(origin.x, origin.y), size));
+        }
+        let axis_data = self.axis_data.borrow();
+        match handle {
+            Handle::ControlPoint => Some(axis_data.control_point),
+            Handle::LineEnd(index, end) => axis_data
+                .axis_lines
+                .get(index)
+                .map(|line| if end { line.1 } else { line.0 }),
+            Handle::ReferenceEnd(end) => axis_data.reference_distance.as_ref().map(|reference| {
+                if end {
+                    reference.line.1
+                } else {
+                    reference.line.0
+                }
+            }),
+        }
+    }
+
+    fn set_handle_position(&self, handle: Handle, position: Point) {
+        let mut axis_data = self.axis_data.borrow_mut();
+        match handle {
+            Handle::ControlPoint => {
+                // the moved origin sits exactly at the control point
+                axis_data.custom_origin_translation = None;
+                axis_data.control_point = position;
+            }
+            Handle::LineEnd(index, false) => axis_data.axis_lines[index].0 = position,
+            Handle::LineEnd(index, true) => axis_data.axis_lines[index].1 = position,
+            Handle::ReferenceEnd(end) => {
+                if let Some(reference) = axis_data.reference_distance.as_mut() {
+                    if end {
+                        reference.line.1 = position;
+                    } else {
+                        reference.line.0 = position;
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_inner(
+        &self,
+        state: &mut State,
+        event: &Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Status {
+        let Some(cursor) = cursor.position_over(bounds) else {
+            return Status::Ignored;
+        };
+        let adjusted_cursor = cursor - bounds.position();
+        let scale_cursor = scale_point(adjusted_cursor, bounds.size());
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let cursor = Point::new(adjusted_cursor.x, adjusted_cursor.y);
+                let gizmo = self.origin_gizmo(bounds.size());
+                if let Some((_, tips)) = gizmo
+                    && let Some(axis) = tips.iter().position(|tip| cursor.distance(*tip) < 10.0)
+                {
+                    let mut axis_data = self.axis_data.borrow_mut();
+                    match axis {
+                        0 => axis_data.flip.0 = !axis_data.flip.0,
+                        1 => axis_data.flip.1 = !axis_data.flip.1,
+                        _ => axis_data.flip.2 = !axis_data.flip.2,
+                    }
+                    state.publish = true;
+                    return Status::Captured;
+                }
+                let axis_data = self.axis_data.borrow();
+                let control_point = match gizmo {
+                    Some((origin, _)) => origin,
+                    None => scale_point_to_canvas(&axis_data.control_point, bounds.size()),
+                };
+                let reference_end = axis_data.reference_distance.as_ref().and_then(|reference| {
+                    if cursor.distance(scale_point_to_canvas(&reference.line.0, bounds.size()))
+                        < 10.0
+                    {
+                        Some(Handle::ReferenceEnd(false))
+                    } else if cursor
+                        .distance(scale_point_to_canvas(&reference.line.1, bounds.size()))
+                        < 10.0
+                    {
+                        Some(Handle::ReferenceEnd(true))
+                    } else {
+                        None
+                    }
+                });
+                state.selected = if cursor.distance(control_point) < 10.0 {
+                    Some(Handle::ControlPoint)
+                } else if reference_end.is_some() {
+                    reference_end
+                } else {
+                    axis_data
+                        .axis_lines
+                        .iter()
+                        .enumerate()
+                        .find_map(|(index, (a, b))| {
+                            if cursor.distance(scale_point_to_canvas(a, bounds.size())) < 10.0 {
+                                Some(Handle::LineEnd(index, false))
+                            } else if cursor.distance(scale_point_to_canvas(b, bounds.size()))
+                                < 10.0
+                            {
+                                Some(Handle::LineEnd(index, true))
+                            } else {
+                                None
+                            }
+                        })
+                };
+                if let Some(Handle::ControlPoint) = state.selected {
+                    state.snap_targets = self.reference_corners(bounds.size());
+                }
+                if state.selected.is_some() {
+                    state.focused = state.selected;
+                }
+                if state.selected.is_some() {
+                    Status::Captured
+                } else {
+                    Status::Ignored
+                }
+            }
+
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if state.selected.take().is_some() {
+                    state.snap_targets.clear();
+                    self.axis_lines_cache.clear();
+                    Status::Captured
+                } else {
+                    Status::Ignored
+                }
+            }
+
+            Event::Mouse(mouse::Event::CursorMoved { position: _ }) => {
+                let Some(selected) = state.selected else {
+                    return Status::Ignored;
+                };
+                let position = match selected {
+                    Handle::ControlPoint => {
+                        // the dragged origin sits under the cursor or a snapped corner
+                        let cursor = Point::new(adjusted_cursor.x, adjusted_cursor.y);
+                        match state
+                            .snap_targets
+                            .iter()
+                            .find(|target| cursor.distance(**target) < 10.0)
+                        {
+                            Some(target) => {
+                                scale_point(Vector::new(target.x, target.y), bounds.size())
+                            }
+                            None => scale_cursor,
+                        }
+                    }
+                    _ => scale_cursor,
+                };
+                self.set_handle_position(selected, position);
+                self.axis_lines_cache.clear();
+                Status::Captured
+            }
+
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
+                if let keyboard::Key::Named(Named::Tab) = key.as_ref() {
+                    let handles = self.handles();
+                    let current = state
+                        .focused
+                        .and_then(|focused| handles.iter().position(|item| *item == focused));
+                    state.focused = cycle(current, handles.len(), modifiers.shift())
+                        .map(|index| handles[index]);
+                    self.axis_lines_cache.clear();
+                    return Status::Captured;
+                }
+                let (Some(focused), Some(offset)) = (
+                    state.focused,
+                    nudge_offset(key, *modifiers, self.image_size),
+                ) else {
+                    return Status::Ignored;
+                };
+                let Some(position) = self.handle_position(focused, bounds.size()) else {
+                    return Status::Ignored;
+                };
+                self.set_handle_position(focused, position + offset);
+                self.axis_lines_cache.clear();
+                state.publish = true;
+                Status::Captured
+            }
+            _ => Status::Ignored,
+        }
+    }
+
+    fn draw_inner(
+        &self,
+        state: &State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Renderer::Geometry> {
+        let referece_cub_cache = self
+            .referece_cub_cache
+            .draw(renderer, bounds.size(), |frame| {
+                if self.compute_solution.borrow().as_ref().is_none() {
+                    return;
+                }
+
+                draw_wireframe(
+                    frame,
+                    self.compute_solution.borrow().as_ref().unwrap(),
+                    &self.reference_cub.borrow(),
+                    &self.occluders,
+                    self.hidden_lines,
+                    bounds.size(),
+                    REFERENCE_COLOR,
+                );
+                draw_reference_labels(
+                    frame,
+                    self.compute_solution.borrow().as_ref().unwrap(),
+                    &self.reference_labels,
+                    bounds.size(),
+                );
+            });
+
+        let axis_lines = self
+            .axis_lines_cache
+            .draw(renderer, bounds.size(), |frame| {
+                let axis_data = self.axis_data.borrow();
+                axis_data
+                    .axis_lines
+                    .iter()
+                    .enumerate()
+                    .for_each(|(index, line)| {
+                        let color = AXIS_COLORS[index / 2];
+                        let a = scale_point_to_canvas(&line.0, bounds.size());
+                        let b = scale_point_to_canvas(&line.1, bounds.size());
+
+                        if let Some(extension) =
+                            get_extension_for_line_within_bounds(&(a, b), bounds.size())
+                        {
+                            let mut builder = canvas::path::Builder::new();
+                            builder.move_to(extension[0]);
+                            builder.line_to(extension[1]);
+                            frame.stroke(
+                                &builder.build(),
+                                Stroke {
+                                    style: canvas::Style::Solid(Color { a: 0.3, ..color }),
+                                    width: 1.0,
+                                    ..Stroke::default()
+                                },
+                            );
+                        }
+
+                        let mut builder = canvas::path::Builder::new();
+                        builder.move_to(a);
+                        builder.line_to(b);
+                        builder.circle(a, 5.0);
+                        builder.circle(b, 5.0);
+                        let width = match state.selected {
+                            Some(Handle::LineEnd(selected, _)) if selected == index => 3.0,
+                            _ => 2.0,
+                        };
+                        frame.stroke(
+                            &builder.build(),
+                            Stroke {
+                                style: canvas::Style::Solid(color),
+                                width,
+                                ..Stroke::default()
+                            },
+                        );
+                    });
+
+                if let Some(reference) = axis_data.reference_distance.as_ref() {
+                    let a = scale_point_to_canvas(&reference.line.0, bounds.size());
+                    let b = scale_point_to_canvas(&reference.line.1, bounds.size());
+                    let mut builder = canvas::path::Builder::new();
+                    builder.move_to(a);
+                    builder.line_to(b);
+                    builder.circle(a, 5.0);
+                    builder.circle(b, 5.0);
+                    let color = match reference.axis {
+                        EditAxis::EditX => AXIS_COLORS[0],
+                        EditAxis::EditY => AXIS_COLORS[1],
+                        _ => AXIS_COLORS[2],
+                    };
+                    frame.stroke(
+                        &builder.build(),
+                        Stroke {
+                            style: canvas::Style::Solid(Color::from_rgba(0.95, 0.6, 0.1, 0.9)),
+                            width: 3.0,
+                            ..Stroke::default()
+                        },
+                    );
+                    let middle = Point::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+                    frame.fill_rectangle(
+                        Point::new(middle.x + 2.0, middle.y + 2.0),
+                        Size::new(100.0, 15.0),
+                        Fill {
+                            style: canvas::Style::Solid(Color::from_rgba(0.3, 0.3, 0.3, 0.9)),
+                            ..Fill::default()
+                        },
+                    );
+                    frame.fill_text(Text {
+                        content: format!(
+                            "{} {:.2} {}",
+                            reference.axis, reference.length, axis_data.reference_distance_unit
+                        ),
+                        position: Point::new(middle.x + 4.0, middle.y + 4.0),
+                        color,
+                        size: Pixels(10.0),
+                        ..Default::default()
+                    });
+                }
+
+                for target in state.snap_targets.iter() {
+                    frame.stroke_rectangle(
+                        Point::new(target.x - 4.0, target.y - 4.0),
+                        Size::new(8.0, 8.0),
+                        Stroke {
+                            style: canvas::Style::Solid(Color::from_rgba(0.8, 0.8, 0.2, 0.6)),
+                            width: 1.0,
+                            ..Stroke::default()
+                        },
+                    );
+                }
+
+                if let Some((origin, tips)) = self.origin_gizmo(bounds.size()) {
+                    let flip = [axis_data.flip.0, axis_data.flip.1, axis_data.flip.2];
+                    for ((tip, axis), (color, flipped)) in tips
+                        .iter()
+                        .zip(EditAxis::ALL)
+                        .zip(AXIS_COLORS.iter().zip(flip))
+                    {
+                        let mut builder = canvas::path::Builder::new();
+                        builder.move_to(origin);
+                        builder.line_to(*tip);
+                        builder.circle(*tip, 8.0);
+                        frame.stroke(
+                            &builder.build(),
+                            Stroke {
+                                style: canvas::Style::Solid(*color),
+                                width: 2.0,
+                                ..Stroke::default()
+                            },
+                        );
+                        frame.fill_text(Text {
+                            content: if flipped {
+                                format!("-{axis}")
+                            } else {
+                                axis.to_string()
+                            },
+                            position: Point::new(tip.x - 4.0, tip.y - 6.0),
+                            color: *color,
+                            size: Pixels(11.0),
+                            ..Default::default()
+                        });
+                    }
+                }
+
+                let control_point = scale_point_to_canvas(&axis_data.control_point, bounds.size());
+                let mut builder = canvas::path::Builder::new();
+                builder.circle(control_point, 6.0);
+                builder.move_to(Point::new(control_point.x - 10.0, control_point.y));
+                builder.line_to(Point::new(control_point.x + 10.0, control_point.y));
+                builder.move_to(Point::new(control_point.x, control_point.y - 10.0));
+                builder.line_to(Point::new(control_point.x, control_point.y + 10.0));
+                frame.stroke(
+                    &builder.build(),
+                    Stroke {
+                        style: canvas::Style::Solid(Color::from_rgba(0.8, 0.8, 0.2, 0.8)),
+                        width: 2.0,
+                        ..Stroke::default()
+                    },
+                );
+
+                if let Some(position) = state
+                    .focused
+                    .and_then(|focused| self.handle_position(focused, bounds.size()))
+                {
+                    draw_handle_position(frame, position, bounds.size(), self.image_size);
+                }
+            });
+
+        let horizon = self.horizon_cache.draw(renderer, bounds.size(), |frame| {
+            let compute_solution = self.compute_solution.borrow();
+            let Some(compute_solution) = compute_solution.as_ref() else {
+                return;
+            };
+            if !self.show_horizon {
+                return;
+            }
+            let vanishing_points = vanishing_points_to_canvas(compute_solution, bounds.size());
+            draw_horizon(frame, compute_solution, &vanishing_points, bounds.size());
+        });
+
+        let mesh = self.mesh_cache.draw(renderer, bounds.size(), |frame| {
+            let compute_solution = self.compute_solution.borrow();
+            if let (Some(compute_solution), Some(mesh)) = (compute_solution.as_ref(), &self.mesh) {
+                draw_mesh(
+                    frame,
+                    compute_solution,
+                    mesh,
+                    &self.occluders,
+                    self.hidden_lines,
+                    bounds.size(),
+                    false,
+                );
+            }
+        });
+
+        vec![mesh, horizon, axis_lines, referece_cub_cache]
+    }
+}
+
+impl<Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for ComputeCameraPoseVanishingPoints<'_, Message, Theme, Renderer>
+where
+    Renderer: geometry::Renderer,
+{
+    fn tag(&self) -> tree::Tag {
+        struct Tag<T>(T);
+        tree::Tag::of::<Tag<State>>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn size(&self) -> Size<Length> {
+        Size {
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _tree: &mut Tree,
+        _renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        layout::atomic(limits, self.width, self.height)
+    }
+
+    fn update(
+        &mut self,
+        tree: &mut Tree,
+        event: &Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _renderer: &Renderer,
+        _clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        _viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+
+        let state = tree.state.downcast_mut::<State>();
+
+        let event_status = self.update_inner(state, event, bounds, cursor);
+        if let Status::Captured = event_status {
+            shell.capture_event();
+            if std::mem::take(&mut state.publish) {
+                shell.publish((self.on_points_move)());
+            } else if let Event::Mouse(mouse::Event::CursorMoved { position: _ }) = event {
+                shell.publish((self.on_points_move)());
+            } else {
+                shell.request_redraw();
+            }
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        _tree: &Tree,
+        _layout: Layout<'_>,
+        _cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+        _renderer: &Renderer,
+    ) -> mouse::Interaction {
+        mouse::Interaction::default()
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        _style: &Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+
+        if bounds.width < 1.0 || bounds.height < 1.0 {
+            return;
+        }
+        let state = tree.state.downcast_ref::<State>();
+
+        renderer.with_translation(Vector::new(bounds.x, bounds.y), |renderer| {
+            let layers = self.draw_inner(state, renderer, theme, bounds, cursor);
+
+            for layer in layers {
+                renderer.draw_geometry(layer);
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Handle {
+    ControlPoint,
+    /// line index and which end, `false` for the first point
+    LineEnd(usize, bool),
+    ReferenceEnd(bool),
+}
+
+#[derive(Default, Clone)]
+pub struct State {
+    selected: Option<Handle>,
+    /// Handle moved by the arrow keys, the last one dragged or reached with Tab.
+    focused: Option<Handle>,
+    snap_targets: Vec<Point>,
+    publish: bool,
+}
+
+impl<'a, Message, Theme, Renderer>
+    From<ComputeCameraPoseVanishingPoints<'a, Message, Theme, Renderer>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: 'a,
+    Renderer: 'a + geometry::Renderer,
+{
+    fn from(
+        axis_decoration: ComputeCameraPoseVanishingPoints<'a, Message, Theme, Renderer>,
+    ) -> Element<'a, Message, Theme, Renderer> {
+        Element::new(axis_decoration)
+    }
+}